@@ -1,13 +1,19 @@
 mod bucket;
 mod cache;
 mod cat;
+mod cleanup;
+mod depends;
 mod info;
 mod list;
 mod neco;
+mod plan;
 mod search;
+mod status;
 mod update;
 
+use crate::roots::Roots;
 use crate::run::Run;
+use crate::run::RunRoots;
 
 #[derive(clap::Subcommand)]
 pub enum Commands {
@@ -21,6 +27,12 @@ pub enum Commands {
 	/// Show an app's manifest
 	Cat(cat::CatCommand),
 
+	/// Remove old versions of apps
+	Cleanup(cleanup::CleanupCommand),
+
+	/// Show an app's dependencies
+	Depends(depends::DependsCommand),
+
 	/// Show an app's info
 	Info(info::InfoCommand),
 
@@ -30,24 +42,35 @@ pub enum Commands {
 	#[command(hide = true)]
 	Neco(neco::NecoCommand),
 
+	/// Show what installing or uninstalling apps would do, without changing anything
+	#[command(subcommand)]
+	Plan(plan::PlanCommands),
+
 	/// Search for an app
 	Search(search::SearchCommand),
 
-	/// Update all buckets
+	/// Show installed apps that are outdated
+	Status(status::StatusCommand),
+
+	/// Update all buckets, or show the plan for updating installed apps
 	Update(update::UpdateCommand),
 }
 
-impl Run for Commands {
-	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+impl RunRoots for Commands {
+	fn run(&self, roots: &mut Roots) -> eyre::Result<()> {
 		match self {
-			Self::Bucket(cmds) => cmds.run(shovel),
-			Self::Cache(cmds) => cmds.run(shovel),
-			Self::Cat(cmd) => cmd.run(shovel),
-			Self::Info(cmd) => cmd.run(shovel),
-			Self::List(cmd) => cmd.run(shovel),
-			Self::Neco(cmd) => cmd.run(shovel),
-			Self::Search(cmd) => cmd.run(shovel),
-			Self::Update(cmd) => cmd.run(shovel),
+			Self::Bucket(cmds) => cmds.run(roots.current()),
+			Self::Cache(cmds) => cmds.run(roots.current()),
+			Self::Cat(cmd) => cmd.run(roots.current()),
+			Self::Cleanup(cmd) => cmd.run(roots.current()),
+			Self::Depends(cmd) => cmd.run(roots.current()),
+			Self::Info(cmd) => cmd.run(roots.current()),
+			Self::List(cmd) => cmd.run(roots),
+			Self::Neco(cmd) => cmd.run(roots.current()),
+			Self::Plan(cmds) => cmds.run(roots.current()),
+			Self::Search(cmd) => cmd.run(roots.current()),
+			Self::Status(cmd) => cmd.run(roots),
+			Self::Update(cmd) => cmd.run(roots.current()),
 		}
 	}
 }
@@ -1,7 +1,14 @@
 mod add;
 mod known;
 mod list;
+mod pin;
 mod remove;
+mod rename;
+mod set_url;
+mod trust;
+mod unpin;
+mod unshallow;
+mod untrust;
 mod verify;
 
 use crate::run::Run;
@@ -15,6 +22,13 @@ pub enum BucketCommands {
 	#[clap(visible_alias("rm"))]
 	Remove(remove::RemoveCommand),
 
+	/// Rename a bucket
+	#[clap(visible_alias("mv"))]
+	Rename(rename::RenameCommand),
+
+	/// Set the URL a bucket pulls from
+	SetUrl(set_url::SetUrlCommand),
+
 	/// List all buckets
 	List(list::ListCommand),
 
@@ -23,6 +37,21 @@ pub enum BucketCommands {
 
 	/// Verify apps in a bucket
 	Verify(verify::VerifyCommand),
+
+	/// Fetch the full history of a shallow bucket
+	Unshallow(unshallow::UnshallowCommand),
+
+	/// Pin a bucket to a branch, tag or commit
+	Pin(pin::PinCommand),
+
+	/// Make a pinned bucket follow its upstream branch again
+	Unpin(unpin::UnpinCommand),
+
+	/// Only accept updates to a bucket with commits signed by a key
+	Trust(trust::TrustCommand),
+
+	/// Stop verifying the commit signatures of a bucket
+	Untrust(untrust::UntrustCommand),
 }
 
 impl Run for BucketCommands {
@@ -30,9 +59,16 @@ impl Run for BucketCommands {
 		match self {
 			Self::Add(cmd) => cmd.run(shovel),
 			Self::Remove(cmd) => cmd.run(shovel),
+			Self::Rename(cmd) => cmd.run(shovel),
+			Self::SetUrl(cmd) => cmd.run(shovel),
 			Self::List(cmd) => cmd.run(shovel),
 			Self::Known(cmd) => cmd.run(shovel),
 			Self::Verify(cmd) => cmd.run(shovel),
+			Self::Unshallow(cmd) => cmd.run(shovel),
+			Self::Pin(cmd) => cmd.run(shovel),
+			Self::Unpin(cmd) => cmd.run(shovel),
+			Self::Trust(cmd) => cmd.run(shovel),
+			Self::Untrust(cmd) => cmd.run(shovel),
 		}
 	}
 }
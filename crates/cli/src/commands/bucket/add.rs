@@ -1,18 +1,35 @@
+use std::path::Path;
+
 use eyre::WrapErr;
 use owo_colors::OwoColorize;
+use shovel::bucket::CloneOptions;
 
 use crate::commands::bucket::known;
 use crate::run::Run;
 use crate::tracker::Tracker;
 
-fn add_bucket(shovel: &mut shovel::Shovel, name: &str, url: &str) -> shovel::Result<()> {
+fn add_bucket(
+	shovel: &mut shovel::Shovel,
+	name: &str,
+	url: &str,
+	command: &AddCommand,
+) -> shovel::Result<()> {
 	let multi_progress = indicatif::MultiProgress::new();
 
 	let tracker = Tracker::new(multi_progress);
-	let mut builder = tracker.repo_builder(name.to_owned());
+
+	let mut options = CloneOptions::new();
+	options
+		.fetch_options(tracker.fetch_options(name.to_owned()))
+		.checkout_builder(tracker.checkout_builder(name.to_owned()))
+		.sparse(command.sparse);
+
+	if command.shallow {
+		options.depth(1);
+	}
 
 	// Add the bucket.
-	shovel.buckets.add(name, url, Some(&mut builder))?;
+	shovel.buckets.add(name, url, options)?;
 
 	Ok(())
 }
@@ -24,20 +41,53 @@ pub struct AddCommand {
 
 	/// The bucket URL.
 	/// Required if the bucket name is not known - run `shovel bucket known` for details.
+	///
+	/// A local directory that is not a Git repository is added as is, without cloning.
 	url: Option<String>,
+
+	/// Clone only the latest commit.
+	/// Features that need the bucket's history are unavailable until `shovel bucket unshallow` is run.
+	#[arg(long)]
+	shallow: bool,
+
+	/// Check out only the manifests and scripts.
+	#[arg(long)]
+	sparse: bool,
 }
 
 impl Run for AddCommand {
 	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
-		let url = self
-			.url
-			.as_deref()
+		let url = match &self.url {
+			Some(url) => url.clone(),
 			// Attempt to get the bucket URL if it's known.
-			.or_else(|| known::bucket(&self.name))
-			.ok_or_else(|| eyre::eyre!("URL was not specified, or bucket name is unknown"))?;
+			None => known::load(shovel)?
+				.get(&self.name)
+				.map(|bucket| bucket.url.clone())
+				.ok_or_else(|| eyre::eyre!("URL was not specified, or bucket name is unknown"))?,
+		};
+		let url = url.as_str();
+
+		// A plain directory of manifests, i.e., on a network share, cannot be cloned.
+		// Bare repositories do not have a `.git` directory, so Git is asked instead.
+		let is_dir = Path::new(url).is_dir() && git2::Repository::open(url).is_err();
+
+		if is_dir {
+			shovel
+				.buckets
+				.add_dir(&self.name, url)
+				.wrap_err_with(|| format!("Failed to add bucket {}", self.name))?;
+
+			println!(
+				"Added bucket {} from local directory {}",
+				self.name.bold(),
+				url.green()
+			);
+
+			return Ok(());
+		}
 
 		// Add the bucket.
-		add_bucket(shovel, &self.name, url)
+		add_bucket(shovel, &self.name, url, self)
 			.wrap_err_with(|| format!("Failed to add bucket {}", self.name))?;
 
 		println!("Added bucket {} from {}", self.name.bold(), url.green());
@@ -1,28 +1,41 @@
+use eyre::WrapErr;
+use shovel::bucket::KnownBuckets;
+
 use crate::run::Run;
 use crate::util;
 
-include!(concat!(env!("OUT_DIR"), "/buckets.rs"));
-
-/// Returns the URL of the known bucket by name.
-pub fn bucket(name: &str) -> Option<&'static str> {
-	KNOWN_BUCKETS.get(name).copied()
+/// Loads the known buckets, naming the known buckets file if it cannot be loaded.
+pub fn load(shovel: &shovel::Shovel) -> eyre::Result<KnownBuckets> {
+	shovel
+		.known_buckets()
+		.wrap_err_with(|| match &shovel.config().known_buckets_file {
+			Some(path) => format!("Failed to load known buckets from {path}"),
+			None => "Failed to load known buckets".to_owned(),
+		})
 }
 
 #[derive(tabled::Tabled)]
 #[tabled(rename_all = "pascal")]
-struct KnownInfo {
-	name: &'static str,
-	source: &'static str,
+struct KnownInfo<'a> {
+	name: &'a str,
+	source: &'a str,
+	description: &'a str,
+	origin: String,
 }
 
 #[derive(clap::Args)]
 pub struct KnownCommand {}
 
 impl Run for KnownCommand {
-	fn run(&self, _shovel: &mut shovel::Shovel) -> eyre::Result<()> {
-		let known = KNOWN_BUCKETS
-			.into_iter()
-			.map(|(name, source)| KnownInfo { name, source });
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		let known = load(shovel)?;
+
+		let known = known.iter().map(|(name, bucket)| KnownInfo {
+			name,
+			source: &bucket.url,
+			description: bucket.description.as_deref().unwrap_or_default(),
+			origin: bucket.origin.to_string(),
+		});
 
 		println!("\n{}\n", util::tableify(known, false));
 
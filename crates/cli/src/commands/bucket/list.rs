@@ -8,6 +8,7 @@ use crate::util;
 struct BucketInfo {
 	name: String,
 	source: String,
+	pin: String,
 	updated: String,
 	manifests: usize,
 }
@@ -16,13 +17,18 @@ impl BucketInfo {
 	fn new(bucket: &bucket::Bucket) -> shovel::Result<Self> {
 		let name = bucket.name();
 		let source = bucket.url()?;
-		let commit = bucket.commit()?;
-		let updated = shovel::Timestamp::from(commit.time()).to_string();
+		let pin = bucket.pin()?.map(|pin| pin.to_string()).unwrap_or_default();
+		// Directory buckets do not have commits.
+		let updated = bucket
+			.commit()?
+			.map(|commit| commit.time.to_string())
+			.unwrap_or_default();
 		let manifests = bucket.manifests()?.count();
 
 		Ok(Self {
 			name,
 			source,
+			pin,
 			updated,
 			manifests,
 		})
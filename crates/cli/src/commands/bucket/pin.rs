@@ -0,0 +1,66 @@
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+use shovel::bucket::Pin;
+
+use crate::run::Run;
+
+#[derive(clap::Args)]
+pub struct PinCommand {
+	/// The existing bucket's name.
+	name: String,
+
+	#[command(flatten)]
+	target: PinTarget,
+}
+
+#[derive(clap::Args)]
+#[group(required = true, multiple = false)]
+struct PinTarget {
+	/// Follow a branch instead of the upstream branch.
+	#[arg(long)]
+	branch: Option<String>,
+
+	/// Follow a tag, moving with it.
+	#[arg(long)]
+	tag: Option<String>,
+
+	/// Stay at a commit.
+	#[arg(long)]
+	commit: Option<String>,
+}
+
+impl PinTarget {
+	fn pin(&self) -> eyre::Result<Pin> {
+		let pin = match (&self.branch, &self.tag, &self.commit) {
+			(Some(branch), _, _) => Pin::Branch(branch.clone()),
+			(_, Some(tag), _) => Pin::Tag(tag.clone()),
+			(_, _, Some(commit)) => format!("commit:{commit}").parse()?,
+			_ => unreachable!("clap requires one of --branch, --tag or --commit"),
+		};
+
+		Ok(pin)
+	}
+}
+
+impl Run for PinCommand {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		let pin = self.target.pin()?;
+
+		let bucket = shovel
+			.buckets
+			.open(&self.name)
+			.wrap_err_with(|| format!("Failed to open bucket {}", self.name))?;
+
+		bucket
+			.set_pin(Some(&pin))
+			.wrap_err_with(|| format!("Failed to pin bucket {}", self.name))?;
+
+		println!(
+			"Pinned bucket {} to {}, it will move on the next update",
+			self.name.bold(),
+			pin.bold()
+		);
+
+		Ok(())
+	}
+}
@@ -1,5 +1,6 @@
 use eyre::WrapErr;
 use owo_colors::OwoColorize;
+use shovel::bucket;
 
 use crate::run::Run;
 
@@ -7,14 +8,25 @@ use crate::run::Run;
 pub struct RemoveCommand {
 	/// The existing bucket's name.
 	name: String,
+
+	/// Remove the bucket even if installed apps came from it.
+	#[arg(long)]
+	force: bool,
 }
 
 impl Run for RemoveCommand {
 	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
-		shovel
-			.buckets
-			.remove(&self.name)
-			.wrap_err_with(|| format!("Failed to remove bucket {}", self.name))?;
+		let result = shovel.remove_bucket(&self.name, self.force);
+
+		if let Err(shovel::Error::Bucket(bucket::Error::InUse(apps))) = &result {
+			eyre::bail!(
+				"Bucket {} is used by installed apps: {}. Use --force to remove it anyway, the apps will no longer be updated.",
+				self.name,
+				apps.join(", ")
+			);
+		}
+
+		result.wrap_err_with(|| format!("Failed to remove bucket {}", self.name))?;
 
 		println!("Removed bucket {}", self.name.bold());
 
@@ -0,0 +1,44 @@
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+
+use crate::run::Run;
+
+#[derive(clap::Args)]
+pub struct RenameCommand {
+	/// The existing bucket's name.
+	name: String,
+
+	/// The new name of the bucket.
+	new_name: String,
+}
+
+impl Run for RenameCommand {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		let apps = shovel
+			.rename_bucket(&self.name, &self.new_name)
+			.wrap_err_with(|| format!("Failed to rename bucket {}", self.name))?;
+
+		println!(
+			"Renamed bucket {} to {}",
+			self.name.bold(),
+			self.new_name.bold()
+		);
+
+		if !apps.is_empty() {
+			println!("Updated the bucket of installed apps: {}", apps.join(", "));
+		}
+
+		if shovel.config().bucket_priority.contains(&self.name) {
+			println!(
+				"{}",
+				format!(
+					"The bucket priority in the config still refers to {}",
+					self.name
+				)
+				.yellow()
+			);
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,30 @@
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+
+use crate::run::Run;
+
+#[derive(clap::Args)]
+pub struct SetUrlCommand {
+	/// The existing bucket's name.
+	name: String,
+
+	/// The new Git URL of the bucket.
+	url: String,
+}
+
+impl Run for SetUrlCommand {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		shovel
+			.buckets
+			.set_url(&self.name, &self.url)
+			.wrap_err_with(|| format!("Failed to set the URL of bucket {}", self.name))?;
+
+		println!(
+			"Bucket {} now pulls from {}",
+			self.name.bold(),
+			self.url.green()
+		);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::Path;
+
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+use shovel::bucket::TrustedKey;
+
+use crate::run::Run;
+
+#[derive(clap::Args)]
+pub struct TrustCommand {
+	/// The existing bucket's name.
+	name: String,
+
+	#[command(flatten)]
+	key: KeyArgs,
+}
+
+#[derive(clap::Args)]
+#[group(required = true, multiple = false)]
+struct KeyArgs {
+	/// Trust an SSH public key, or the key in a public key file (i.e., `~/.ssh/id_ed25519.pub`).
+	#[arg(long, value_name = "KEY")]
+	ssh: Option<String>,
+
+	/// Trust a GPG key by its fingerprint. The key must be in your keyring.
+	#[arg(long, value_name = "FINGERPRINT")]
+	gpg: Option<String>,
+}
+
+impl KeyArgs {
+	fn key(&self) -> eyre::Result<TrustedKey> {
+		let key = match (&self.ssh, &self.gpg) {
+			(Some(ssh), _) => {
+				let path = Path::new(ssh);
+
+				let key = if path.is_file() {
+					fs::read_to_string(path)
+						.wrap_err_with(|| format!("Failed to read {}", path.display()))?
+				} else {
+					ssh.clone()
+				};
+
+				format!("ssh:{key}").parse()?
+			}
+			(_, Some(gpg)) => format!("gpg:{gpg}").parse()?,
+			_ => unreachable!("clap requires one of --ssh or --gpg"),
+		};
+
+		Ok(key)
+	}
+}
+
+impl Run for TrustCommand {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		let key = self.key.key()?;
+
+		let bucket = shovel
+			.buckets
+			.open(&self.name)
+			.wrap_err_with(|| format!("Failed to open bucket {}", self.name))?;
+
+		let mut keys = bucket.trusted_keys()?;
+
+		if keys.contains(&key) {
+			println!("Bucket {} already trusts {}", self.name.bold(), key.bold());
+			return Ok(());
+		}
+
+		keys.push(key);
+
+		bucket
+			.set_trusted_keys(&keys)
+			.wrap_err_with(|| format!("Failed to trust key for bucket {}", self.name))?;
+
+		println!(
+			"Bucket {} now only accepts commits signed by {} trusted key(s)",
+			self.name.bold(),
+			keys.len()
+		);
+
+		Ok(())
+	}
+}
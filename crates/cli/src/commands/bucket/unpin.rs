@@ -0,0 +1,35 @@
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+
+use crate::run::Run;
+
+#[derive(clap::Args)]
+pub struct UnpinCommand {
+	/// The existing bucket's name.
+	name: String,
+}
+
+impl Run for UnpinCommand {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		let bucket = shovel
+			.buckets
+			.open(&self.name)
+			.wrap_err_with(|| format!("Failed to open bucket {}", self.name))?;
+
+		if bucket.pin()?.is_none() {
+			println!("Bucket {} is not pinned", self.name.bold());
+			return Ok(());
+		}
+
+		bucket
+			.set_pin(None)
+			.wrap_err_with(|| format!("Failed to unpin bucket {}", self.name))?;
+
+		println!(
+			"Unpinned bucket {}, it will follow its upstream branch on the next update",
+			self.name.bold()
+		);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,35 @@
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+
+use crate::run::Run;
+use crate::tracker::Tracker;
+
+#[derive(clap::Args)]
+pub struct UnshallowCommand {
+	/// The existing bucket's name.
+	name: String,
+}
+
+impl Run for UnshallowCommand {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		let bucket = shovel
+			.buckets
+			.open(&self.name)
+			.wrap_err_with(|| format!("Failed to open bucket {}", self.name))?;
+
+		if !bucket.is_shallow() {
+			println!("Bucket {} already has its full history", self.name.bold());
+			return Ok(());
+		}
+
+		let tracker = Tracker::new(indicatif::MultiProgress::new());
+
+		bucket
+			.unshallow(Some(tracker.fetch_options(self.name.clone())))
+			.wrap_err_with(|| format!("Failed to fetch the history of bucket {}", self.name))?;
+
+		println!("Fetched the full history of bucket {}", self.name.bold());
+
+		Ok(())
+	}
+}
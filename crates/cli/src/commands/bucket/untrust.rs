@@ -0,0 +1,35 @@
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+
+use crate::run::Run;
+
+#[derive(clap::Args)]
+pub struct UntrustCommand {
+	/// The existing bucket's name.
+	name: String,
+}
+
+impl Run for UntrustCommand {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		let bucket = shovel
+			.buckets
+			.open(&self.name)
+			.wrap_err_with(|| format!("Failed to open bucket {}", self.name))?;
+
+		if bucket.trusted_keys()?.is_empty() {
+			println!("Bucket {} has no trusted keys", self.name.bold());
+			return Ok(());
+		}
+
+		bucket
+			.set_trusted_keys(&[])
+			.wrap_err_with(|| format!("Failed to untrust keys for bucket {}", self.name))?;
+
+		println!(
+			"Removed the trusted keys of bucket {}, its commits will no longer be verified",
+			self.name.bold()
+		);
+
+		Ok(())
+	}
+}
@@ -23,7 +23,7 @@ pub fn schema() -> &'static jsonschema::JSONSchema {
 /// Formats an iterator over JSON Schema errors.
 fn format_schema_errors(errors: jsonschema::ErrorIterator<'_>) -> Vec<String> {
 	errors
-		.map(|err| format!("{:?} for {}", err.kind.red(), err.instance_path.blue(),))
+		.map(|err| format!("{:?} for {}", err.kind.red(), err.instance_path.blue()))
 		.collect()
 }
 
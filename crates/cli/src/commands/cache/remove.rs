@@ -26,7 +26,7 @@ impl Run for RemoveCommand {
 			let keys = shovel
 				.cache
 				.iter()?
-				.filter(|key| apps.is_empty() || apps.get(&key.name).is_some());
+				.filter(|key| apps.is_empty() || apps.contains(&key.name));
 
 			for key in keys {
 				shovel.cache.remove(&key.name)?;
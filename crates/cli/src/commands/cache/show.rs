@@ -46,7 +46,7 @@ impl Run for ShowCommand {
 			.cache
 			.iter()?
 			// Filter apps not in the list.
-			.filter(|key| apps.is_empty() || apps.get(&key.name).is_some())
+			.filter(|key| apps.is_empty() || apps.contains(&key.name))
 			// Ignore cache infos with errors.
 			.filter_map(|key| ShowInfo::new(shovel, key).ok())
 			.collect();
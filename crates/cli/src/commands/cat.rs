@@ -3,6 +3,7 @@ use std::io;
 use shovel::bucket::Name;
 
 use crate::run::Run;
+use crate::util;
 
 #[derive(clap::Args)]
 pub struct CatCommand {
@@ -14,9 +15,11 @@ impl Run for CatCommand {
 	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
 		let name = Name::new(self.manifest.clone());
 
-		let mut opts = shovel::CatOptions::new(name, io::stdout());
+		let mut opts = shovel::CatOptions::new(name.clone(), io::stdout());
 
-		shovel.cat(&mut opts)?;
+		shovel
+			.cat(&mut opts)
+			.map_err(|err| util::suggest_apps(shovel, &name, err))?;
 
 		Ok(())
 	}
@@ -0,0 +1,69 @@
+use std::fs;
+
+use eyre::WrapErr;
+
+use crate::run::Run;
+use crate::util;
+
+#[derive(tabled::Tabled)]
+#[tabled(rename_all = "pascal")]
+struct CleanupInfo {
+	name: String,
+	path: String,
+}
+
+#[derive(clap::Args)]
+pub struct CleanupCommand {
+	/// The apps to clean up. If not specified, all apps are cleaned up.
+	apps: Vec<String>,
+
+	/// Show the versions that would be removed without removing them
+	#[arg(long)]
+	dry_run: bool,
+}
+
+impl Run for CleanupCommand {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		let apps = if self.apps.is_empty() {
+			shovel.apps.each()?.map(|(name, _)| name).collect()
+		} else {
+			self.apps.clone()
+		};
+
+		let mut infos = vec![];
+
+		for name in apps {
+			let versions = shovel
+				.apps
+				.old_versions(&name)
+				.wrap_err_with(|| format!("Failed to find old versions of {name}"))?;
+
+			for app in versions {
+				if !self.dry_run {
+					fs::remove_dir_all(app.dir())
+						.wrap_err_with(|| format!("Failed to remove {}", app.dir().display()))?;
+				}
+
+				infos.push(CleanupInfo {
+					name: name.clone(),
+					path: app.dir().display().to_string(),
+				});
+			}
+		}
+
+		if infos.is_empty() {
+			println!("Nothing to clean up.");
+			return Ok(());
+		}
+
+		let verb = if self.dry_run {
+			"Would remove"
+		} else {
+			"Removed"
+		};
+
+		println!("\n{verb}:\n{}\n", util::tableify(infos, false));
+
+		Ok(())
+	}
+}
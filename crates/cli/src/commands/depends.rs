@@ -0,0 +1,68 @@
+use std::fmt::Write;
+
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+use shovel::bucket::Name;
+use shovel::depends;
+
+use crate::run::Run;
+
+/// Writes a dependency tree, with each level of dependencies indented under its parent.
+fn write_tree(out: &mut String, node: &depends::Node, prefix: &str, last: bool, root: bool) {
+	let (branch, indent) = match (root, last) {
+		(true, _) => ("", ""),
+		(false, true) => ("└── ", "    "),
+		(false, false) => ("├── ", "│   "),
+	};
+
+	let kind = match node.kind {
+		depends::Kind::Helper => format!(" [{}]", node.kind).yellow().to_string(),
+		_ => String::new(),
+	};
+
+	// Writing to a string never fails.
+	writeln!(
+		out,
+		"{prefix}{branch}{} ({}){kind}",
+		node.name.bold(),
+		node.bucket.blue()
+	)
+	.unwrap();
+
+	let prefix = format!("{prefix}{indent}");
+	let count = node.dependencies.len();
+
+	for (i, dep) in node.dependencies.iter().enumerate() {
+		write_tree(out, dep, &prefix, i + 1 == count, false);
+	}
+}
+
+#[derive(clap::Args)]
+pub struct DependsCommand {
+	/// The app to show dependencies for. To specify a bucket, use the syntax `bucket/app`.
+	app: String,
+}
+
+impl Run for DependsCommand {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		let name = Name::new(self.app.clone());
+
+		let resolution = depends::Resolver::new(&shovel.buckets)
+			.resolve(&name)
+			.wrap_err_with(|| format!("Failed to resolve dependencies for {}", self.app))?;
+
+		let mut tree = String::new();
+		write_tree(&mut tree, &resolution.tree, "", true, true);
+
+		let order: Vec<_> = resolution
+			.order
+			.iter()
+			.map(|resolved| format!("{}/{}", resolved.bucket, resolved.name))
+			.collect();
+
+		println!("\n{tree}");
+		println!("Install order: {}\n", order.join(" -> ").green());
+
+		Ok(())
+	}
+}
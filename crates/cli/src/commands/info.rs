@@ -1,6 +1,9 @@
 use std::iter;
+use std::mem;
 
 use shovel::app::Error as AppError;
+use shovel::app::Suggestion;
+use shovel::bucket;
 use shovel::bucket::Name;
 
 use crate::run::Run;
@@ -22,29 +25,64 @@ struct Info {
 	installed: String,
 	binaries: String,
 	shortcuts: String,
+	suggestions: String,
+	#[tabled(skip)]
+	suggested: Vec<Suggestion>,
 }
 
 impl Info {
 	fn new(shovel: &mut shovel::Shovel, name: &Name) -> shovel::Result<Self> {
 		let (bucket, item) = shovel.buckets.manifest(name)?;
 		let manifest = item.manifest?;
+
+		// Show which manifest an unqualified name resolves to, if other buckets have one with the same name.
+		let unqualified = Name::new(name.manifest().to_owned());
+		let buckets: Vec<_> = shovel
+			.buckets
+			.candidates(&unqualified)?
+			.iter()
+			.map(|(candidate, _)| candidate.name())
+			.collect();
+
+		let others: Vec<_> = buckets
+			.iter()
+			.filter(|candidate| **candidate != bucket.name())
+			.map(String::as_str)
+			.collect();
+
+		let bucket_name = match buckets.first() {
+			Some(first) if *first != bucket.name() => {
+				format!("{} (shadowed by {first})", bucket.name())
+			}
+			_ if !others.is_empty() => format!("{} (shadows {})", bucket.name(), others.join(", ")),
+			_ => bucket.name(),
+		};
 		let arch = manifest.compatible();
 
 		let license = manifest.license.to_string();
 
-		let commit = bucket.manifest_commit(name.manifest())?;
+		let commit = bucket.manifest_commit(name.manifest());
 
 		let (updated_at, updated_by) = match commit {
-			Some(commit) => {
-				let updated_at = shovel::Timestamp::from(commit.time()).to_string();
-				let updated_by = commit.author().name().unwrap().to_owned();
+			Ok(Some(commit)) => {
+				let updated_at = commit.time.to_string();
 
-				(updated_at, updated_by)
+				(updated_at, commit.author)
 			}
-			None => (
+			Ok(None) if bucket.kind() == bucket::Kind::Directory => (
+				"(history unavailable, bucket is a local directory)".to_owned(),
+				"(history unavailable, bucket is a local directory)".to_owned(),
+			),
+			Ok(None) => (
 				"(commit not found)".to_owned(),
 				"(author not found)".to_owned(),
 			),
+			// The history of a shallow clone may not go back far enough.
+			Err(bucket::Error::ShallowHistory) => (
+				"(history unavailable, bucket is shallow)".to_owned(),
+				"(history unavailable, bucket is shallow)".to_owned(),
+			),
+			Err(err) => return Err(err.into()),
 		};
 
 		let app = shovel.apps.open_current(name.manifest());
@@ -70,11 +108,14 @@ impl Info {
 			})
 			.unwrap_or_default();
 
+		let suggested = shovel.apps.suggestions(&manifest)?;
+		let suggestions = util::format_suggestions(&suggested);
+
 		Ok(Self {
 			name: name.manifest().to_owned(),
 			description: manifest.description.unwrap_or_default(),
 			version: manifest.version,
-			bucket: bucket.name(),
+			bucket: bucket_name,
 			website: manifest.homepage,
 			license,
 			updated_at,
@@ -82,6 +123,8 @@ impl Info {
 			installed,
 			binaries,
 			shortcuts,
+			suggestions,
+			suggested,
 		})
 	}
 }
@@ -95,12 +138,16 @@ pub struct InfoCommand {
 impl Run for InfoCommand {
 	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
 		let name = Name::new(self.app.clone());
-		let info = Info::new(shovel, &name)?;
+		let mut info =
+			Info::new(shovel, &name).map_err(|err| util::suggest_apps(shovel, &name, err))?;
+		let suggested = mem::take(&mut info.suggested);
 
 		let table = util::tableify(iter::once(info), true);
 
 		println!("\n{table}\n");
 
+		util::print_missing_suggestions(name.manifest(), &suggested);
+
 		Ok(())
 	}
 }
@@ -1,7 +1,12 @@
+use std::collections::HashMap;
+
 use eyre::WrapErr;
+use owo_colors::OwoColorize;
 use shovel::app;
 
-use crate::run::Run;
+use crate::roots::Root;
+use crate::roots::Roots;
+use crate::run::RunRoots;
 use crate::util;
 
 #[derive(tabled::Tabled, Default)]
@@ -11,11 +16,23 @@ struct ListInfo {
 	version: String,
 	bucket: String,
 	updated: String,
+	root: String,
 	info: String,
 }
 
 impl ListInfo {
-	fn new(name: &str, app: app::Result<app::App>) -> Self {
+	/// # Arguments
+	///
+	/// * `resolved` - The bucket each manifest name resolves to. See [`util::resolved_buckets`].
+	fn new(
+		shovel: &shovel::Shovel,
+		name: &str,
+		app: app::Result<app::App>,
+		root: Root,
+		resolved: &HashMap<&str, String>,
+	) -> Self {
+		let root = root.to_string();
+
 		// Obtain the app's info if it didn't error out.
 		let info = app.and_then(|app| {
 			let manifest = app.manifest()?;
@@ -25,12 +42,30 @@ impl ListInfo {
 			let bucket = metadata.bucket;
 			let updated = app.timestamp()?.to_string();
 
+			// Warn about apps that are no longer maintained in their bucket.
+			let deprecated = shovel
+				.buckets
+				.open(&bucket)
+				.is_ok_and(|b| b.is_deprecated(name));
+
+			// Point out apps whose name now resolves to another bucket, i.e., `shovel info` shows another manifest.
+			let shadowed_by = resolved.get(name).filter(|by| **by != bucket);
+
+			let info = if deprecated {
+				"Deprecated".yellow().to_string()
+			} else if let Some(by) = shadowed_by {
+				format!("Shadowed by {by}").yellow().to_string()
+			} else {
+				String::new()
+			};
+
 			Ok(Self {
 				name: name.to_owned(),
 				version,
 				bucket,
 				updated,
-				..Default::default()
+				root: root.clone(),
+				info,
 			})
 		});
 
@@ -39,6 +74,7 @@ impl ListInfo {
 			// Wrap the error infomation.
 			Err(err) => Self {
 				name: name.to_owned(),
+				root,
 				info: err.to_string(),
 				..Default::default()
 			},
@@ -52,8 +88,8 @@ pub struct ListCommand {
 	query: Option<String>,
 }
 
-impl Run for ListCommand {
-	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+impl RunRoots for ListCommand {
+	fn run(&self, roots: &mut Roots) -> eyre::Result<()> {
 		let query = match &self.query {
 			Some(q) => q,
 			None => "",
@@ -63,10 +99,24 @@ impl Run for ListCommand {
 
 		let regex = regex::Regex::new(app).wrap_err("Invalid pattern")?;
 
-		let apps: Vec<_> = shovel
-			.apps
-			.each()?
-			.map(|(name, app)| ListInfo::new(&name, app))
+		let mut apps = vec![];
+
+		// Merge the apps from each root.
+		for (root, shovel) in roots.all() {
+			// Shadowing is only extra information, so it is left out if the buckets cannot be read.
+			let summaries = shovel.buckets.summaries(&()).unwrap_or_default();
+			let resolved = util::resolved_buckets(&summaries);
+
+			let each = shovel
+				.apps
+				.each()?
+				.map(|(name, app)| ListInfo::new(shovel, &name, app, root, &resolved));
+
+			apps.extend(each);
+		}
+
+		let apps: Vec<_> = apps
+			.into_iter()
 			.filter_map(|info| {
 				// check the bucket and name.
 				if (bucket.is_empty() || info.bucket == bucket)
@@ -80,7 +130,7 @@ impl Run for ListCommand {
 			.collect();
 
 		if apps.is_empty() {
-			eyre::bail!("No app(s) found.")
+			eyre::bail!("No app(s) found.");
 		}
 
 		println!("\n{}\n", util::tableify(apps, false));
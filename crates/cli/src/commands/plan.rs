@@ -0,0 +1,22 @@
+mod install;
+mod uninstall;
+
+use crate::run::Run;
+
+#[derive(clap::Subcommand)]
+pub enum PlanCommands {
+	/// Show what installing apps would do, and the features they suggest
+	Install(install::InstallCommand),
+
+	/// Show what uninstalling apps would do
+	Uninstall(uninstall::UninstallCommand),
+}
+
+impl Run for PlanCommands {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		match self {
+			Self::Install(cmd) => cmd.run(shovel),
+			Self::Uninstall(cmd) => cmd.run(shovel),
+		}
+	}
+}
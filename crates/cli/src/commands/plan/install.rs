@@ -0,0 +1,37 @@
+use eyre::WrapErr;
+use shovel::bucket::Name;
+use shovel::plan::Planner;
+
+use crate::run::Run;
+use crate::util;
+
+#[derive(clap::Args)]
+pub struct InstallCommand {
+	/// The apps to install. To specify a bucket, use the syntax `bucket/app`.
+	#[arg(required = true)]
+	apps: Vec<String>,
+
+	/// Show the plan as JSON
+	#[arg(long)]
+	json: bool,
+}
+
+impl Run for InstallCommand {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		let names: Vec<_> = self.apps.iter().map(|app| Name::new(app.clone())).collect();
+
+		let plan = Planner::new(shovel)
+			.install(&names)
+			.wrap_err("Failed to plan installation")?;
+
+		util::print_plan(&plan, self.json)?;
+
+		if !self.json {
+			for step in &plan.steps {
+				util::print_missing_suggestions(&step.name, &step.suggestions);
+			}
+		}
+
+		Ok(())
+	}
+}
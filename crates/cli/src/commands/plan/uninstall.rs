@@ -0,0 +1,28 @@
+use eyre::WrapErr;
+use shovel::plan::Planner;
+
+use crate::run::Run;
+use crate::util;
+
+#[derive(clap::Args)]
+pub struct UninstallCommand {
+	/// The apps to uninstall.
+	#[arg(required = true)]
+	apps: Vec<String>,
+
+	/// Show the plan as JSON
+	#[arg(long)]
+	json: bool,
+}
+
+impl Run for UninstallCommand {
+	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		let names: Vec<_> = self.apps.iter().map(String::as_str).collect();
+
+		let plan = Planner::new(shovel)
+			.uninstall(&names)
+			.wrap_err("Failed to plan uninstallation")?;
+
+		util::print_plan(&plan, self.json)
+	}
+}
@@ -1,13 +1,135 @@
+use std::collections::HashSet;
+
 use eyre::WrapErr;
+use owo_colors::OwoColorize;
 
+use shovel::bucket;
 use shovel::bucket::Bucket;
+use shovel::bucket::Criteria;
 use shovel::bucket::Name;
-use shovel::bucket::Predicate;
-use shovel::bucket::SearchItem;
+use shovel::bucket::Summary;
+use shovel::bucket::SummaryItem;
+use shovel::fuzzy;
+use shovel::manifest::Manifest;
 
 use crate::run::Run;
 use crate::util;
 
+/// A field of a manifest to search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+enum Field {
+	/// The manifest's name.
+	Name,
+
+	/// The names of the commands the app adds.
+	Bin,
+
+	/// The app's description.
+	Description,
+
+	/// The app's homepage.
+	Homepage,
+}
+
+impl Field {
+	/// Returns the text in this field of a manifest.
+	fn texts<'s>(self, name: &'s str, summary: &'s Summary) -> Vec<&'s str> {
+		match self {
+			Self::Name => vec![name],
+			Self::Bin => summary.bins.iter().map(String::as_str).collect(),
+			Self::Description => summary.description.as_deref().into_iter().collect(),
+			Self::Homepage => vec![&summary.homepage],
+		}
+	}
+
+	/// Checks if fuzzy matches are allowed in this field.
+	/// Long text would match almost any query as a subsequence, so only names are matched fuzzily.
+	fn is_fuzzy(self) -> bool {
+		matches!(self, Self::Name | Self::Bin)
+	}
+}
+
+/// Where a query matched in a manifest.
+struct Hit {
+	field: Field,
+	text: String,
+	matched: Option<fuzzy::Match>,
+}
+
+impl Hit {
+	/// Returns the best fuzzy match for a query in a manifest's fields, if any.
+	fn fuzzy(fields: &[Field], query: &str, name: &str, summary: &Summary) -> Option<Self> {
+		fields
+			.iter()
+			.flat_map(|&field| {
+				field
+					.texts(name, summary)
+					.into_iter()
+					.filter_map(move |text| {
+						let matched = fuzzy::score(query, text)?;
+
+						(field.is_fuzzy() || matched.kind != fuzzy::Kind::Fuzzy)
+							.then_some((matched, field, text))
+					})
+			})
+			.min()
+			.map(|(matched, field, text)| Self {
+				field,
+				text: text.to_owned(),
+				matched: Some(matched),
+			})
+	}
+
+	/// Returns the first regex match in a manifest's fields, if any.
+	fn regex(
+		fields: &[Field],
+		regex: &regex::Regex,
+		name: &str,
+		summary: &Summary,
+	) -> Option<Self> {
+		fields.iter().find_map(|&field| {
+			let text = field
+				.texts(name, summary)
+				.into_iter()
+				.find(|text| regex.is_match(text))?;
+
+			Some(Self {
+				field,
+				text: text.to_owned(),
+				matched: None,
+			})
+		})
+	}
+
+	/// Returns the text with the matched characters highlighted, if this hit is for `text` in `field`.
+	fn highlight(&self, field: Field, text: &str) -> String {
+		match &self.matched {
+			Some(matched) if self.field == field && self.text == text => {
+				util::highlight(text, &matched.indices)
+			}
+			_ => text.to_owned(),
+		}
+	}
+}
+
+/// A filter for parsed manifests, satisfied when every field filter is satisfied.
+#[derive(Clone)]
+struct Filters(Vec<bucket::Field>);
+
+impl Criteria for Filters {
+	fn filter_bucket(&self, _bucket: &Bucket) -> bool {
+		true
+	}
+
+	fn filter_manifest(&self, _manifest: &str) -> bool {
+		true
+	}
+
+	fn filter_parsed(&self, manifest: &Manifest) -> bool {
+		self.0.iter().all(|field| field.filter_parsed(manifest))
+	}
+}
+
 #[derive(tabled::Tabled)]
 #[tabled(rename_all = "pascal")]
 struct SearchInfo {
@@ -15,58 +137,202 @@ struct SearchInfo {
 	version: String,
 	bucket: String,
 	binaries: String,
+	matched: String,
 }
 
 impl SearchInfo {
-	fn new(bucket: &Bucket, item: SearchItem) -> shovel::Result<Self> {
-		let manifest = item.manifest?;
-		let arch = manifest.compatible();
-
-		let version = manifest.version.clone();
-		let binaries = manifest
-			.bin(arch)
-			.map(ToString::to_string)
-			.unwrap_or_default();
-
-		Ok(SearchInfo {
-			name: item.name,
-			version,
-			bucket: bucket.name(),
-			binaries,
-		})
+	/// # Arguments
+	///
+	/// * `shadowed_by` - The bucket with higher priority that has a manifest with the same name, if any.
+	fn new(
+		bucket: &Bucket,
+		item: &SummaryItem,
+		summary: &Summary,
+		hit: &Hit,
+		shadowed_by: Option<&str>,
+	) -> Self {
+		let binaries: Vec<_> = summary
+			.bins
+			.iter()
+			.map(|bin| hit.highlight(Field::Bin, bin))
+			.collect();
+
+		let matched = match hit.field {
+			Field::Name => "name".to_owned(),
+			Field::Bin => format!("bin ({})", hit.text),
+			Field::Description => "description".to_owned(),
+			Field::Homepage => "homepage".to_owned(),
+		};
+
+		SearchInfo {
+			name: hit.highlight(Field::Name, &item.name),
+			version: summary.version.clone(),
+			bucket: match shadowed_by {
+				Some(by) => format!("{} (shadowed by {by})", bucket.name()),
+				None => bucket.name(),
+			},
+			binaries: binaries.join(" | "),
+			matched,
+		}
 	}
 }
 
 #[derive(clap::Args)]
 pub struct SearchCommand {
-	/// The apps to search as a regex. To specify a bucket, use the syntax `bucket/pattern`.
+	/// The apps to search for. To specify a bucket, use the syntax `bucket/query`.
 	query: String,
+
+	/// The fields to search, separated by commas. By default, names and binaries are searched.
+	#[arg(short, long, value_enum, value_delimiter = ',', default_values = ["name", "bin"])]
+	field: Vec<Field>,
+
+	/// Match the query as a regex instead of ranking apps by how closely they match
+	#[arg(short, long)]
+	regex: bool,
+
+	/// Only show apps with this license identifier, e.g., `MIT`
+	#[arg(long)]
+	license: Option<String>,
+
+	/// Only show apps that add commands
+	#[arg(long)]
+	has_bin: bool,
+
+	/// Only show apps that can be updated automatically
+	#[arg(long)]
+	autoupdate: bool,
+}
+
+impl SearchCommand {
+	/// Returns the filters for parsed manifests, or None if no filter was given.
+	fn filters(&self) -> Option<Filters> {
+		let mut fields = vec![];
+
+		if let Some(license) = &self.license {
+			fields.push(bucket::Field::License(license.clone()));
+		}
+
+		if self.has_bin {
+			fields.push(bucket::Field::HasBin);
+		}
+
+		if self.autoupdate {
+			fields.push(bucket::Field::HasAutoupdate);
+		}
+
+		(!fields.is_empty()).then_some(Filters(fields))
+	}
 }
 
 impl Run for SearchCommand {
 	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
 		let name = Name::new(self.query.clone());
+		let query = name.manifest();
+
+		let regex = if self.regex {
+			Some(regex::Regex::new(query).wrap_err("Invalid pattern")?)
+		} else {
+			None
+		};
 
-		let regex = regex::Regex::new(name.manifest()).wrap_err("Invalid pattern")?;
+		// All buckets are read, so manifests in a specified bucket are still shown as shadowed.
+		let summaries = shovel.buckets.summaries(&()).wrap_err("Search failed")?;
+		let resolved = util::resolved_buckets(&summaries);
 
-		let predicate = Predicate::new(
-			|b| name.bucket().map_or(true, |nb| b.name() == nb),
-			|m| regex.is_match(m),
-		);
+		// The index does not have every field, so filtering by them parses the manifests in parallel.
+		// Manifests that cannot be parsed are always kept, so they are still reported.
+		let filtered = match self.filters() {
+			Some(filters) => {
+				let items = shovel
+					.buckets
+					.par_search_all(&filters)
+					.wrap_err("Search failed")?;
 
-		let apps: shovel::Result<Vec<_>> = shovel
-			.buckets
-			.search_all(&predicate)
-			.wrap_err("Search failed")?
-			.map(|(bucket, item)| SearchInfo::new(&bucket, item))
+				let names: HashSet<_> = items
+					.into_iter()
+					.map(|(bucket, item)| (bucket.name(), item.name))
+					.collect();
+
+				Some(names)
+			}
+			None => None,
+		};
+
+		let summaries: Vec<_> = summaries
+			.iter()
+			.filter(|(bucket, _)| name.bucket().map_or(true, |nb| bucket.name() == nb))
+			.filter(|(bucket, item)| {
+				filtered.as_ref().map_or(true, |names| {
+					names.contains(&(bucket.name(), item.name.clone()))
+				})
+			})
 			.collect();
 
-		let apps = apps?;
+		let mut hits: Vec<_> = summaries
+			.iter()
+			.filter_map(|(bucket, item)| {
+				let summary = item.summary.as_ref().ok()?;
+
+				let hit = match &regex {
+					Some(regex) => Hit::regex(&self.field, regex, &item.name, summary),
+					None => Hit::fuzzy(&self.field, query, &item.name, summary),
+				}?;
+
+				Some((hit, bucket, item, summary))
+			})
+			.collect();
+
+		// Manifests that cannot be parsed are only matched by name, but are reported so they are not silently missing.
+		let failed: Vec<_> = summaries
+			.iter()
+			.filter_map(|(bucket, item)| {
+				let err = item.summary.as_ref().err()?;
+
+				let matched = match &regex {
+					Some(regex) => regex.is_match(&item.name),
+					None => fuzzy::score(query, &item.name).is_some(),
+				};
+
+				matched.then_some((bucket, item, err))
+			})
+			.collect();
 
-		if apps.is_empty() {
-			eyre::bail!("No app(s) found.");
+		for (bucket, item, err) in &failed {
+			let name = format!("{}/{}", bucket.name(), item.name);
+
+			println!("{} cannot be parsed: {err}", name.red());
+		}
+
+		if hits.is_empty() {
+			// Manifests that cannot be parsed were already reported, so they are not suggested.
+			let candidates = summaries.iter().flat_map(|(_, item)| {
+				item.summary.iter().flat_map(|summary| {
+					std::iter::once(item.name.as_str())
+						.chain(summary.bins.iter().map(String::as_str))
+				})
+			});
+
+			eyre::bail!(
+				"No app(s) found.{}",
+				util::did_you_mean(&fuzzy::suggest(query, candidates, 5))
+			);
 		}
 
+		// Rank the best matches first. Summaries are already sorted by bucket and name otherwise.
+		hits.sort_by(|(a, ..), (b, ..)| {
+			let a = a.matched.as_ref().map(|m| (m, a.field));
+			let b = b.matched.as_ref().map(|m| (m, b.field));
+
+			a.cmp(&b)
+		});
+
+		let apps = hits.iter().map(|(hit, bucket, item, summary)| {
+			let first = &resolved[item.name.as_str()];
+			let shadowed_by = (*first != bucket.name()).then_some(first.as_str());
+
+			SearchInfo::new(bucket, item, summary, hit, shadowed_by)
+		});
+
 		println!("\n{}\n", util::tableify(apps, false));
 
 		Ok(())
@@ -0,0 +1,112 @@
+use owo_colors::OwoColorize;
+use shovel::bucket;
+use shovel::bucket::Name;
+
+use crate::roots::Root;
+use crate::roots::Roots;
+use crate::run::RunRoots;
+use crate::util;
+
+#[derive(tabled::Tabled, Default)]
+#[tabled(rename_all = "pascal")]
+struct StatusInfo {
+	name: String,
+	installed: String,
+	latest: String,
+	bucket: String,
+	root: String,
+	info: String,
+}
+
+impl StatusInfo {
+	/// Returns the status of an installed app, or None if it is up-to-date.
+	fn new(shovel: &shovel::Shovel, name: &str, root: Root) -> Option<Self> {
+		let status = || -> shovel::Result<Self> {
+			let app = shovel.apps.open_current(name)?;
+			let installed = app.manifest()?.version;
+			let bucket = app.metadata()?.bucket;
+
+			// Apps whose bucket is unknown or removed cannot be compared, but are still listed.
+			let missing = |info: String| Self {
+				name: name.to_owned(),
+				installed: installed.clone(),
+				root: root.to_string(),
+				info: info.yellow().to_string(),
+				bucket: bucket.clone(),
+				..Default::default()
+			};
+
+			if bucket.is_empty() {
+				return Ok(missing("Installed from an unknown bucket".to_owned()));
+			}
+
+			// Retired manifests are not searched, but the app can still be compared to the last version.
+			let retired = match shovel.buckets.open(&bucket) {
+				Ok(retired) => retired,
+				Err(bucket::Error::NotFound) => {
+					return Ok(missing(format!("Bucket {bucket} was removed")));
+				}
+				Err(err) => return Err(err.into()),
+			};
+
+			if retired.is_deprecated(name) {
+				return Ok(Self {
+					name: name.to_owned(),
+					installed,
+					latest: retired.manifest(name, true)?.version,
+					root: root.to_string(),
+					info: format!("Deprecated in {bucket}").yellow().to_string(),
+					bucket,
+				});
+			}
+
+			let (_, item) = shovel
+				.buckets
+				.manifest(&Name::new(format!("{bucket}/{name}")))?;
+
+			Ok(Self {
+				name: name.to_owned(),
+				installed,
+				latest: item.manifest?.version,
+				bucket,
+				root: root.to_string(),
+				..Default::default()
+			})
+		};
+
+		match status() {
+			Ok(info) if info.installed == info.latest && info.info.is_empty() => None,
+			Ok(info) => Some(info),
+			// Wrap the error infomation.
+			Err(err) => Some(Self {
+				name: name.to_owned(),
+				root: root.to_string(),
+				info: err.to_string(),
+				..Default::default()
+			}),
+		}
+	}
+}
+
+#[derive(clap::Args)]
+pub struct StatusCommand {}
+
+impl RunRoots for StatusCommand {
+	fn run(&self, roots: &mut Roots) -> eyre::Result<()> {
+		let mut infos = vec![];
+
+		for (root, shovel) in roots.all() {
+			for (name, _) in shovel.apps.each()? {
+				infos.extend(StatusInfo::new(shovel, &name, root));
+			}
+		}
+
+		if infos.is_empty() {
+			println!("{}", "Everything is up-to-date.".green());
+		} else {
+			println!("\n{}\n", util::tableify(infos, false));
+		}
+
+		Ok(())
+	}
+}
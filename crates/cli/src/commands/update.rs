@@ -1,3 +1,12 @@
+use std::collections::HashSet;
+
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+use shovel::bucket::Change;
+use shovel::bucket::Commit;
+use shovel::plan::Planner;
+use shovel::BucketUpdate;
+
 use crate::run::Run;
 use crate::tracker::Tracker;
 use crate::util;
@@ -11,13 +20,13 @@ struct UpdateInfo {
 }
 
 impl UpdateInfo {
-	fn new(commit: &git2::Commit) -> Self {
+	fn new(commit: Commit) -> Self {
 		// Take the first 9 characters from the commit ID.
-		let hash: String = commit.id().to_string().chars().take(9).collect();
+		let hash: String = commit.id.to_string().chars().take(9).collect();
 
-		let summary = commit.summary().unwrap().to_owned();
+		let summary = commit.summary;
 
-		let time = shovel::Timestamp::from(commit.time()).to_string();
+		let time = commit.time.to_string();
 
 		Self {
 			hash,
@@ -27,11 +36,160 @@ impl UpdateInfo {
 	}
 }
 
+#[derive(tabled::Tabled, Debug)]
+#[tabled(rename_all = "pascal")]
+struct ChangeInfo {
+	app: String,
+	change: String,
+	version: String,
+}
+
+impl ChangeInfo {
+	/// # Arguments
+	///
+	/// * `installed` - Whether the app affected by the change is installed from the bucket.
+	fn new(change: Change, installed: bool) -> Self {
+		let version = |version: Option<String>| version.unwrap_or_else(|| "?".to_owned());
+
+		let (app, change, version) = match change {
+			Change::Added { name, version: v } => (name, "added", version(v)),
+			Change::Removed { name, version: v } => (name, "removed", version(v)),
+			Change::Renamed { from, to } => (format!("{from} -> {to}"), "renamed", String::new()),
+			Change::Updated { name, from, to } => (
+				name,
+				"updated",
+				format!("{} -> {}", version(from), version(to)),
+			),
+		};
+
+		let app = if installed {
+			format!("{} (installed)", app.yellow().bold())
+		} else {
+			app
+		};
+
+		Self {
+			app,
+			change: change.to_owned(),
+			version,
+		}
+	}
+}
+
+/// Returns the installed apps as (bucket, app).
+fn installed_apps(shovel: &shovel::Shovel) -> eyre::Result<HashSet<(String, String)>> {
+	let mut installed = HashSet::new();
+
+	for (name, _) in shovel.apps.each()? {
+		// Apps that cannot be read are not affected by bucket changes.
+		if let Ok(metadata) = shovel
+			.apps
+			.open_current(&name)
+			.and_then(|app| app.metadata())
+		{
+			installed.insert((metadata.bucket, name));
+		}
+	}
+
+	Ok(installed)
+}
+
 #[derive(clap::Args)]
-pub struct UpdateCommand {}
+pub struct UpdateCommand {
+	/// The buckets to update. By default, all buckets are updated.
+	buckets: Vec<String>,
+
+	/// Show the commits pulled into each bucket instead of the changes to apps
+	#[arg(long)]
+	commits: bool,
+
+	/// Show the plan for updating outdated apps from the buckets, without pulling the buckets first
+	#[arg(long)]
+	dry_run: bool,
+
+	/// Show the plan as JSON. Implies --dry-run.
+	#[arg(long)]
+	json: bool,
+}
+
+impl UpdateCommand {
+	/// Returns a table of the commits pulled into a bucket.
+	fn commit_table(
+		shovel: &shovel::Shovel,
+		name: &str,
+		from: git2::Oid,
+	) -> shovel::Result<tabled::Table> {
+		let infos: Vec<_> = shovel
+			.buckets
+			.open(name)?
+			.commits(from)?
+			.map(UpdateInfo::new)
+			.collect();
+
+		Ok(util::tableify(infos, false))
+	}
+
+	/// Returns a table of the changes to apps in a bucket, with installed apps first.
+	fn change_table(
+		shovel: &shovel::Shovel,
+		name: &str,
+		from: git2::Oid,
+		to: git2::Oid,
+		installed: &HashSet<(String, String)>,
+	) -> shovel::Result<tabled::Table> {
+		let mut changes: Vec<_> = shovel
+			.buckets
+			.open(name)?
+			.changes(from, to)?
+			.into_iter()
+			.map(|change| {
+				// A renamed app is affected under its old name.
+				let app = match &change {
+					Change::Renamed { from, .. } => from,
+					change => change.name(),
+				};
+
+				let is_installed = installed.contains(&(name.to_owned(), app.to_owned()));
+
+				(is_installed, change)
+			})
+			.collect();
+
+		// Changes are sorted by name, so a stable sort keeps them sorted within each group.
+		changes.sort_by_key(|(is_installed, _)| !is_installed);
+
+		let infos = changes
+			.into_iter()
+			.map(|(is_installed, change)| ChangeInfo::new(change, is_installed));
+
+		Ok(util::tableify(infos, false))
+	}
+}
 
 impl Run for UpdateCommand {
 	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()> {
+		if self.dry_run || self.json {
+			for name in &self.buckets {
+				shovel
+					.buckets
+					.open(name)
+					.wrap_err_with(|| format!("Failed to open bucket {name}"))?;
+			}
+
+			// Plan against the buckets as they are, as pulling would change them.
+			let plan = Planner::new(shovel)
+				.update(&[], &self.buckets)
+				.wrap_err("Failed to plan update")?;
+
+			util::print_plan(&plan, self.json)?;
+
+			if !plan.failed.is_empty() {
+				eyre::bail!("{} app(s) could not be planned", plan.failed.len());
+			}
+
+			return Ok(());
+		}
+
 		// Prepare a new Git progress tracker.
 		let multi_progress = indicatif::MultiProgress::new();
 		let tracker = Tracker::new(multi_progress);
@@ -42,32 +200,62 @@ impl Run for UpdateCommand {
 		opts.fetch_options(|bucket| tracker.fetch_options(bucket.name()));
 		opts.checkout_builder(|bucket| tracker.checkout_builder(bucket.name()));
 
-		// Update all buckets.
+		if !self.buckets.is_empty() {
+			opts.buckets(&self.buckets);
+		}
+
+		let installed = installed_apps(shovel)?;
 		let updates = shovel.update(&opts)?;
+		let mut failed = 0;
 
-		for (bucket, head) in updates {
+		for (name, update) in updates {
 			println!();
 
-			// Get the commits between the previous and current HEAD.
-			let mut infos = bucket
-				.commits(head)?
-				.map(|commit| UpdateInfo::new(&commit))
-				.peekable();
-
-			// If there are any commits, show them.
-			if infos.peek().is_some() {
-				println!(
-					"{} has been updated:\n{}",
-					bucket.name(),
-					util::tableify(infos, false)
-				);
-			} else {
-				println!("{} is already up-to-date.", bucket.name());
+			match update {
+				BucketUpdate::Updated { from, to, forced } => {
+					let action = if forced {
+						"has been reset to a commit outside of its history"
+					} else {
+						"has been updated"
+					};
+
+					let table = if self.commits {
+						Self::commit_table(shovel, &name, from)
+					} else {
+						Self::change_table(shovel, &name, from, to, &installed)
+					};
+
+					match table {
+						Ok(table) => println!("{name} {action}:\n{table}"),
+						Err(err) => {
+							failed += 1;
+
+							println!(
+								"{} {action}, but the changes cannot be read: {err}",
+								name.red()
+							);
+						}
+					}
+				}
+				BucketUpdate::UpToDate => println!("{name} is already up-to-date."),
+				BucketUpdate::Local => {
+					println!("{name} is a local directory, skipped. Changes to it are seen without updating.");
+				}
+				BucketUpdate::Failed(err) => {
+					failed += 1;
+
+					println!("{} failed to update: {err}", name.red());
+				}
 			}
 		}
 
 		println!();
 
+		// Report failures only after every bucket has been updated.
+		if failed > 0 {
+			eyre::bail!("{failed} bucket(s) failed to update");
+		}
+
 		Ok(())
 	}
 }
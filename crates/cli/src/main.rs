@@ -1,15 +1,18 @@
 mod commands;
+mod roots;
 mod run;
 mod tracker;
 mod util;
 
+use std::env;
 use std::fs;
 
 use clap::Parser;
 use eyre::WrapErr;
 use shovel::json;
 
-use run::Run;
+use roots::Roots;
+use run::RunRoots;
 
 #[derive(clap::Parser)]
 #[command(version, about)]
@@ -21,6 +24,16 @@ struct Args {
 	/// Specify a configuration file
 	#[arg(short, long, global = true)]
 	config: Option<String>,
+
+	/// Operate on the global installation directory instead of the user's
+	#[arg(short, long, global = true)]
+	global: bool,
+}
+
+/// Checks if the global installation directory exists, without creating it.
+fn global_exists() -> bool {
+	// The global installation directory is only defined on Windows.
+	env::var_os("ProgramData").is_some() && shovel::config::global_install_dir().exists()
 }
 
 fn main() -> eyre::Result<()> {
@@ -39,8 +52,27 @@ fn main() -> eyre::Result<()> {
 		None => shovel::Config::default(),
 	};
 
-	let mut shovel = shovel::Shovel::new(config)?;
+	let shovel = shovel::Shovel::new(config)?;
+
+	// Creating the global installation directory usually requires admin rights,
+	// so it is only opened if it was asked for or already exists.
+	let global = if args.global || global_exists() {
+		let global = shovel
+			.global()
+			.wrap_err("Failed to open the global installation directory");
+
+		// Other commands should still work if the global root is inaccessible.
+		if args.global {
+			Some(global?)
+		} else {
+			global.ok()
+		}
+	} else {
+		None
+	};
+
+	let mut roots = Roots::new(shovel, global, args.global);
 
 	// Delegate to sub-commands.
-	args.commands.run(&mut shovel)
+	args.commands.run(&mut roots)
 }
@@ -0,0 +1,62 @@
+use std::fmt;
+
+/// An installation root.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Root {
+	/// The current user's installation directory.
+	User,
+
+	/// The global installation directory, shared by all users.
+	Global,
+}
+
+impl fmt::Display for Root {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{}",
+			match self {
+				Self::User => "user",
+				Self::Global => "global",
+			}
+		)
+	}
+}
+
+/// The installation roots available to commands.
+pub struct Roots {
+	user: shovel::Shovel,
+	global: Option<shovel::Shovel>,
+	is_global: bool,
+}
+
+impl Roots {
+	/// Creates a new set of roots.
+	/// If `is_global` is true, `global` must be Some.
+	pub fn new(user: shovel::Shovel, global: Option<shovel::Shovel>, is_global: bool) -> Self {
+		assert!(!is_global || global.is_some(), "global root should exist");
+
+		Self {
+			user,
+			global,
+			is_global,
+		}
+	}
+
+	/// Returns the root targeted by the command line, i.e., global if `--global` was passed.
+	pub fn current(&mut self) -> &mut shovel::Shovel {
+		match (self.is_global, &mut self.global) {
+			(true, Some(global)) => global,
+			_ => &mut self.user,
+		}
+	}
+
+	/// Returns the roots to merge for commands that work on both roots.
+	/// If `--global` was passed, only the global root is returned.
+	pub fn all(&self) -> Vec<(Root, &shovel::Shovel)> {
+		let user = (!self.is_global).then_some((Root::User, &self.user));
+		let global = self.global.as_ref().map(|global| (Root::Global, global));
+
+		user.into_iter().chain(global).collect()
+	}
+}
@@ -1,5 +1,13 @@
+use crate::roots::Roots;
+
 /// A runnable subcommand.
 pub trait Run {
 	/// Runs the subcommand using the given `shovel`.
 	fn run(&self, shovel: &mut shovel::Shovel) -> eyre::Result<()>;
 }
+
+/// A runnable subcommand that can work on more than one installation root.
+pub trait RunRoots {
+	/// Runs the subcommand using the given `roots`.
+	fn run(&self, roots: &mut Roots) -> eyre::Result<()>;
+}
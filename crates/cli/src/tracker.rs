@@ -2,13 +2,13 @@ use std::cell::OnceCell;
 use std::sync::OnceLock;
 
 use git2::build::CheckoutBuilder;
-use git2::build::RepoBuilder;
 use git2::FetchOptions;
 use git2::RemoteCallbacks;
 
 use indicatif::MultiProgress;
 use indicatif::ProgressBar;
 use indicatif::ProgressStyle;
+use shovel::bucket::Credentials;
 
 fn progress_style() -> &'static ProgressStyle {
 	static PROGRESS_STYLE: OnceLock<ProgressStyle> = OnceLock::new();
@@ -27,10 +27,12 @@ impl Tracker {
 		Self { multi_progress }
 	}
 
-	/// Returns a set of remote callbacks that send updates when invoked.
+	/// Returns a set of remote callbacks that send updates when invoked, and supply credentials for private buckets.
 	pub fn remote_callbacks(&self, repo: String) -> RemoteCallbacks<'_> {
 		let mut callbacks = RemoteCallbacks::new();
 
+		Credentials::from_env().register(&mut callbacks);
+
 		let recv_bar = OnceCell::new();
 		let index_bar = OnceCell::new();
 		let delta_bar = OnceCell::new();
@@ -114,16 +116,6 @@ impl Tracker {
 		checkout
 	}
 
-	/// Returns a repository builder with both remote and checkout callbacks.
-	pub fn repo_builder(&self, repo: String) -> RepoBuilder<'_> {
-		let mut builder = RepoBuilder::new();
-		builder
-			.fetch_options(self.fetch_options(repo.clone()))
-			.with_checkout(self.checkout_builder(repo));
-
-		builder
-	}
-
 	fn add_progress_bar(&self, len: u64) -> ProgressBar {
 		self.multi_progress
 			.add(ProgressBar::new(len).with_style(progress_style().clone()))
@@ -1,3 +1,15 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use owo_colors::OwoColorize;
+use shovel::app::Suggestion;
+use shovel::bucket;
+use shovel::bucket::Bucket;
+use shovel::bucket::Name;
+use shovel::bucket::Source;
+use shovel::bucket::SummaryItem;
+use shovel::fuzzy;
+use shovel::plan;
 use tabled::settings;
 use tabled::settings::object;
 use tabled::settings::peaker;
@@ -58,3 +70,258 @@ pub fn term_size() -> (u16, u16) {
 pub fn parse_app(name: &str) -> (&str, &str) {
 	name.split_once('/').unwrap_or(("", name))
 }
+
+/// Highlights the characters at the given character indices of a text.
+pub fn highlight(text: &str, indices: &[usize]) -> String {
+	text.chars()
+		.enumerate()
+		.map(|(i, c)| {
+			if indices.contains(&i) {
+				c.yellow().bold().to_string()
+			} else {
+				c.to_string()
+			}
+		})
+		.collect()
+}
+
+/// Formats corrections for a misspelt name as a sentence to append to an error message.
+/// If there are no corrections, an empty string is returned.
+pub fn did_you_mean(corrections: &[&str]) -> String {
+	if corrections.is_empty() {
+		String::new()
+	} else {
+		format!(" Did you mean: {}?", corrections.join(", "))
+	}
+}
+
+/// Converts an error into a report, suggesting similar app names if the app's manifest was not found.
+pub fn suggest_apps(shovel: &shovel::Shovel, name: &Name, err: shovel::Error) -> eyre::Report {
+	if !matches!(err, shovel::Error::Bucket(bucket::Error::ManifestNotFound)) {
+		return err.into();
+	}
+
+	// If the summaries cannot be read, the original error is more useful.
+	let predicate = bucket::Predicate::new(
+		|b| name.bucket().map_or(true, |nb| b.name() == nb),
+		|_| true,
+	);
+
+	let Ok(summaries) = shovel.buckets.summaries(&predicate) else {
+		return err.into();
+	};
+
+	let candidates = summaries.iter().map(|(_, item)| item.name.as_str());
+	let corrections = fuzzy::suggest(name.manifest(), candidates, 5);
+
+	eyre::eyre!("{err}.{}", did_you_mean(&corrections))
+}
+
+/// Returns the bucket each manifest name resolves to when it is not qualified.
+///
+/// # Arguments
+///
+/// * `summaries` - The summaries of all buckets, in bucket priority order. See [`shovel::bucket::Buckets::summaries`].
+pub fn resolved_buckets(summaries: &[(Arc<Bucket>, SummaryItem)]) -> HashMap<&str, String> {
+	let mut resolved = HashMap::new();
+
+	// The first bucket with a name is the one it resolves to.
+	for (bucket, item) in summaries {
+		resolved
+			.entry(item.name.as_str())
+			.or_insert_with(|| bucket.name());
+	}
+
+	resolved
+}
+
+/// Formats suggestions as a single line, marking each feature with the installed app providing it.
+pub fn format_suggestions(suggestions: &[Suggestion]) -> String {
+	let features: Vec<_> = suggestions
+		.iter()
+		.map(|suggestion| match &suggestion.installed {
+			Some(app) => format!("{} ({app})", suggestion.feature),
+			None => format!("{} (missing)", suggestion.feature),
+		})
+		.collect();
+
+	features.join(" | ")
+}
+
+/// Prints the install commands for features suggested by an app that are not installed yet.
+///
+/// Shovel cannot install apps yet, so these are shown by `info` and `plan install` instead of after an install,
+/// and the commands use Scoop, which shares its installed apps with Shovel.
+pub fn print_missing_suggestions(app: &str, suggestions: &[Suggestion]) {
+	// A feature without apps cannot be installed, so it is not suggested.
+	let missing: Vec<_> = suggestions
+		.iter()
+		.filter(|s| !s.is_satisfied() && !s.apps.is_empty())
+		.collect();
+
+	if missing.is_empty() {
+		return;
+	}
+
+	println!("{} suggests installing:", app.bold());
+
+	for suggestion in missing {
+		let Some(preferred) = suggestion.apps.first() else {
+			continue;
+		};
+
+		// The first app is the preferred one.
+		let apps = suggestion.apps.join(" or ");
+		let command = format!("scoop install {preferred}");
+
+		println!(
+			"* {} ({apps}): run {}",
+			suggestion.feature.bold(),
+			command.green()
+		);
+	}
+
+	println!();
+}
+
+#[derive(tabled::Tabled)]
+#[tabled(rename_all = "pascal")]
+struct PlanRow {
+	step: String,
+	detail: String,
+}
+
+impl PlanRow {
+	fn new(step: &str, detail: String) -> Self {
+		Self {
+			step: step.to_owned(),
+			detail,
+		}
+	}
+}
+
+/// Prints a plan, either as JSON or as a table of steps for each app.
+pub fn print_plan(plan: &plan::Plan, json: bool) -> eyre::Result<()> {
+	use plan::Action;
+	use plan::EnvChange;
+
+	if json {
+		println!("{}", serde_json::to_string_pretty(plan)?);
+		return Ok(());
+	}
+
+	if plan.steps.is_empty() && plan.failed.is_empty() {
+		println!("Nothing to {}.", plan.command);
+		return Ok(());
+	}
+
+	println!();
+
+	for step in &plan.steps {
+		let action = match &step.action {
+			Action::Install => "install".green().to_string(),
+			Action::Update { from } => format!("update from {from}").green().to_string(),
+			Action::Uninstall => "uninstall".red().to_string(),
+			Action::Skip => "skip (already installed)".dimmed().to_string(),
+		};
+
+		println!(
+			"{} ({}) {} [{}]: {action}",
+			step.name.bold(),
+			step.bucket.blue(),
+			step.version,
+			step.kind
+		);
+
+		match &step.source {
+			Source::Latest => {}
+			Source::History { commit } => {
+				println!("  {}", format!("manifest from commit {commit}").dimmed());
+			}
+			Source::Autoupdate { from } => println!(
+				"  {}",
+				format!("manifest generated from {from}, hashes are not verified").yellow()
+			),
+		}
+
+		let mut rows = vec![];
+
+		for download in &step.downloads {
+			let cache = if download.cached { "hit" } else { "miss" };
+			rows.push(PlanRow::new(
+				"download",
+				format!("{} (cache {cache})", download.url),
+			));
+		}
+
+		for extract in &step.extract {
+			let verb = if extract.archive { "extract" } else { "copy" };
+			let from = match &extract.extract_dir {
+				Some(dir) => format!("{}/{dir}", extract.file),
+				None => extract.file.clone(),
+			};
+
+			rows.push(PlanRow::new(
+				verb,
+				format!("{from} -> {}", extract.to.display()),
+			));
+		}
+
+		for shim in &step.shims {
+			let mut target = shim.target.display().to_string();
+
+			for arg in &shim.arguments {
+				target.push(' ');
+				target.push_str(arg);
+			}
+
+			rows.push(PlanRow::new("shim", format!("{} -> {target}", shim.name)));
+		}
+
+		for persist in &step.persist {
+			rows.push(PlanRow::new(
+				"persist",
+				format!(
+					"{} -> {}",
+					persist.source.display(),
+					persist.target.display()
+				),
+			));
+		}
+
+		for change in &step.env {
+			let detail = match change {
+				EnvChange::AddPath { name, entry } => format!("{name} += {entry}"),
+				EnvChange::RemovePath { name, entry } => format!("{name} -= {entry}"),
+				EnvChange::Set { name, value } => format!("{name} = {value}"),
+				EnvChange::Unset { name } => format!("unset {name}"),
+			};
+
+			rows.push(PlanRow::new("env", detail));
+		}
+
+		if let Some(module) = &step.module {
+			rows.push(PlanRow::new("module", module.display().to_string()));
+		}
+
+		for hook in &step.hooks {
+			rows.push(PlanRow::new("hook", format!("{hook:?}")));
+		}
+
+		if !rows.is_empty() {
+			println!("{}", tableify(rows, false));
+		}
+
+		println!();
+	}
+
+	for failed in &plan.failed {
+		println!("{} cannot be planned: {}", failed.name.red(), failed.error);
+	}
+
+	if !plan.failed.is_empty() {
+		println!();
+	}
+
+	Ok(())
+}
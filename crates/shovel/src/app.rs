@@ -4,8 +4,13 @@ use std::iter::Filter;
 use std::path::Path;
 use std::path::PathBuf;
 
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
 use thiserror;
 
+use crate::bucket::Name;
+use crate::bucket::Source;
 use crate::json;
 use crate::manifest::Arch;
 use crate::manifest::Manifest;
@@ -54,6 +59,32 @@ json::json_struct! {
 
 		/// The bucket the app originated from.
 		pub bucket: String,
+
+		/// Where the app's manifest came from.
+		/// This is None if the app was installed before sources were recorded, or by Scoop.
+		pub source: Option<Source>,
+	}
+}
+
+/// A feature suggested by an app's manifest. Created by the `suggestions` method on `Apps`.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Suggestion {
+	/// The feature's name.
+	pub feature: String,
+
+	/// The apps that provide the feature, in order of preference.
+	/// Each app may be qualified with a bucket, i.e., `bucket/app`.
+	pub apps: Vec<String>,
+
+	/// The first app providing the feature that is installed, if any.
+	pub installed: Option<String>,
+}
+
+impl Suggestion {
+	/// Checks if the feature is provided by an installed app.
+	#[must_use]
+	pub fn is_satisfied(&self) -> bool {
+		self.installed.is_some()
 	}
 }
 
@@ -141,6 +172,39 @@ impl App {
 
 		Ok(metadata)
 	}
+
+	/// Writes the app's metadata.
+	/// Fields that are not part of [`Metadata`], e.g., Scoop's `hold`, are kept.
+	///
+	/// # Arguments
+	///
+	/// * `metadata` - The metadata to write.
+	///
+	/// # Errors
+	///
+	/// If the metadata file cannot be read or written, [`Error::Io`] or [`Error::Json`] is returned.
+	pub fn set_metadata(&self, metadata: &Metadata) -> Result<()> {
+		let path = self.metadata_path();
+
+		let mut fields: Map<String, Value> = match fs::File::open(&path) {
+			Ok(file) => json::from_reader(file)?,
+			Err(err) if err.kind() == io::ErrorKind::NotFound => Map::new(),
+			Err(err) => return Err(err.into()),
+		};
+
+		if let Value::Object(known) = json::to_value(metadata)? {
+			fields.extend(known);
+		}
+
+		// A source of None is not serialized, so it would not replace an existing source.
+		if metadata.source.is_none() {
+			fields.remove("source");
+		}
+
+		util::write_atomic(path, json::to_string(&fields)?.as_bytes())?;
+
+		Ok(())
+	}
 }
 
 /// An iterator over apps. Created by the `iter` method on `Apps`.
@@ -329,4 +393,265 @@ impl Apps {
 	pub fn open_current(&self, name: &str) -> Result<App> {
 		self.open(name, CURRENT)
 	}
+
+	/// Returns the versions of an app that are installed but not in use, i.e., not linked to by `current`.
+	///
+	/// # Arguments
+	///
+	/// * `name` - The name of the app.
+	///
+	/// # Errors
+	///
+	/// If the current version does not exist, [`Error::NotFound`] is returned.
+	/// No versions are considered old in this case, as the version in use cannot be determined.
+	///
+	/// If the app directory cannot be read, [`Error::Io`] is returned.
+	pub fn old_versions(&self, name: &str) -> Result<Vec<App>> {
+		let current = fs::canonicalize(self.open_current(name)?.dir())?;
+
+		let mut old = vec![];
+
+		for app in self.versions(name)? {
+			if fs::canonicalize(app.dir())? != current {
+				old.push(app);
+			}
+		}
+
+		Ok(old)
+	}
+
+	/// Links a version of an app whose files are already in its version directory, see [`path`].
+	/// The app's manifest and metadata are written, and `current` is linked to the version,
+	/// replacing the link to any other version.
+	///
+	/// [`path`]: Self::path
+	///
+	/// # Arguments
+	///
+	/// * `name` - The name of the app.
+	/// * `manifest` - The app's manifest. Its version is the version linked.
+	/// * `metadata` - The app's metadata.
+	///
+	/// # Errors
+	///
+	/// If the version directory does not exist, [`Error::NotFound`] is returned.
+	///
+	/// If the manifest, metadata or link cannot be written, [`Error::Io`] or [`Error::Json`] is returned.
+	pub fn link(&self, name: &str, manifest: &Manifest, metadata: &Metadata) -> Result<App> {
+		let app = self.open(name, &manifest.version)?;
+
+		util::write_atomic(app.manifest_path(), json::to_string(manifest)?.as_bytes())?;
+		app.set_metadata(metadata)?;
+
+		self.unlink(name)?;
+		util::link_dir(app.dir(), self.current_path(name))?;
+
+		Ok(app)
+	}
+
+	/// Unlinks the current version of an app by removing `current`, so no version is in use.
+	/// The version directories are kept. If the app is not linked, this is a no-op.
+	///
+	/// # Arguments
+	///
+	/// * `name` - The name of the app.
+	///
+	/// # Errors
+	///
+	/// If the link cannot be removed, [`Error::Io`] is returned.
+	pub fn unlink(&self, name: &str) -> Result<()> {
+		let current = self.current_path(name);
+
+		if current.symlink_metadata().is_ok() {
+			util::unlink_dir(current)?;
+		}
+
+		Ok(())
+	}
+
+	/// Returns the features suggested by a manifest, sorted by name,
+	/// and whether any app providing each feature is installed.
+	///
+	/// # Arguments
+	///
+	/// * `manifest` - The manifest to get suggestions for.
+	///
+	/// # Errors
+	///
+	/// If an app's installation cannot be checked, [`Error::Io`] is returned.
+	pub fn suggestions(&self, manifest: &Manifest) -> Result<Vec<Suggestion>> {
+		let Some(suggest) = manifest.suggest.as_ref() else {
+			return Ok(vec![]);
+		};
+
+		let mut suggestions = vec![];
+
+		for (feature, apps) in suggest {
+			let mut installed = None;
+
+			for app in apps.iter() {
+				// Installed apps are stored by name only, so the bucket is ignored.
+				let name = Name::new(app.clone());
+
+				match self.open_current(name.manifest()) {
+					Ok(_) => {
+						installed = Some(app.clone());
+						break;
+					}
+					Err(Error::NotFound { .. }) => {}
+					Err(err) => return Err(err),
+				}
+			}
+
+			suggestions.push(Suggestion {
+				feature: feature.clone(),
+				apps: apps.to_vec(),
+				installed,
+			});
+		}
+
+		suggestions.sort_by(|a, b| a.feature.cmp(&b.feature));
+
+		Ok(suggestions)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use std::fs;
+
+	use super::*;
+	use crate::list;
+
+	#[test]
+	fn old_versions() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let apps = Apps::new(temp_dir.path());
+
+		for version in ["1.0", "2.0"] {
+			fs::create_dir_all(apps.path("app", version)).unwrap();
+		}
+
+		assert!(matches!(
+			apps.old_versions("app"),
+			Err(Error::NotFound { .. })
+		));
+
+		util::link_dir(apps.path("app", "2.0"), apps.current_path("app")).unwrap();
+
+		let old: Vec<_> = apps
+			.old_versions("app")
+			.unwrap()
+			.into_iter()
+			.map(|app| app.dir().to_owned())
+			.collect();
+
+		assert_eq!(old, [apps.path("app", "1.0")]);
+	}
+
+	#[test]
+	fn link() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let apps = Apps::new(temp_dir.path());
+
+		for version in ["1.0", "2.0"] {
+			fs::create_dir_all(apps.path("app", version)).unwrap();
+		}
+
+		let manifest = |version: &str| Manifest {
+			version: version.to_owned(),
+			..Default::default()
+		};
+		let metadata = Metadata {
+			bucket: "main".to_owned(),
+			..Default::default()
+		};
+
+		apps.link("app", &manifest("1.0"), &metadata).unwrap();
+
+		// Linking another version replaces the link.
+		let app = apps.link("app", &manifest("2.0"), &metadata).unwrap();
+		let current = apps.open_current("app").unwrap();
+
+		assert_eq!(
+			fs::canonicalize(current.dir()).unwrap(),
+			fs::canonicalize(app.dir()).unwrap()
+		);
+		assert_eq!(current.manifest().unwrap(), manifest("2.0"));
+		assert_eq!(current.metadata().unwrap(), metadata);
+
+		assert!(matches!(
+			apps.link("app", &manifest("3.0"), &metadata),
+			Err(Error::NotFound { .. })
+		));
+
+		apps.unlink("app").unwrap();
+		apps.unlink("app").unwrap();
+
+		assert!(matches!(
+			apps.open_current("app"),
+			Err(Error::NotFound { .. })
+		));
+		assert_eq!(apps.versions("app").unwrap().count(), 2);
+	}
+
+	#[test]
+	fn set_metadata() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let app = App::open(temp_dir.path());
+
+		fs::write(
+			app.metadata_path(),
+			r#"{"bucket":"main","architecture":"64bit","hold":true}"#,
+		)
+		.unwrap();
+
+		let mut metadata = app.metadata().unwrap();
+		metadata.bucket = "extras".to_owned();
+		app.set_metadata(&metadata).unwrap();
+
+		assert_eq!(app.metadata().unwrap(), metadata);
+
+		let fields: Value =
+			json::from_reader(fs::File::open(app.metadata_path()).unwrap()).unwrap();
+		assert_eq!(fields["hold"], true);
+	}
+
+	#[test]
+	fn suggestions() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let apps = Apps::new(temp_dir.path());
+
+		fs::create_dir_all(apps.current_path("vcredist2022")).unwrap();
+
+		let manifest = Manifest {
+			suggest: Some(HashMap::from([
+				(
+					"Visual C++ Runtime".to_owned(),
+					list!["extras/vcredist2022".into(), "vcredist".into()],
+				),
+				("JDK".to_owned(), list!["java/openjdk".into()]),
+			])),
+			..Default::default()
+		};
+
+		let suggestions = apps.suggestions(&manifest).unwrap();
+
+		assert_eq!(
+			suggestions,
+			[
+				Suggestion {
+					feature: "JDK".to_owned(),
+					apps: vec!["java/openjdk".to_owned()],
+					installed: None,
+				},
+				Suggestion {
+					feature: "Visual C++ Runtime".to_owned(),
+					apps: vec!["extras/vcredist2022".to_owned(), "vcredist".to_owned()],
+					installed: Some("extras/vcredist2022".to_owned()),
+				},
+			]
+		);
+	}
 }
@@ -1,21 +1,59 @@
+mod auth;
 #[allow(clippy::module_inception)]
 mod bucket;
 mod buckets;
+mod changes;
+mod clone;
 mod criteria;
 mod error;
+mod history;
+mod index;
+mod known;
+mod layout;
 mod name;
+mod pin;
+mod signature;
+mod versioned;
 
+pub use auth::Credentials;
+pub use auth::SSH_KEYS_VAR;
+pub use auth::SSH_KEY_CONFIG;
+pub use auth::TOKEN_VAR;
 pub use bucket::Bucket;
+pub use bucket::Commit;
 pub use bucket::Commits;
+pub use bucket::Kind;
 pub use bucket::Manifests;
+pub use bucket::Pull;
 pub use bucket::Search;
 pub use bucket::SearchItem;
+pub use bucket::SummaryItem;
+pub use bucket::Upstream;
 pub use buckets::AllManifests;
 pub use buckets::Buckets;
 pub use buckets::Iter;
 pub use buckets::SearchAll;
+pub use changes::Change;
+pub use clone::CloneOptions;
+pub use criteria::And;
+pub use criteria::BucketList;
 pub use criteria::Criteria;
+pub use criteria::Field;
+pub use criteria::Not;
+pub use criteria::Or;
+pub use criteria::Pattern;
 pub use criteria::Predicate;
 pub use error::Error;
 pub use error::Result;
+pub use history::Revision;
+pub use index::Index;
+pub use index::Summary;
+pub use known::KnownBucket;
+pub use known::KnownBuckets;
+pub use known::KnownEntry;
+pub use known::Origin;
+pub use layout::Layout;
 pub use name::Name;
+pub use pin::Pin;
+pub use signature::TrustedKey;
+pub use versioned::Source;
@@ -0,0 +1,408 @@
+use std::collections::HashSet;
+use std::env;
+use std::path::PathBuf;
+
+use git2::Cred;
+use git2::CredentialType;
+use git2::FetchOptions;
+use git2::RemoteCallbacks;
+
+/// The environment variable with a token for HTTPS remotes, i.e., a personal access token.
+pub const TOKEN_VAR: &str = "SHOVEL_GIT_TOKEN";
+
+/// The environment variable with SSH private key files to try, separated like `PATH`.
+pub const SSH_KEYS_VAR: &str = "SHOVEL_SSH_KEYS";
+
+/// The key in the Git config with an SSH private key file to try. It may be set more than once.
+pub const SSH_KEY_CONFIG: &str = "shovel.sshKey";
+
+/// The key files in `~/.ssh` that are tried if they exist, like OpenSSH does.
+const DEFAULT_KEYS: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// The user name for SSH remotes that do not specify one, as used by most Git hosts.
+const SSH_USER: &str = "git";
+
+/// The user name sent with a token if the remote does not specify one.
+/// Git hosts ignore it, but it must not be empty.
+const TOKEN_USER: &str = "x-access-token";
+
+/// A source of credentials that is tried once. Key files are tried one by one instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Method {
+	Username,
+	Agent,
+	Helper,
+	Token,
+}
+
+/// A source of credentials, in the order they are tried.
+#[derive(Debug, PartialEq, Eq)]
+enum Attempt {
+	Username(String),
+	Agent(String),
+	KeyFile { username: String, path: PathBuf },
+	Helper,
+	Token { username: String, token: String },
+}
+
+/// Supplies credentials for private buckets. See [`Credentials::register`].
+///
+/// Each source is tried once, in order:
+/// * For SSH remotes, the SSH agent, then the key files in `SHOVEL_SSH_KEYS`, then the key files in the
+///   `shovel.sshKey` entries of the global Git config, then the default key files in `~/.ssh`.
+/// * For HTTPS remotes, the Git credential helpers, then the token in `SHOVEL_GIT_TOKEN`.
+pub struct Credentials {
+	keys: Vec<PathBuf>,
+	token: Option<String>,
+	tried: HashSet<Method>,
+	next_key: usize,
+}
+
+impl Credentials {
+	/// Reads the key files and token to try from the environment, the global Git config and `~/.ssh`.
+	#[must_use]
+	pub fn from_env() -> Self {
+		let mut keys: Vec<PathBuf> = env::var_os(SSH_KEYS_VAR)
+			.map(|keys| env::split_paths(&keys).collect())
+			.unwrap_or_default();
+
+		// A missing or invalid config only means there are no configured key files.
+		if let Ok(config) = git2::Config::open_default() {
+			keys.extend(Self::config_keys(&config));
+		}
+
+		if let Some(home) = home::home_dir() {
+			let ssh_dir = home.join(".ssh");
+
+			keys.extend(
+				DEFAULT_KEYS
+					.iter()
+					.map(|key| ssh_dir.join(key))
+					.filter(|path| path.is_file()),
+			);
+		}
+
+		let token = env::var(TOKEN_VAR).ok().filter(|token| !token.is_empty());
+
+		Self::new(keys, token)
+	}
+
+	/// Returns the key files in the `shovel.sshKey` entries of a Git config.
+	/// Like other paths in Git configs, a leading `~/` is the home directory.
+	fn config_keys(config: &git2::Config) -> Vec<PathBuf> {
+		let mut keys = vec![];
+
+		let Ok(mut entries) = config.multivar(SSH_KEY_CONFIG, None) else {
+			return keys;
+		};
+
+		while let Some(Ok(entry)) = entries.next() {
+			let Some(value) = entry.value() else {
+				continue;
+			};
+
+			let path = match (value.strip_prefix("~/"), home::home_dir()) {
+				(Some(rest), Some(home)) => home.join(rest),
+				_ => PathBuf::from(value),
+			};
+
+			keys.push(path);
+		}
+
+		keys
+	}
+
+	fn new(keys: Vec<PathBuf>, token: Option<String>) -> Self {
+		Self {
+			keys,
+			token,
+			tried: HashSet::new(),
+			next_key: 0,
+		}
+	}
+
+	/// Marks a method as tried, and returns whether it was not tried before.
+	fn try_once(&mut self, method: Method) -> bool {
+		self.tried.insert(method)
+	}
+
+	/// Registers the credentials as the credentials callback of a set of remote callbacks.
+	///
+	/// # Arguments
+	///
+	/// * `callbacks` - The remote callbacks to register with.
+	pub fn register(self, callbacks: &mut RemoteCallbacks) {
+		let mut credentials = self;
+
+		callbacks.credentials(move |url, username, allowed| {
+			credentials.credential(url, username, allowed)
+		});
+	}
+
+	/// Returns a set of fetch options with only the credentials callback.
+	/// This is what [`Buckets::add`] and [`Bucket::pull`] use if no fetch options are given.
+	///
+	/// [`Buckets::add`]: crate::bucket::Buckets::add
+	/// [`Bucket::pull`]: crate::bucket::Bucket::pull
+	#[must_use]
+	pub fn into_fetch_options<'a>(self) -> FetchOptions<'a> {
+		let mut callbacks = RemoteCallbacks::new();
+		self.register(&mut callbacks);
+
+		let mut fetch_options = FetchOptions::new();
+		fetch_options.remote_callbacks(callbacks);
+
+		fetch_options
+	}
+
+	/// Returns the next source to try, or None if every allowed source was tried.
+	///
+	/// Git asks again whenever credentials are rejected, so sources are never repeated to avoid asking forever.
+	fn next(&mut self, username: Option<&str>, allowed: CredentialType) -> Option<Attempt> {
+		// SSH remotes without a user name in the URL ask for one first.
+		if allowed.contains(CredentialType::USERNAME) && self.try_once(Method::Username) {
+			return Some(Attempt::Username(username.unwrap_or(SSH_USER).to_owned()));
+		}
+
+		if allowed.contains(CredentialType::SSH_KEY) {
+			let username = username.unwrap_or(SSH_USER).to_owned();
+
+			if self.try_once(Method::Agent) {
+				return Some(Attempt::Agent(username));
+			}
+
+			if let Some(path) = self.keys.get(self.next_key) {
+				self.next_key += 1;
+
+				return Some(Attempt::KeyFile {
+					username,
+					path: path.clone(),
+				});
+			}
+		}
+
+		if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+			if self.try_once(Method::Helper) {
+				return Some(Attempt::Helper);
+			}
+
+			if self.try_once(Method::Token) {
+				if let Some(token) = &self.token {
+					return Some(Attempt::Token {
+						username: username.unwrap_or(TOKEN_USER).to_owned(),
+						token: token.clone(),
+					});
+				}
+			}
+		}
+
+		None
+	}
+
+	fn credential(
+		&mut self,
+		url: &str,
+		username: Option<&str>,
+		allowed: CredentialType,
+	) -> std::result::Result<Cred, git2::Error> {
+		while let Some(attempt) = self.next(username, allowed) {
+			let cred = match attempt {
+				Attempt::Username(username) => Cred::username(&username),
+				Attempt::Agent(username) => Cred::ssh_key_from_agent(&username),
+				Attempt::KeyFile { username, path } => Cred::ssh_key(&username, None, &path, None),
+				Attempt::Helper => git2::Config::open_default()
+					.and_then(|config| Cred::credential_helper(&config, url, username)),
+				Attempt::Token { username, token } => Cred::userpass_plaintext(&username, &token),
+			};
+
+			// Sources that cannot supply credentials, e.g., when no credential helper is configured, are skipped.
+			if let Ok(cred) = cred {
+				return Ok(cred);
+			}
+		}
+
+		Err(git2::Error::from_str(&format!(
+			"No credentials were accepted for {url}, \
+			 set {TOKEN_VAR} for HTTPS, or {SSH_KEYS_VAR} or {SSH_KEY_CONFIG} for SSH"
+		)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::io::BufRead;
+	use std::io::BufReader;
+	use std::io::Write;
+	use std::net::TcpListener;
+	use std::sync::Arc;
+	use std::sync::Mutex;
+	use std::thread;
+
+	use super::*;
+	use crate::bucket::Bucket;
+	use crate::bucket::CloneOptions;
+	use crate::bucket::Pull;
+	use crate::test;
+
+	#[test]
+	fn ssh() {
+		let keys = vec![PathBuf::from("deploy_key"), PathBuf::from("id_rsa")];
+		let mut credentials = Credentials::new(keys, Some("token".to_owned()));
+
+		let allowed = CredentialType::SSH_KEY;
+		let username = |username: &str| username.to_owned();
+
+		assert_eq!(
+			credentials.next(None, CredentialType::USERNAME),
+			Some(Attempt::Username(username("git")))
+		);
+		assert_eq!(
+			credentials.next(Some("deploy"), allowed),
+			Some(Attempt::Agent(username("deploy")))
+		);
+
+		for key in ["deploy_key", "id_rsa"] {
+			assert_eq!(
+				credentials.next(Some("deploy"), allowed),
+				Some(Attempt::KeyFile {
+					username: username("deploy"),
+					path: PathBuf::from(key),
+				})
+			);
+		}
+
+		// The token is only for HTTPS remotes.
+		assert_eq!(credentials.next(Some("deploy"), allowed), None);
+	}
+
+	#[test]
+	fn https() {
+		let mut credentials = Credentials::new(vec![], Some("token".to_owned()));
+		let allowed = CredentialType::USER_PASS_PLAINTEXT;
+
+		assert_eq!(credentials.next(None, allowed), Some(Attempt::Helper));
+		assert_eq!(
+			credentials.next(None, allowed),
+			Some(Attempt::Token {
+				username: TOKEN_USER.to_owned(),
+				token: "token".to_owned(),
+			})
+		);
+		assert_eq!(credentials.next(None, allowed), None);
+
+		// Without a token, sources run out after the credential helpers.
+		let mut credentials = Credentials::new(vec![], None);
+
+		assert_eq!(credentials.next(None, allowed), Some(Attempt::Helper));
+		assert_eq!(credentials.next(None, allowed), None);
+		assert!(credentials
+			.credential("https://example.test/bucket.git", None, allowed)
+			.is_err());
+	}
+
+	#[test]
+	fn file_url() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let upstream_dir = temp_dir.path().join("upstream");
+		let json = test::manifest_json("1.0", "");
+
+		test::create_bucket(&upstream_dir, &[("app", &json)]);
+
+		let url = url::Url::from_directory_path(&upstream_dir).unwrap();
+
+		let mut options = CloneOptions::new();
+		options.fetch_options(Credentials::from_env().into_fetch_options());
+
+		let bucket = Bucket::clone(url.as_str(), temp_dir.path().join("bucket"), options).unwrap();
+
+		assert_eq!(bucket.manifest("app", false).unwrap().version, "1.0");
+		assert!(matches!(bucket.pull(None, None).unwrap(), Pull::UpToDate));
+	}
+
+	#[test]
+	fn config() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let path = temp_dir.path().join("config");
+
+		fs::write(
+			&path,
+			"[shovel]\n\tsshKey = /keys/deploy\n\tsshKey = ~/.ssh/work\n",
+		)
+		.unwrap();
+
+		let config = git2::Config::open(&path).unwrap();
+		let keys = Credentials::config_keys(&config);
+
+		assert_eq!(keys[0], PathBuf::from("/keys/deploy"));
+		assert!(!keys[1].starts_with("~"));
+		assert!(keys[1].ends_with(".ssh/work"));
+	}
+
+	/// Serves every request with `401 Unauthorized`, returning the `Authorization` headers that were sent.
+	fn serve_unauthorized() -> (String, Arc<Mutex<Vec<String>>>) {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}/bucket.git", listener.local_addr().unwrap());
+
+		let headers = Arc::new(Mutex::new(vec![]));
+		let sent = Arc::clone(&headers);
+
+		// The thread ends with the test process.
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				let Ok(mut stream) = stream else {
+					continue;
+				};
+
+				let reader = BufReader::new(&stream);
+
+				for line in reader.lines() {
+					let Ok(line) = line else {
+						break;
+					};
+
+					if line.is_empty() {
+						break;
+					}
+
+					if let Some((name, value)) = line.split_once(": ") {
+						if name.eq_ignore_ascii_case("authorization") {
+							sent.lock().unwrap().push(value.to_owned());
+						}
+					}
+				}
+
+				let _ = stream.write_all(
+					b"HTTP/1.1 401 Unauthorized\r\n\
+					  WWW-Authenticate: Basic realm=\"shovel\"\r\n\
+					  Content-Length: 0\r\n\
+					  Connection: close\r\n\r\n",
+				);
+			}
+		});
+
+		(url, headers)
+	}
+
+	#[test]
+	fn http_token() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let (url, headers) = serve_unauthorized();
+
+		let mut options = CloneOptions::new();
+		options.fetch_options(
+			Credentials::new(vec![], Some("secret".to_owned())).into_fetch_options(),
+		);
+
+		let Err(err) = Bucket::clone(&url, temp_dir.path().join("bucket"), options) else {
+			panic!("expected the credentials to be rejected");
+		};
+
+		// The token was sent as the password, and asking stopped once every source was tried.
+		assert!(headers
+			.lock()
+			.unwrap()
+			.contains(&"Basic eC1hY2Nlc3MtdG9rZW46c2VjcmV0".to_owned()));
+		assert!(err.to_string().contains(TOKEN_VAR));
+	}
+}
@@ -2,17 +2,37 @@ use std::fs;
 use std::io;
 use std::path::Path;
 use std::path::PathBuf;
+use std::vec;
 
 use git2::build;
 
+use crate::bucket::changes;
+use crate::bucket::clone;
+use crate::bucket::history::History;
+use crate::bucket::index;
+use crate::bucket::layout::DEPRECATED_DIR;
+use crate::bucket::pin;
+use crate::bucket::signature;
+use crate::bucket::versioned;
+use crate::bucket::Change;
+use crate::bucket::CloneOptions;
+use crate::bucket::Credentials;
 use crate::bucket::Criteria;
 use crate::bucket::Error;
+use crate::bucket::Index;
+use crate::bucket::Layout;
+use crate::bucket::Pin;
 use crate::bucket::Result;
+use crate::bucket::Revision;
+use crate::bucket::Source;
+use crate::bucket::Summary;
+use crate::bucket::TrustedKey;
 use crate::json;
 use crate::manifest::Manifest;
+use crate::timestamp::Timestamp;
 use crate::util;
 
-fn manifest_from_file<P>(path: P) -> Result<Manifest>
+pub(crate) fn manifest_from_file<P>(path: P) -> Result<Manifest>
 where
 	P: AsRef<Path>,
 {
@@ -26,13 +46,59 @@ where
 	Ok(manifest)
 }
 
-/// A collection of manifests in a Git repository.
+/// Returns the manifest name and path for a file in a bucket, if it is a manifest that satisfies the criteria.
+fn manifest_entry<C: Criteria>(path: PathBuf, criteria: &C) -> Option<(String, PathBuf)> {
+	let ext = path.extension().unwrap_or_default();
+
+	// If the path does not end in '.json', it is not a manifest.
+	if ext != "json" {
+		return None;
+	}
+
+	// Take only the file stem (i.e., 'example' for 'example.json')
+	let name = util::osstr_to_string(path.file_stem().unwrap());
+
+	// If the predicate does not match, the manifest is skipped.
+	if !criteria.filter_manifest(&name) {
+		return None;
+	}
+
+	Some((name, path))
+}
+
+/// The file in a [`Kind::Directory`] bucket's directory with the path to the directory of manifests it was added from.
+const DIRECTORY_FILE: &str = ".shovel-directory";
+
+/// What kind of directory a bucket is. See [`Bucket::kind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+	/// The bucket is a Git repository, which is updated by pulling.
+	Git,
+
+	/// The bucket is a plain directory of manifests, i.e., a folder on a network share.
+	/// It has no history, and it cannot be updated, as changes to the directory are seen immediately.
+	///
+	/// Only buckets added with [`Buckets::add_dir`] are directories. The bucket's own directory only records
+	/// the path to the manifests, so no link is needed.
+	///
+	/// [`Buckets::add_dir`]: crate::bucket::Buckets::add_dir
+	Directory,
+}
+
+/// A collection of manifests in a Git repository, or in a plain directory. See [`Kind`].
+///
+/// Manifest files are in `.json` format, either in a `bucket` directory or at the root of the repository.
+/// See [`Layout`] for details, and refer to [`crate::manifest::Manifest`] for the schema.
+///
+/// Retired manifests may be moved to a `deprecated` directory, where they are not searched.
 ///
-/// The repository must have a `bucket` directory, with manifest files in `.json` format.
-/// Refer to [`crate::manifest::Manifest`] for the schema.
+/// The repository is opened on demand, so buckets can be shared across threads.
+#[derive(Clone)]
 pub struct Bucket {
 	dir: PathBuf,
-	repo: git2::Repository,
+	kind: Kind,
+	/// Where the manifests are. This is `dir`, unless the bucket is a [`Kind::Directory`].
+	root: PathBuf,
 }
 
 impl Bucket {
@@ -41,18 +107,70 @@ impl Bucket {
 	/// # Arguments
 	///
 	/// * `dir` - The path to the bucket. It must point to a directory.
+	///   Directories added with [`Buckets::add_dir`] are opened as [`Kind::Directory`].
 	///
 	/// # Errors
 	///
-	/// If dir is not a valid Git repository, [`Error::Git`] is returned.
+	/// If dir does not exist, [`Error::NotFound`] is returned.
+	///
+	/// If dir is neither a Git repository nor an added directory, i.e., a clone that did not finish,
+	/// [`Error::NotBucket`] is returned.
+	///
+	/// If dir has Git metadata, but it is not a valid Git repository, [`Error::Git`] is returned.
+	///
+	/// If the path of an added directory cannot be read, [`Error::Io`] is returned.
+	///
+	/// [`Buckets::add_dir`]: crate::bucket::Buckets::add_dir
 	pub fn open<P>(dir: P) -> Result<Self>
 	where
 		P: AsRef<Path>,
 	{
 		let dir = dir.as_ref().to_owned();
-		let repo = git2::Repository::open(&dir)?;
+		let directory_file = dir.join(DIRECTORY_FILE);
+
+		if dir.join(".git").exists() {
+			// Make sure the bucket is a repository.
+			git2::Repository::open(&dir)?;
+
+			Ok(Bucket {
+				root: dir.clone(),
+				dir,
+				kind: Kind::Git,
+			})
+		} else if directory_file.is_file() {
+			let root = PathBuf::from(fs::read_to_string(directory_file)?.trim_end());
 
-		Ok(Bucket { dir, repo })
+			Ok(Bucket {
+				dir,
+				kind: Kind::Directory,
+				root,
+			})
+		} else if dir.is_dir() {
+			Err(Error::NotBucket)
+		} else {
+			Err(Error::NotFound)
+		}
+	}
+
+	/// Creates a bucket for a directory of manifests. See [`Kind::Directory`].
+	///
+	/// # Arguments
+	///
+	/// * `dir` - The path to the bucket. It must not exist yet.
+	/// * `root` - The absolute path to the directory of manifests.
+	///
+	/// # Errors
+	///
+	/// If the bucket directory cannot be created, [`Error::Io`] is returned.
+	pub(crate) fn create_dir(dir: PathBuf, root: PathBuf) -> Result<Self> {
+		fs::create_dir(&dir)?;
+		fs::write(dir.join(DIRECTORY_FILE), util::path_to_string(&root))?;
+
+		Ok(Bucket {
+			dir,
+			kind: Kind::Directory,
+			root,
+		})
 	}
 
 	/// Clone a remote bucket.
@@ -61,32 +179,120 @@ impl Bucket {
 	///
 	/// * `url` - The Git URL of the remote bucket.
 	/// * `dir` - The path to clone to. It must not exist yet.
-	/// * `builder` - The builder to clone with. If None, a new builder is created.
+	/// * `options` - The options to clone with. Without fetch options, [`Credentials::from_env`] is used.
 	///
 	/// # Errors
 	///
 	/// If the remote bucket failed to clone, [`Error::Git`] is returned.
-	pub fn clone<P>(url: &str, dir: P, builder: Option<&mut build::RepoBuilder>) -> Result<Self>
+	///
+	/// If a sparse checkout cannot be set up, [`Error::Io`] or [`Error::Git`] is returned.
+	pub fn clone<P>(url: &str, dir: P, options: CloneOptions) -> Result<Self>
 	where
 		P: AsRef<Path>,
 	{
 		let dir = dir.as_ref().to_owned();
 
-		// A temporary builder is needed if the one passed in is None.
-		let mut temp_builder = build::RepoBuilder::new();
+		let CloneOptions {
+			depth,
+			sparse,
+			fetch_options,
+			checkout_builder,
+		} = options;
+
+		let mut fetch_options =
+			fetch_options.unwrap_or_else(|| Credentials::from_env().into_fetch_options());
+		let mut checkout_builder = checkout_builder.unwrap_or_default();
+
+		if let Some(depth) = depth {
+			fetch_options.depth(i32::try_from(depth).unwrap_or(i32::MAX));
+		}
+
+		// The layout is not known until the tree is fetched, so other files are checked out afterwards if needed.
+		if sparse {
+			clone::limit_checkout(&mut checkout_builder);
+		}
+
+		let repo = build::RepoBuilder::new()
+			.fetch_options(fetch_options)
+			.with_checkout(checkout_builder)
+			.clone(url, &dir)?;
 
-		let builder = builder.unwrap_or(&mut temp_builder);
-		let repo = builder.clone(url, &dir)?;
+		if sparse {
+			clone::enable_sparse(&repo)?;
+		}
 
-		Ok(Bucket { dir, repo })
+		Ok(Bucket {
+			root: dir.clone(),
+			dir,
+			kind: Kind::Git,
+		})
 	}
 
 	/// Returns the bucket directory.
+	/// For a [`Kind::Directory`], this is not where the manifests are, see [`url`].
+	///
+	/// [`url`]: Bucket::url
 	#[must_use]
 	pub fn dir(&self) -> &Path {
 		&self.dir
 	}
 
+	/// Returns whether the bucket is a Git repository or a plain directory.
+	#[must_use]
+	pub fn kind(&self) -> Kind {
+		self.kind
+	}
+
+	/// Checks if the bucket is a shallow clone, i.e., it does not have its full history.
+	#[must_use]
+	pub fn is_shallow(&self) -> bool {
+		self.repo().is_ok_and(|repo| repo.is_shallow())
+	}
+
+	/// Checks if the bucket has a sparse checkout, i.e., only the manifests and scripts are in its working tree.
+	#[must_use]
+	pub fn is_sparse(&self) -> bool {
+		self.repo().is_ok_and(|repo| clone::is_sparse(&repo))
+	}
+
+	/// Fetches the full history of a shallow clone from the bucket's [`upstream`].
+	///
+	/// # Arguments
+	///
+	/// * `fetch_options` - The options to use for fetching. If None, [`Credentials::from_env`] is used.
+	///
+	/// # Errors
+	///
+	/// If the upstream remote does not exist, or fetching failed, [`Error::Git`] is returned.
+	///
+	/// [`upstream`]: Bucket::upstream
+	pub fn unshallow(&self, fetch_options: Option<git2::FetchOptions>) -> Result<()> {
+		let repo = self.repo()?;
+		let Upstream { remote, branch } = Self::upstream_of(&repo)?;
+
+		let mut fetch_options =
+			fetch_options.unwrap_or_else(|| Credentials::from_env().into_fetch_options());
+		fetch_options.depth(clone::UNSHALLOW);
+
+		let refspec = format!("+refs/heads/{branch}:refs/remotes/{remote}/{branch}");
+		repo.find_remote(&remote)?
+			.fetch(&[&refspec], Some(&mut fetch_options), None)?;
+
+		Ok(())
+	}
+
+	/// Returns where the bucket stores its manifests.
+	#[must_use]
+	pub fn layout(&self) -> Layout {
+		Layout::detect(&self.root)
+	}
+
+	/// Returns the directory where the bucket stores its manifests.
+	#[must_use]
+	pub fn manifest_dir(&self) -> PathBuf {
+		self.root.join(self.layout().manifest_dir())
+	}
+
 	/// Returns the bucket name.
 	#[must_use]
 	pub fn name(&self) -> String {
@@ -96,44 +302,89 @@ impl Bucket {
 		name.to_string_lossy().into_owned()
 	}
 
+	/// Opens and returns the bucket's Git repository.
+	///
+	/// # Errors
+	///
+	/// If the bucket is a [`Kind::Directory`], [`Error::NotGit`] is returned.
+	///
+	/// If the repository cannot be opened, [`Error::Git`] is returned.
+	pub fn repo(&self) -> Result<git2::Repository> {
+		if self.kind == Kind::Directory {
+			return Err(Error::NotGit);
+		}
+
+		Ok(git2::Repository::open(&self.dir)?)
+	}
+
 	/// Returns the bucket URL, i.e., where it was cloned from.
+	/// For a [`Kind::Directory`], this is the directory it was added from.
 	///
 	/// # Errors
 	///
-	/// If the bucket does not have a origin, [`Error::Git`] is returned.
+	/// If the bucket's upstream remote does not exist, [`Error::Git`] is returned.
 	pub fn url(&self) -> Result<String> {
-		Ok(self.origin()?.url().unwrap_or("").to_owned())
+		if self.kind == Kind::Directory {
+			return Ok(util::path_to_string(&self.root));
+		}
+
+		let repo = self.repo()?;
+		// If HEAD is not a branch, there is no upstream to read the remote from.
+		let name = Self::upstream_of(&repo).map_or_else(|_| "origin".to_owned(), |u| u.remote);
+		let remote = repo.find_remote(&name)?;
+
+		Ok(remote.url().unwrap_or("").to_owned())
 	}
 
-	fn origin(&self) -> Result<git2::Remote> {
-		let origin = self.repo.find_remote("origin")?;
+	/// Sets the URL of the remote the bucket pulls from, i.e., when the bucket moved to another host.
+	///
+	/// # Arguments
+	///
+	/// * `url` - The new Git URL of the remote bucket.
+	///
+	/// # Errors
+	///
+	/// If the remote does not exist, or the repository's config cannot be written, [`Error::Git`] is returned.
+	pub fn set_url(&self, url: &str) -> Result<()> {
+		let repo = self.repo()?;
+		let name = Self::upstream_of(&repo).map_or_else(|_| "origin".to_owned(), |u| u.remote);
+
+		// Make sure the remote exists.
+		repo.find_remote(&name)?;
+		repo.remote_set_url(&name, url)?;
 
-		Ok(origin)
+		Ok(())
 	}
 
 	/// Returns the HEAD commit of the bucket.
+	/// If the bucket is a [`Kind::Directory`], None is returned.
 	///
 	/// # Errors
 	///
 	/// If the bucket does not have a HEAD or HEAD commit, [`Error::Git`] is returned.
-	pub fn commit(&self) -> Result<git2::Commit> {
-		let head = self.repo.head()?;
-		let commit = head.peel_to_commit()?;
+	pub fn commit(&self) -> Result<Option<Commit>> {
+		if self.kind == Kind::Directory {
+			return Ok(None);
+		}
+
+		let repo = self.repo()?;
+		let commit = repo.head()?.peel_to_commit()?;
 
-		Ok(commit)
+		Ok(Some(Commit::from(&commit)))
 	}
 
-	/// Yields the commits made to the bucket from HEAD until the commit pointed to by `since`.
+	/// Yields the commits reachable from HEAD that are not reachable from `since`, newest first.
+	/// If HEAD was reset to a rewritten history, only the commits since the histories diverged are yielded.
 	///
 	/// # Arguments
 	///
-	/// * `since` - The commit ID to yield until.
+	/// * `since` - The commit ID to yield until, i.e., HEAD before pulling.
 	///
 	/// # Errors
 	///
-	/// If the Git revwalk failed, [`Error::Git`] is returned.
-	pub fn commits(&self, since: git2::Oid) -> Result<Commits<'_>> {
-		Commits::new(&self.repo, since)
+	/// If `since` is not in the repository, or the Git revwalk failed, [`Error::Git`] is returned.
+	pub fn commits(&self, since: git2::Oid) -> Result<Commits> {
+		Commits::new(self.repo()?, since)
 	}
 
 	/// Parses and yields each manifest in the bucket as (name, manifest) where the criteria is satisfied.
@@ -146,9 +397,33 @@ impl Bucket {
 	///
 	/// If the bucket directory cannot be read, [`Error::Io`] is returned.
 	pub fn search<C: Criteria>(&self, criteria: C) -> Result<Search<C>> {
-		let dir = self.dir().join("bucket");
+		Search::new(self.clone(), criteria)
+	}
+
+	/// Returns the name and path of each manifest in the bucket where the criteria is satisfied, sorted by name.
+	/// Unlike [`search`], the manifests are not parsed.
+	///
+	/// # Arguments
+	///
+	/// `criteria` - The criteria that determines if a manifest should be returned.
+	///
+	/// # Errors
+	///
+	/// If the bucket directory cannot be read, [`Error::Io`] is returned.
+	///
+	/// [`search`]: Bucket::search
+	pub fn manifest_paths<C: Criteria>(&self, criteria: &C) -> Result<Vec<(String, PathBuf)>> {
+		let mut paths = vec![];
+
+		for entry in fs::read_dir(self.manifest_dir())? {
+			if let Some(path) = manifest_entry(entry?.path(), criteria) {
+				paths.push(path);
+			}
+		}
 
-		Search::new(dir, criteria)
+		paths.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+		Ok(paths)
 	}
 
 	/// Parses and yields each manifest in the bucket as (name, manifest).
@@ -161,10 +436,240 @@ impl Bucket {
 		self.search(())
 	}
 
+	/// Returns the changes to manifests between two commits, sorted by manifest name.
+	///
+	/// Added, removed and renamed manifests are included, and so are manifests whose version changed.
+	/// Other changes, such as fixing a manifest's hash, are not included.
+	///
+	/// # Arguments
+	///
+	/// * `from` - The old commit, i.e., HEAD before pulling.
+	/// * `to` - The new commit. It does not need to descend from `from`.
+	///
+	/// # Errors
+	///
+	/// If either commit is not in the repository, or the trees cannot be compared, [`Error::Git`] is returned.
+	pub fn changes(&self, from: git2::Oid, to: git2::Oid) -> Result<Vec<Change>> {
+		let repo = self.repo()?;
+		let old = repo.find_commit(from)?.tree()?;
+		let new = repo.find_commit(to)?.tree()?;
+
+		changes::diff(&repo, self.layout(), &old, &new)
+	}
+
+	/// Returns the path to the bucket's index, which is in its Git directory.
+	///
+	/// # Errors
+	///
+	/// If the bucket is a [`Kind::Directory`], [`Error::NotGit`] is returned.
+	///
+	/// If the repository cannot be opened, [`Error::Git`] is returned.
+	pub fn index_path(&self) -> Result<PathBuf> {
+		// The Git directory is not always `.git`, e.g., in worktrees and submodules it is given by a `.git` file.
+		Ok(self.repo()?.path().join(index::INDEX_FILE))
+	}
+
+	/// Returns the bucket's index of manifests at the HEAD commit.
+	///
+	/// The index is stored on disk and rebuilt if HEAD has changed since it was last built.
+	/// If the previous commit is still in the repository, only manifests that changed since then are parsed again.
+	///
+	/// A [`Kind::Directory`] has no commit to tell when it changed, so its index is built from the working tree
+	/// every time, with an empty commit.
+	///
+	/// # Errors
+	///
+	/// [`Error::Git`] is returned if the bucket does not have a HEAD commit, or its tree cannot be read.
+	///
+	/// [`Error::Io`] or [`Error::Json`] is returned if the index cannot be saved.
+	pub fn index(&self) -> Result<Index> {
+		if self.kind == Kind::Directory {
+			return self.build_dir_index();
+		}
+
+		let path = self.index_path()?;
+		let repo = self.repo()?;
+		let head = repo.head()?.peel_to_commit()?;
+
+		// Only update incrementally if the old commit still exists, i.e., it was not lost to a force-push.
+		let index = match Index::load(&path) {
+			Some(index) if index.commit == head.id().to_string() => return Ok(index),
+			Some(index) => match Self::find_commit_str(&repo, &index.commit) {
+				Some(old) => Self::update_index(&repo, self.layout(), index, &old, &head)?,
+				None => Self::build_index(&repo, self.layout(), &head)?,
+			},
+			None => Self::build_index(&repo, self.layout(), &head)?,
+		};
+
+		index.save(&path)?;
+
+		Ok(index)
+	}
+
+	fn build_dir_index(&self) -> Result<Index> {
+		let mut index = Index::default();
+
+		for (name, path) in self.manifest_paths(&())? {
+			index.insert(name, &fs::read(path)?);
+		}
+
+		Ok(index)
+	}
+
+	fn find_commit_str<'r>(repo: &'r git2::Repository, id: &str) -> Option<git2::Commit<'r>> {
+		let oid = git2::Oid::from_str(id).ok()?;
+
+		repo.find_commit(oid).ok()
+	}
+
+	fn build_index(repo: &git2::Repository, layout: Layout, head: &git2::Commit) -> Result<Index> {
+		let mut index = Index {
+			commit: head.id().to_string(),
+			..Default::default()
+		};
+
+		let tree = head.tree()?;
+
+		let manifests = match layout {
+			Layout::Standard => {
+				// A bucket without any manifests has an empty index.
+				let Ok(entry) = tree.get_path(layout.manifest_dir()) else {
+					return Ok(index);
+				};
+
+				entry.to_object(repo)?.peel_to_tree()?
+			}
+			Layout::Root => tree,
+		};
+
+		for entry in &manifests {
+			let Some(name) = entry
+				.name()
+				.and_then(|name| name.strip_suffix(".json"))
+				.map(str::to_owned)
+			else {
+				continue;
+			};
+
+			if let Ok(blob) = entry.to_object(repo)?.peel_to_blob() {
+				index.insert_blob(name, &blob);
+			}
+		}
+
+		Ok(index)
+	}
+
+	fn update_index(
+		repo: &git2::Repository,
+		layout: Layout,
+		mut index: Index,
+		old: &git2::Commit,
+		head: &git2::Commit,
+	) -> Result<Index> {
+		let mut options = git2::DiffOptions::new();
+
+		if let Some(pathspec) = layout.pathspec() {
+			options.pathspec(pathspec);
+		}
+
+		let diff =
+			repo.diff_tree_to_tree(Some(&old.tree()?), Some(&head.tree()?), Some(&mut options))?;
+
+		for delta in diff.deltas() {
+			let old_file = delta.old_file();
+			let new_file = delta.new_file();
+
+			// Renamed manifests are removed under the old name and added under the new name.
+			if let Some(name) = old_file.path().and_then(|path| layout.manifest_name(path)) {
+				index.remove(&name);
+			}
+
+			if delta.status() == git2::Delta::Deleted {
+				continue;
+			}
+
+			if let Some(name) = new_file.path().and_then(|path| layout.manifest_name(path)) {
+				let blob = repo.find_blob(new_file.id())?;
+				index.insert_blob(name, &blob);
+			}
+		}
+
+		index.commit = head.id().to_string();
+
+		Ok(index)
+	}
+
+	/// Returns the summaries of the manifests in the bucket's index where the criteria is satisfied, sorted by name.
+	/// This is much faster than [`search`], as manifests do not need to be parsed unless they have changed,
+	/// or the criteria needs them (see [`Criteria::needs_manifest`]).
+	///
+	/// Like [`search`], manifests that failed to parse are always returned with [`Error::InvalidManifest`],
+	/// so the error is not lost.
+	///
+	/// # Arguments
+	///
+	/// `criteria` - The criteria that determines if a manifest should be returned.
+	///
+	/// # Errors
+	///
+	/// See [`index`].
+	///
+	/// [`search`]: Bucket::search
+	/// [`index`]: Bucket::index
+	pub fn summaries<C: Criteria>(&self, criteria: &C) -> Result<Vec<SummaryItem>> {
+		let index = self.index()?;
+
+		let parsed = index
+			.manifests
+			.into_iter()
+			.map(|(name, summary)| (name, Ok(summary)));
+		let failed = index
+			.failures
+			.into_iter()
+			.map(|(name, err)| (name, Err(Error::InvalidManifest(err))));
+
+		let mut items = vec![];
+
+		for (name, summary) in parsed.chain(failed) {
+			if !criteria.filter_manifest(&name) {
+				continue;
+			}
+
+			let summary = match summary {
+				Ok(summary) if criteria.needs_manifest() => {
+					match manifest_from_file(self.manifest_path(&name)) {
+						Ok(manifest) if !criteria.filter_item(self, &name, &manifest) => continue,
+						Ok(_) => Ok(summary),
+						Err(err) => Err(err),
+					}
+				}
+				summary => summary,
+			};
+
+			items.push(SummaryItem { name, summary });
+		}
+
+		items.sort_by(|a, b| a.name.cmp(&b.name));
+
+		Ok(items)
+	}
+
 	/// Returns the path to an manifest.
 	#[must_use]
 	pub fn manifest_path(&self, name: &str) -> PathBuf {
-		self.dir().join(format!(r"bucket\{name}.json"))
+		self.manifest_dir().join(format!("{name}.json"))
+	}
+
+	/// Returns the path to a manifest in the `deprecated` directory.
+	#[must_use]
+	pub fn deprecated_path(&self, name: &str) -> PathBuf {
+		self.root.join(DEPRECATED_DIR).join(format!("{name}.json"))
+	}
+
+	/// Checks if a manifest was retired, i.e., it is in the `deprecated` directory and not with the other manifests.
+	#[must_use]
+	pub fn is_deprecated(&self, name: &str) -> bool {
+		!self.manifest_path(name).exists() && self.deprecated_path(name).exists()
 	}
 
 	/// Parses and returns an manifest.
@@ -172,130 +677,520 @@ impl Bucket {
 	/// # Arguments
 	///
 	/// * `name` - The app's name.
+	/// * `deprecated` - Whether to fall back to the `deprecated` directory if the manifest is not found.
 	///
 	/// # Errors
 	///
 	/// If the manifest file does not exist, `Error::ManifestNotFound` is returned.
-	pub fn manifest(&self, name: &str) -> Result<Manifest> {
-		let path = self.manifest_path(name);
+	pub fn manifest(&self, name: &str, deprecated: bool) -> Result<Manifest> {
+		match manifest_from_file(self.manifest_path(name)) {
+			Err(Error::ManifestNotFound) if deprecated => {
+				manifest_from_file(self.deprecated_path(name))
+			}
+			res => res,
+		}
+	}
 
-		manifest_from_file(path)
+	/// Parses and returns a specific version of a manifest, with where it came from.
+	///
+	/// If the latest manifest is not the version, the newest commit in the bucket's history where the manifest
+	/// had the version is used. Otherwise, the manifest is generated from the latest manifest's `autoupdate`.
+	///
+	/// # Arguments
+	///
+	/// * `name` - The manifest's name.
+	/// * `version` - The version.
+	///
+	/// # Errors
+	///
+	/// If the version is not in the history and the manifest does not have `autoupdate`, [`Error::VersionNotFound`] is returned.
+	/// [`Error::ShallowHistory`] is returned instead if the bucket is a shallow clone, so the version may be older.
+	///
+	/// If the manifest does not exist, [`Error::ManifestNotFound`] is returned.
+	///
+	/// If the manifest cannot be parsed, [`Error::Json`] is returned.
+	///
+	/// If the bucket's history cannot be read, [`Error::Git`] is returned.
+	pub fn manifest_version(&self, name: &str, version: &str) -> Result<(Manifest, Source)> {
+		let latest = self.manifest(name, false)?;
+
+		if latest.version == version {
+			return Ok((latest, Source::Latest));
+		}
+
+		let mut shallow = false;
+
+		// A directory bucket has no history, but a version may still be generated from `autoupdate`.
+		if self.kind == Kind::Git {
+			let repo = self.repo()?;
+			let path = self.layout().tree_path(name);
+
+			for res in History::new(&repo, path)? {
+				let (commit, blob) = match res {
+					Ok(item) => item,
+					// An older version may still be generated from `autoupdate`.
+					Err(Error::ShallowHistory) => {
+						shallow = true;
+						break;
+					}
+					Err(err) => return Err(err),
+				};
+
+				let Some(blob) = blob else {
+					continue;
+				};
+
+				if changes::blob_version(&repo, blob).as_deref() != Some(version) {
+					continue;
+				}
+
+				let blob = repo.find_blob(blob)?;
+				let manifest = json::from_reader(blob.content())?;
+
+				return Ok((
+					manifest,
+					Source::History {
+						commit: commit.id().to_string(),
+					},
+				));
+			}
+		}
+
+		let file = fs::File::open(self.manifest_path(name))?;
+		let value = json::from_reader(file)?;
+
+		let Some(generated) = versioned::generate(value, version) else {
+			if shallow {
+				return Err(Error::ShallowHistory);
+			}
+
+			return Err(Error::VersionNotFound {
+				name: name.to_owned(),
+				version: version.to_owned(),
+			});
+		};
+
+		Ok((
+			json::from_value(generated)?,
+			Source::Autoupdate {
+				from: latest.version,
+			},
+		))
 	}
 
 	/// Returns the last commit made to a manifest.
-	/// If the manifest has not been commited, None is returned.
+	/// If the manifest has not been commited, or the bucket is a [`Kind::Directory`], None is returned.
 	///
 	/// # Errors
 	///
+	/// If the manifest file does not exist, [`Error::ManifestNotFound`] is returned.
+	///
+	/// If the bucket is a shallow clone and the manifest was last changed before its history, [`Error::ShallowHistory`] is returned.
+	///
 	/// If the status of the manifest file cannot be read, or the revwalk failed, [`Error::Git`] is returned.
-	#[allow(clippy::missing_panics_doc)]
-	pub fn manifest_commit(&self, name: &str) -> Result<Option<git2::Commit>> {
-		let path = self.manifest_path(name);
+	pub fn manifest_commit(&self, name: &str) -> Result<Option<Commit>> {
+		let path = self.layout().tree_path(name);
 
 		// Ensure the manifest exists.
-		if !path.exists() {
+		if !self.root.join(&path).exists() {
 			return Err(Error::ManifestNotFound);
 		}
 
-		// SAFETY: path is always a child of dir.
-		let relpath = path
-			.strip_prefix(&self.dir)
-			.expect("manifest path is in bucket");
+		if self.kind == Kind::Directory {
+			return Ok(None);
+		}
+
+		let repo = self.repo()?;
 
 		// Ensure the manifest is commited.
-		if self.is_commited(relpath)? {
-			let commit = self.find_commit(relpath)?;
+		if !Self::is_commited(&repo, &path)? {
+			return Ok(None);
+		}
 
-			Ok(Some(commit.expect("Manifest is commited")))
-		} else {
-			Ok(None)
+		let mut history = History::new(&repo, path)?;
+		let commit = history.next().transpose()?;
+
+		Ok(commit.map(|(commit, _)| Commit::from(&commit)))
+	}
+
+	/// Returns every commit from HEAD that changed a manifest, newest first, with the manifest's version after each commit.
+	/// Manifests that were removed still have a history. A [`Kind::Directory`] has no history, so it is empty.
+	///
+	/// # Arguments
+	///
+	/// * `name` - The manifest's name.
+	///
+	/// # Errors
+	///
+	/// If the bucket is a shallow clone and the manifest is older than its history, [`Error::ShallowHistory`] is returned.
+	///
+	/// If the bucket does not have a HEAD commit, or the revwalk failed, [`Error::Git`] is returned.
+	pub fn manifest_history(&self, name: &str) -> Result<Vec<Revision>> {
+		if self.kind == Kind::Directory {
+			return Ok(vec![]);
 		}
+
+		let repo = self.repo()?;
+
+		let history = History::new(&repo, self.layout().tree_path(name))?;
+
+		history.revisions()
 	}
 
-	fn find_commit(&self, path: &Path) -> Result<Option<git2::Commit>> {
-		let mut revwalk = self.repo.revwalk()?;
-		revwalk.set_sorting(git2::Sort::TIME)?;
-		revwalk.push_head()?;
+	fn is_commited(repo: &git2::Repository, path: &Path) -> Result<bool> {
+		let status = repo.status_file(path)?;
 
-		let mut old_tree: Option<git2::Tree> = None;
+		Ok(!(status.contains(git2::Status::WT_NEW) || status.contains(git2::Status::INDEX_NEW)))
+	}
 
-		for oid in revwalk {
-			let commit = self.repo.find_commit(oid?)?;
-			let tree = Some(commit.tree()?);
+	/// Returns the remote and branch the bucket pulls from.
+	///
+	/// These are read from the upstream of the HEAD branch in the repository's config.
+	/// If no upstream is set, the branch of the same name in `origin` is used.
+	///
+	/// # Errors
+	///
+	/// If the HEAD branch or the repository's config cannot be read, [`Error::Git`] is returned.
+	pub fn upstream(&self) -> Result<Upstream> {
+		let repo = self.repo()?;
 
-			let diff = self
-				.repo
-				.diff_tree_to_tree(old_tree.as_ref(), tree.as_ref(), None)?;
+		Self::upstream_of(&repo)
+	}
 
-			let has_file = diff.deltas().any(|delta| {
-				// SAFETY: Okay as long as the path is UTF-8.
-				let delta_path = delta.new_file().path().unwrap();
+	/// Sets the remote and branch the bucket pulls from, as the upstream of the HEAD branch.
+	///
+	/// # Arguments
+	///
+	/// * `upstream` - The remote and branch to pull from.
+	///
+	/// # Errors
+	///
+	/// If the remote does not exist, or the repository's config cannot be written, [`Error::Git`] is returned.
+	pub fn set_upstream(&self, upstream: &Upstream) -> Result<()> {
+		let repo = self.repo()?;
+		let local = Self::head_branch(&repo)?;
 
-				delta_path == path
-			});
+		// Make sure the remote exists.
+		repo.find_remote(&upstream.remote)?;
 
-			if has_file {
-				return Ok(Some(commit));
-			}
+		let mut config = repo.config()?;
+		config.set_str(&format!("branch.{local}.remote"), &upstream.remote)?;
+		config.set_str(
+			&format!("branch.{local}.merge"),
+			&format!("refs/heads/{}", upstream.branch),
+		)?;
+
+		Ok(())
+	}
 
-			old_tree = tree;
+	/// Returns what the bucket is pinned to, if anything. Pins are stored in the repository's config.
+	/// A [`Kind::Directory`] cannot be pinned, so None is returned.
+	///
+	/// # Errors
+	///
+	/// If the pin in the config is invalid, [`Error::InvalidPin`] is returned.
+	///
+	/// If the config cannot be read, [`Error::Git`] is returned.
+	pub fn pin(&self) -> Result<Option<Pin>> {
+		if self.kind == Kind::Directory {
+			return Ok(None);
 		}
 
-		Ok(None)
+		pin::get(&self.repo()?)
 	}
 
-	fn is_commited(&self, path: &Path) -> Result<bool> {
-		let status = self.repo.status_file(path)?;
+	/// Pins the bucket to a branch, tag or commit, or unpins it. The bucket is moved on the next [`pull`].
+	///
+	/// # Arguments
+	///
+	/// * `pin` - The pin. If None, the bucket follows its [`upstream`] again.
+	///
+	/// # Errors
+	///
+	/// If the config cannot be written, [`Error::Git`] is returned.
+	///
+	/// [`pull`]: Bucket::pull
+	/// [`upstream`]: Bucket::upstream
+	pub fn set_pin(&self, pin: Option<&Pin>) -> Result<()> {
+		pin::set(&self.repo()?, pin)
+	}
 
-		Ok(!(status.contains(git2::Status::WT_NEW) || status.contains(git2::Status::INDEX_NEW)))
+	/// Returns the keys trusted to sign the bucket's commits. Keys are stored in the repository's config.
+	///
+	/// If any keys are trusted, [`pull`] only accepts new commits that are signed by one of them.
+	/// A [`Kind::Directory`] has no commits, so no keys are returned.
+	///
+	/// # Errors
+	///
+	/// If a key in the config is invalid, [`Error::InvalidKey`] is returned.
+	///
+	/// If the config cannot be read, [`Error::Git`] is returned.
+	///
+	/// [`pull`]: Bucket::pull
+	pub fn trusted_keys(&self) -> Result<Vec<TrustedKey>> {
+		if self.kind == Kind::Directory {
+			return Ok(vec![]);
+		}
+
+		signature::trusted_keys(&self.repo()?)
 	}
 
-	/// Updates the bucket by pulling new changes. Only fast-forwarding is supported.
+	/// Replaces the keys trusted to sign the bucket's commits.
 	///
 	/// # Arguments
 	///
-	/// `fetch_options` - The options to use for fetching changes.
+	/// * `keys` - The trusted keys. If empty, commits are no longer verified.
+	///
+	/// # Errors
+	///
+	/// If the config cannot be written, [`Error::Git`] is returned.
+	pub fn set_trusted_keys(&self, keys: &[TrustedKey]) -> Result<()> {
+		signature::set_trusted_keys(&self.repo()?, keys)
+	}
+
+	/// Returns the short name of the HEAD branch, i.e., `master` for `refs/heads/master`.
+	fn head_branch(repo: &git2::Repository) -> Result<String> {
+		let head = repo.head()?;
+
+		match head.shorthand() {
+			Some(name) if head.is_branch() => Ok(name.to_owned()),
+			_ => Err(git2::Error::from_str("HEAD is not a branch").into()),
+		}
+	}
+
+	fn upstream_of(repo: &git2::Repository) -> Result<Upstream> {
+		let local = Self::head_branch(repo)?;
+		let config = repo.config()?;
+
+		let remote = config
+			.get_string(&format!("branch.{local}.remote"))
+			.unwrap_or_else(|_| "origin".to_owned());
+
+		let branch = match config.get_string(&format!("branch.{local}.merge")) {
+			Ok(merge) => merge
+				.strip_prefix("refs/heads/")
+				.unwrap_or(&merge)
+				.to_owned(),
+			Err(_) => local,
+		};
+
+		Ok(Upstream { remote, branch })
+	}
+
+	/// Updates the bucket by pulling new changes from its [`upstream`].
+	///
+	/// Changes are fast-forwarded. If the upstream was force-pushed and the bucket has no local commits,
+	/// the bucket is reset to the upstream instead, discarding the commits that were rewritten.
+	///
+	/// If the bucket is [`pin`]ned, the pin is respected instead:
+	/// * A branch is fetched, and the bucket is moved to it.
+	/// * A tag is fetched, and the bucket is moved to it.
+	/// * A commit is only fetched if the bucket does not have it yet, and the bucket stays at it.
+	///
+	/// # Arguments
+	///
+	/// `fetch_options` - The options to use for fetching changes. If None, [`Credentials::from_env`] is used.
 	/// `checkout_builder` - The builder to use for checking out changes.
 	///
 	/// # Errors
 	///
+	/// If the bucket has local commits, i.e., commits that are not in any remote-tracking branch or tag,
+	/// and the upstream or pinned commit does not have them, [`Error::Diverged`] is returned.
+	///
+	/// If the bucket has [`trusted_keys`] and a new commit is not signed by one of them,
+	/// [`Error::UntrustedCommit`] is returned, and the bucket is not updated.
+	///
 	/// [`Error::Git`] is returned when any of these occur:
 	/// * The HEAD branch cannot be retrieved.
-	/// * The bucket does not have an origin.
-	/// * Fetching changes from the origin failed.
-	/// * Fast-forwarding failed.
-	///
-	/// # Panics
+	/// * The upstream remote does not exist.
+	/// * Fetching changes from the upstream failed.
+	/// * Checking out the changes failed.
 	///
-	/// This function panics if the HEAD branch has a non-UTF-8 name.
+	/// [`upstream`]: Bucket::upstream
+	/// [`pin`]: Bucket::pin
+	/// [`trusted_keys`]: Bucket::trusted_keys
 	pub fn pull(
-		&mut self,
+		&self,
 		fetch_options: Option<&mut git2::FetchOptions>,
 		checkout_builder: Option<&mut build::CheckoutBuilder>,
-	) -> Result<()> {
-		// Get the name of the HEAD branch.
-		let head = self.repo.head()?;
-		let branch = head.name().unwrap();
+	) -> Result<Pull> {
+		let repo = self.repo()?;
+		let local = Self::head_branch(&repo)?;
+		let Upstream { remote, branch } = Self::upstream_of(&repo)?;
+		let reference = format!("refs/heads/{local}");
+
+		let mut default_options = None;
+		let fetch_options = fetch_options.unwrap_or_else(|| {
+			default_options.insert(Credentials::from_env().into_fetch_options())
+		});
+
+		let head = repo.head()?.peel_to_commit()?.id();
+
+		// Checked before fetching, as the fetch may replace a force-pushed history that HEAD is in.
+		let has_local_commits = !Self::is_fetched(&repo, head)?;
+
+		if let Some(pin) = pin::get(&repo)? {
+			let target = match &pin {
+				Pin::Branch(pinned) => {
+					let mut origin = repo.find_remote(&remote)?;
+					let refspec = format!("+refs/heads/{pinned}:refs/remotes/{remote}/{pinned}");
+
+					Self::fetch(&repo, &[&refspec], &mut origin, fetch_options)?
+				}
+				Pin::Tag(tag) => {
+					let tag_ref = format!("refs/tags/{tag}");
+					let refspec = format!("+{tag_ref}:{tag_ref}");
+
+					// Tag following never overwrites existing tags, so a moved tag would not be fetched.
+					fetch_options.download_tags(git2::AutotagOption::None);
+
+					repo.find_remote(&remote)?
+						.fetch(&[&refspec], Some(fetch_options), None)?;
+
+					repo.reference_to_annotated_commit(&repo.find_reference(&tag_ref)?)?
+				}
+				Pin::Commit(id) => {
+					// Only fetch if the commit is missing, so a pinned bucket does not need the network.
+					if repo.revparse_single(id).is_err() {
+						let mut origin = repo.find_remote(&remote)?;
+						let refspec =
+							format!("+refs/heads/{branch}:refs/remotes/{remote}/{branch}");
+						Self::fetch(&repo, &[&refspec], &mut origin, fetch_options)?;
+					}
+
+					let commit = repo.revparse_single(id)?.peel_to_commit()?;
+
+					repo.find_annotated_commit(commit.id())?
+				}
+			};
+
+			return Self::move_to(
+				&repo,
+				&reference,
+				&target,
+				&pin,
+				has_local_commits,
+				checkout_builder,
+			);
+		}
 
-		// Get the origin remote.
-		// TODO: Don't hardcode?
-		let mut origin = self.origin()?;
+		let tracking = format!("refs/remotes/{remote}/{branch}");
 
-		// Fetch updates for the HEAD branch from the origin.
-		let new_head = self.fetch(&[branch], &mut origin, fetch_options)?;
+		let mut origin = repo.find_remote(&remote)?;
+		let refspec = format!("+refs/heads/{branch}:{tracking}");
+		let new_head = Self::fetch(&repo, &[&refspec], &mut origin, fetch_options)?;
 
-		// Attempt to fast-forward changes.
-		self.fast_forward(branch, &new_head, checkout_builder)?;
+		let (analysis, _) = repo.merge_analysis(&[&new_head])?;
 
-		Ok(())
+		if analysis.is_up_to_date() {
+			return Ok(Pull::UpToDate);
+		}
+
+		if analysis.is_fast_forward() {
+			Self::checkout(
+				&repo,
+				&reference,
+				&new_head,
+				"pull: Fast-forward",
+				checkout_builder,
+			)?;
+
+			return Ok(Pull::FastForward {
+				from: head,
+				to: new_head.id(),
+			});
+		}
+
+		// The branches have diverged. If HEAD was fetched, i.e., the upstream was rewritten (force-pushed)
+		// or the bucket was moved to a pin before, nothing is lost by resetting to the upstream.
+		if has_local_commits {
+			return Err(Error::Diverged {
+				target: format!("{remote}/{branch}"),
+			});
+		}
+
+		Self::checkout(
+			&repo,
+			&reference,
+			&new_head,
+			"pull: Reset to force-pushed upstream",
+			checkout_builder,
+		)?;
+
+		Ok(Pull::Reset {
+			from: head,
+			to: new_head.id(),
+		})
 	}
 
-	fn fast_forward(
-		&self,
+	/// Checks if a commit was fetched, i.e., it is in a remote-tracking branch or tag, rather than made locally.
+	fn is_fetched(repo: &git2::Repository, oid: git2::Oid) -> Result<bool> {
+		for reference in repo.references()? {
+			let reference = reference?;
+
+			let fetched = reference.name().is_some_and(|name| {
+				name.starts_with("refs/remotes/") || name.starts_with("refs/tags/")
+			});
+
+			// References that do not point to a commit, i.e., `refs/remotes/origin/HEAD` when it is dangling, are skipped.
+			let Ok(commit) = reference.peel_to_commit() else {
+				continue;
+			};
+
+			if fetched && (commit.id() == oid || repo.graph_descendant_of(commit.id(), oid)?) {
+				return Ok(true);
+			}
+		}
+
+		Ok(false)
+	}
+
+	/// Moves a branch to a pinned commit, whether or not it descends from HEAD.
+	/// Like pulling, commits are only discarded if none of them were made locally.
+	fn move_to(
+		repo: &git2::Repository,
+		branch: &str,
+		target: &git2::AnnotatedCommit,
+		pin: &Pin,
+		has_local_commits: bool,
+		checkout_builder: Option<&mut build::CheckoutBuilder>,
+	) -> Result<Pull> {
+		let head = repo.head()?.peel_to_commit()?.id();
+		let to = target.id();
+
+		if head == to {
+			return Ok(Pull::UpToDate);
+		}
+
+		let forward = repo.graph_descendant_of(to, head)?;
+
+		if !forward && has_local_commits {
+			return Err(Error::Diverged {
+				target: pin.to_string(),
+			});
+		}
+
+		Self::checkout(repo, branch, target, "pull: Move to pin", checkout_builder)?;
+
+		if forward {
+			Ok(Pull::FastForward { from: head, to })
+		} else {
+			Ok(Pull::Reset { from: head, to })
+		}
+	}
+
+	/// Points a branch to a commit, and checks it out.
+	fn checkout(
+		repo: &git2::Repository,
 		branch: &str,
 		commit: &git2::AnnotatedCommit,
+		message: &str,
 		checkout_builder: Option<&mut build::CheckoutBuilder>,
 	) -> Result<()> {
+		// Every path that moves HEAD comes through here, so no update can skip verification.
+		let head = repo.head()?.peel_to_commit()?.id();
+		signature::verify(repo, head, commit.id())?;
+
 		let mut new_checkout_builder = None;
 
 		let checkout_builder = checkout_builder
@@ -308,73 +1203,135 @@ impl Bucket {
 		// NOTE: According to the git2 pull example, not including this option causes the working directory to not update.
 		checkout_builder.force();
 
+		let sparse = clone::is_sparse(repo);
+
+		if sparse {
+			clone::limit_checkout(checkout_builder);
+		}
+
 		// Obtain a reference to the branch HEAD.
-		let mut head = self.repo.find_reference(branch)?;
+		let mut head = repo.find_reference(branch)?;
 
 		// Set the branch HEAD to the new commit ID.
-		head.set_target(commit.id(), "pull: Fast-forward")?;
+		head.set_target(commit.id(), message)?;
 
 		// Set the repository HEAD to the branch HEAD.
-		self.repo.set_head(branch)?;
+		repo.set_head(branch)?;
 
 		// Checkout the new changes.
-		self.repo.checkout_head(Some(checkout_builder))?;
+		repo.checkout_head(Some(checkout_builder))?;
+
+		if sparse {
+			clone::apply_sparse(repo)?;
+		}
 
 		Ok(())
 	}
 
-	fn fetch(
-		&self,
-		branches: &[&str],
+	fn fetch<'r>(
+		repo: &'r git2::Repository,
+		refspecs: &[&str],
 		remote: &mut git2::Remote,
-		fetch_options: Option<&mut git2::FetchOptions>,
-	) -> Result<git2::AnnotatedCommit> {
-		remote.fetch(branches, fetch_options, None)?;
+		fetch_options: &mut git2::FetchOptions,
+	) -> Result<git2::AnnotatedCommit<'r>> {
+		remote.fetch(refspecs, Some(fetch_options), None)?;
 
 		// Get the HEAD of the fetched remote.
-		let head = self.repo.find_reference("FETCH_HEAD")?;
+		let head = repo.find_reference("FETCH_HEAD")?;
+
+		Ok(repo.reference_to_annotated_commit(&head)?)
+	}
+}
+
+/// The remote and branch a bucket pulls from. See [`Bucket::upstream`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Upstream {
+	/// The name of the remote, i.e., `origin`.
+	pub remote: String,
+
+	/// The name of the branch in the remote, i.e., `master`.
+	pub branch: String,
+}
+
+/// The result of pulling changes into a bucket. See [`Bucket::pull`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pull {
+	/// The bucket already had the upstream's changes.
+	UpToDate,
+
+	/// The bucket was fast-forwarded.
+	FastForward { from: git2::Oid, to: git2::Oid },
+
+	/// The upstream was force-pushed, so the bucket was reset to it.
+	/// This is also the result of moving a pinned bucket back in its history.
+	Reset { from: git2::Oid, to: git2::Oid },
+}
+
+/// A commit in a bucket.
+///
+/// Unlike [`git2::Commit`], this does not borrow the bucket's repository.
+#[derive(Clone, Debug)]
+pub struct Commit {
+	/// The commit's ID.
+	pub id: git2::Oid,
+
+	/// The first line of the commit message.
+	pub summary: String,
+
+	/// The name of the commit's author.
+	pub author: String,
 
-		Ok(self.repo.reference_to_annotated_commit(&head)?)
+	/// The time the commit was made.
+	pub time: Timestamp,
+}
+
+impl From<&git2::Commit<'_>> for Commit {
+	fn from(commit: &git2::Commit<'_>) -> Self {
+		Self {
+			id: commit.id(),
+			summary: commit.summary().unwrap_or_default().to_owned(),
+			author: commit.author().name().unwrap_or_default().to_owned(),
+			time: Timestamp::from(commit.time()),
+		}
 	}
 }
 
-/// An iterator over commits since a specific commit. Created by `Buckets::commits`.
-pub struct Commits<'c> {
-	repo: &'c git2::Repository,
-	revwalk: git2::Revwalk<'c>,
-	since: git2::Oid,
+/// An iterator over commits since a specific commit. Created by `Bucket::commits`.
+pub struct Commits {
+	repo: git2::Repository,
+	oids: vec::IntoIter<git2::Oid>,
 }
 
-impl<'c> Commits<'c> {
-	fn new(repo: &'c git2::Repository, since: git2::Oid) -> Result<Self> {
-		// Create a revwalk to iterate commits from HEAD chronologically.
-		let mut revwalk = repo.revwalk()?;
-		revwalk.set_sorting(git2::Sort::TIME)?;
-		revwalk.push_head()?;
+impl Commits {
+	fn new(repo: git2::Repository, since: git2::Oid) -> Result<Self> {
+		// Walk the commits from HEAD chronologically, leaving out `since` and its ancestors.
+		// Unlike stopping at `since`, this also works when it is not an ancestor of HEAD, i.e., after a force-push.
+		let oids = {
+			let mut revwalk = repo.revwalk()?;
+			revwalk.set_sorting(git2::Sort::TIME)?;
+			revwalk.push_head()?;
+			revwalk.hide(since)?;
+
+			revwalk.collect::<std::result::Result<Vec<_>, _>>()?
+		};
 
 		Ok(Self {
 			repo,
-			revwalk,
-			since,
+			oids: oids.into_iter(),
 		})
 	}
 }
 
-impl<'c> Iterator for Commits<'c> {
-	type Item = git2::Commit<'c>;
+impl Iterator for Commits {
+	type Item = Commit;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		// Get the next OID.
-		let oid = self.revwalk.next()?.ok()?;
+		let oid = self.oids.next()?;
 
-		if oid == self.since {
-			// The `since` commit has been reached.
-			None
-		} else {
-			// Return the commit.
-			// NOTE: Since the OID was retreived through revwalk, this should not be None.
-			self.repo.find_commit(oid).ok()
-		}
+		// NOTE: Since the OID was retreived through revwalk, this should not be None.
+		let commit = self.repo.find_commit(oid).ok()?;
+
+		Some(Commit::from(&commit))
 	}
 }
 
@@ -387,20 +1344,31 @@ pub struct SearchItem {
 	pub manifest: Result<Manifest>,
 }
 
+/// A summary of a manifest in a bucket's index.
+pub struct SummaryItem {
+	/// The manifest's name.
+	pub name: String,
+
+	/// The manifest's summary.
+	pub summary: Result<Summary>,
+}
+
 /// An iterator over manifests in a bucket, filtered by criteria of type `C`. Created by `Buckets::search`.
 pub struct Search<C: Criteria> {
+	bucket: Bucket,
 	entries: fs::ReadDir,
 	criteria: C,
 }
 
 impl<C: Criteria> Search<C> {
-	fn new<P>(dir: P, criteria: C) -> Result<Self>
-	where
-		P: AsRef<Path>,
-	{
-		let entries = fs::read_dir(dir)?;
+	fn new(bucket: Bucket, criteria: C) -> Result<Self> {
+		let entries = fs::read_dir(bucket.manifest_dir())?;
 
-		Ok(Self { entries, criteria })
+		Ok(Self {
+			bucket,
+			entries,
+			criteria,
+		})
 	}
 }
 
@@ -409,27 +1377,14 @@ impl<C: Criteria> Iterator for Search<C> {
 
 	fn next(&mut self) -> Option<Self::Item> {
 		self.entries.find_map(|res| {
-			let path = res.ok()?.path();
-
-			let ext = path.extension().unwrap_or_default();
+			let (name, path) = manifest_entry(res.ok()?.path(), &self.criteria)?;
+			let manifest = manifest_from_file(path);
 
-			// If the path does not end in '.json', it is not a manifest.
-			if ext != "json" {
-				return None;
+			// Manifests that failed to parse are always yielded, so the error is not lost.
+			match &manifest {
+				Ok(parsed) if !self.criteria.filter_item(&self.bucket, &name, parsed) => None,
+				_ => Some(SearchItem { name, manifest }),
 			}
-
-			// Take only the file stem (i.e., 'example' for 'example.json')
-			let name = util::osstr_to_string(path.file_stem().unwrap());
-
-			// If the predicate does not match, the manifest is skipped.
-			if !self.criteria.filter_manifest(&name) {
-				return None;
-			}
-
-			Some(SearchItem {
-				name,
-				manifest: manifest_from_file(path),
-			})
 		})
 	}
 }
@@ -454,7 +1409,7 @@ mod tests {
 
 	#[test]
 	fn origin() {
-		let bucket = Bucket::open(test::testdir().join(r"buckets\main")).unwrap();
+		let bucket = Bucket::open(test::testdir().join("buckets").join("main")).unwrap();
 
 		assert_eq!(
 			bucket.url().unwrap(),
@@ -462,6 +1417,429 @@ mod tests {
 		);
 	}
 
+	/// Creates an upstream bucket and a clone of it.
+	fn clone_upstream(temp_dir: &tempfile::TempDir) -> (git2::Repository, Bucket) {
+		let json = test::manifest_json("1.0", "");
+		let dir = temp_dir.path().join("upstream");
+		let upstream = test::create_bucket(&dir, &[("app", &json)]);
+
+		let bucket = Bucket::clone(
+			&util::path_to_string(&dir),
+			temp_dir.path().join("main"),
+			CloneOptions::new(),
+		)
+		.unwrap();
+
+		(upstream, bucket)
+	}
+
+	fn read_app(bucket: &Bucket) -> String {
+		fs::read_to_string(bucket.dir().join("bucket").join("app.json")).unwrap()
+	}
+
+	/// Replaces the upstream's history with a single new commit, as a force push would.
+	fn force_push(upstream: &git2::Repository) -> git2::Oid {
+		let tree = upstream.head().unwrap().peel_to_tree().unwrap();
+		let signature = git2::Signature::now("Shovel", "shovel@example.test").unwrap();
+		let oid = upstream
+			.commit(None, &signature, &signature, "Rewrite history", &tree, &[])
+			.unwrap();
+
+		let branch = upstream.head().unwrap().name().unwrap().to_owned();
+		upstream
+			.reference(&branch, oid, true, "Force push")
+			.unwrap();
+
+		oid
+	}
+
+	#[test]
+	fn pull_fast_forward() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let (upstream, bucket) = clone_upstream(&temp_dir);
+		let from = bucket.commit().unwrap().unwrap().id;
+
+		let json = test::manifest_json("2.0", "");
+		let to = test::commit_manifests(&upstream, &[("app", &json)], "Update app");
+
+		assert_eq!(
+			bucket.pull(None, None).unwrap(),
+			Pull::FastForward { from, to }
+		);
+		assert_eq!(read_app(&bucket), json);
+		assert_eq!(bucket.pull(None, None).unwrap(), Pull::UpToDate);
+	}
+
+	#[test]
+	fn pull_force_pushed() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let (upstream, bucket) = clone_upstream(&temp_dir);
+		let from = bucket.commit().unwrap().unwrap().id;
+
+		let to = force_push(&upstream);
+
+		assert_eq!(bucket.pull(None, None).unwrap(), Pull::Reset { from, to });
+		assert_eq!(bucket.commit().unwrap().unwrap().id, to);
+	}
+
+	#[test]
+	fn commits_force_pushed() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let (upstream, bucket) = clone_upstream(&temp_dir);
+		let base = upstream.head().unwrap().peel_to_commit().unwrap();
+
+		let json = test::manifest_json("2.0", "");
+		let from = test::commit_manifests(&upstream, &[("app", &json)], "Update app");
+		bucket.pull(None, None).unwrap();
+
+		// Amend the last commit, as a force push would.
+		upstream
+			.reset(base.as_object(), git2::ResetType::Hard, None)
+			.unwrap();
+		let json = test::manifest_json("2.1", "");
+		let to = test::commit_manifests(&upstream, &[("app", &json)], "Update app again");
+
+		assert_eq!(bucket.pull(None, None).unwrap(), Pull::Reset { from, to });
+
+		// The common history is not shown again.
+		let ids: Vec<_> = bucket
+			.commits(from)
+			.unwrap()
+			.map(|commit| commit.id)
+			.collect();
+		assert_eq!(ids, [to]);
+	}
+
+	#[test]
+	fn pull_diverged() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let (upstream, bucket) = clone_upstream(&temp_dir);
+
+		let json = test::manifest_json("2.0", "");
+		let local =
+			test::commit_manifests(&bucket.repo().unwrap(), &[("app", &json)], "Local change");
+		force_push(&upstream);
+
+		assert!(matches!(
+			bucket.pull(None, None),
+			Err(Error::Diverged { target }) if target.starts_with("origin/")
+		));
+
+		// The local commit is kept.
+		assert_eq!(bucket.commit().unwrap().unwrap().id, local);
+	}
+
+	#[test]
+	fn pull_upstream() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let (upstream, bucket) = clone_upstream(&temp_dir);
+
+		// Create a branch in the upstream, ahead of the default branch.
+		let head = upstream.head().unwrap().peel_to_commit().unwrap();
+		upstream.branch("dev", &head, false).unwrap();
+		upstream.set_head("refs/heads/dev").unwrap();
+
+		let json = test::manifest_json("2.0-dev", "");
+		test::commit_manifests(&upstream, &[("app", &json)], "Update app on dev");
+
+		let dev = Upstream {
+			remote: "origin".to_owned(),
+			branch: "dev".to_owned(),
+		};
+
+		bucket.set_upstream(&dev).unwrap();
+
+		assert_eq!(bucket.upstream().unwrap(), dev);
+		assert!(matches!(
+			bucket.pull(None, None).unwrap(),
+			Pull::FastForward { .. }
+		));
+		assert_eq!(read_app(&bucket), json);
+	}
+
+	#[test]
+	fn directory() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let json = test::manifest_json(
+			"2.0",
+			r#""autoupdate": { "url": "https://example.test/app-$version.zip" }"#,
+		);
+
+		let share = temp_dir.path().join("share");
+		fs::create_dir_all(share.join("bucket")).unwrap();
+		fs::write(share.join("bucket").join("app.json"), &json).unwrap();
+
+		// A directory without Git metadata is not a bucket, i.e., a clone that did not finish.
+		assert!(matches!(Bucket::open(&share), Err(Error::NotBucket)));
+
+		let dir = temp_dir.path().join("local");
+		Bucket::create_dir(dir.clone(), share.clone()).unwrap();
+		let bucket = Bucket::open(&dir).unwrap();
+
+		assert_eq!(bucket.kind(), Kind::Directory);
+		assert_eq!(bucket.name(), "local");
+		assert_eq!(bucket.url().unwrap(), util::path_to_string(&share));
+		assert!(bucket.commit().unwrap().is_none());
+		assert!(bucket.manifest_commit("app").unwrap().is_none());
+		assert!(bucket.manifest_history("app").unwrap().is_empty());
+		assert!(matches!(bucket.repo(), Err(Error::NotGit)));
+		assert!(matches!(bucket.pull(None, None), Err(Error::NotGit)));
+
+		assert_eq!(bucket.manifest("app", false).unwrap().version, "2.0");
+		assert_eq!(bucket.index().unwrap().manifests["app"].version, "2.0");
+
+		let (manifest, source) = bucket.manifest_version("app", "1.0").unwrap();
+		assert_eq!(manifest.version, "1.0");
+		assert_eq!(
+			source,
+			Source::Autoupdate {
+				from: "2.0".to_owned()
+			}
+		);
+
+		assert!(matches!(
+			Bucket::open(temp_dir.path().join("missing")),
+			Err(Error::NotFound)
+		));
+	}
+
+	#[test]
+	fn set_url() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let (_, bucket) = clone_upstream(&temp_dir);
+
+		// The bucket moved to another directory, as it would to another host.
+		let moved = temp_dir.path().join("moved");
+		fs::rename(temp_dir.path().join("upstream"), &moved).unwrap();
+
+		let url = util::path_to_string(&moved);
+		bucket.set_url(&url).unwrap();
+
+		assert_eq!(bucket.url().unwrap(), url);
+		assert!(matches!(bucket.pull(None, None).unwrap(), Pull::UpToDate));
+	}
+
+	#[test]
+	fn pin_commit() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let (upstream, bucket) = clone_upstream(&temp_dir);
+		let pinned = bucket.commit().unwrap().unwrap().id;
+
+		let json = test::manifest_json("2.0", "");
+		let to = test::commit_manifests(&upstream, &[("app", &json)], "Update app");
+		bucket.pull(None, None).unwrap();
+
+		// Pinning to an older commit moves the bucket back, and keeps it there.
+		let pin = Pin::Commit(pinned.to_string()[..8].to_owned());
+		bucket.set_pin(Some(&pin)).unwrap();
+
+		assert_eq!(bucket.pin().unwrap(), Some(pin));
+		assert_eq!(
+			bucket.pull(None, None).unwrap(),
+			Pull::Reset {
+				from: to,
+				to: pinned
+			}
+		);
+
+		test::commit_manifests(&upstream, &[("app", &json)], "Update app again");
+		assert_eq!(bucket.pull(None, None).unwrap(), Pull::UpToDate);
+		assert_eq!(bucket.commit().unwrap().unwrap().id, pinned);
+
+		// Unpinning follows the upstream again.
+		bucket.set_pin(None).unwrap();
+		assert_eq!(bucket.pin().unwrap(), None);
+		assert!(matches!(
+			bucket.pull(None, None).unwrap(),
+			Pull::FastForward { .. }
+		));
+	}
+
+	#[test]
+	fn pin_local_commits() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let (_, bucket) = clone_upstream(&temp_dir);
+		let pinned = bucket.commit().unwrap().unwrap().id;
+
+		let json = test::manifest_json("2.0", "");
+		let local =
+			test::commit_manifests(&bucket.repo().unwrap(), &[("app", &json)], "Local change");
+
+		// Moving back to the pinned commit would discard the local commit.
+		let pin = Pin::Commit(pinned.to_string());
+		bucket.set_pin(Some(&pin)).unwrap();
+
+		assert!(matches!(
+			bucket.pull(None, None),
+			Err(Error::Diverged { target }) if target == pin.to_string()
+		));
+		assert_eq!(bucket.commit().unwrap().unwrap().id, local);
+	}
+
+	#[test]
+	fn pin_branch() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let (upstream, bucket) = clone_upstream(&temp_dir);
+
+		// Create a release branch in the upstream that diverges from the default branch.
+		let head = upstream.head().unwrap();
+		let default = head.name().unwrap().to_owned();
+		let base = head.peel_to_commit().unwrap();
+		upstream.branch("release", &base, false).unwrap();
+
+		let json = test::manifest_json("2.0", "");
+		let latest = test::commit_manifests(&upstream, &[("app", &json)], "Update app");
+		bucket.pull(None, None).unwrap();
+
+		upstream.set_head("refs/heads/release").unwrap();
+		let json = test::manifest_json("1.1", "");
+		let release = test::commit_manifests(&upstream, &[("app", &json)], "Fix app");
+		upstream.set_head(&default).unwrap();
+
+		let pin = Pin::Branch("release".to_owned());
+		bucket.set_pin(Some(&pin)).unwrap();
+
+		assert_eq!(
+			bucket.pull(None, None).unwrap(),
+			Pull::Reset {
+				from: latest,
+				to: release
+			}
+		);
+		assert_eq!(read_app(&bucket), json);
+
+		upstream.set_head("refs/heads/release").unwrap();
+		let fixed = test::commit_manifests(&upstream, &[("app", &json)], "Fix app again");
+		upstream.set_head(&default).unwrap();
+
+		assert_eq!(
+			bucket.pull(None, None).unwrap(),
+			Pull::FastForward {
+				from: release,
+				to: fixed
+			}
+		);
+
+		// Unpinning moves the bucket back to the upstream branch, as the pinned commits were not made locally.
+		bucket.set_pin(None).unwrap();
+		assert_eq!(
+			bucket.pull(None, None).unwrap(),
+			Pull::Reset {
+				from: fixed,
+				to: latest
+			}
+		);
+	}
+
+	#[test]
+	fn pin_tag() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let (upstream, bucket) = clone_upstream(&temp_dir);
+
+		let tag = |oid: git2::Oid| {
+			let object = upstream.find_object(oid, None).unwrap();
+			upstream.tag_lightweight("stable", &object, true).unwrap();
+		};
+
+		let json = test::manifest_json("2.0", "");
+		let stable = test::commit_manifests(&upstream, &[("app", &json)], "Update app");
+		tag(stable);
+		test::commit_manifests(&upstream, &[("app", "{}")], "Break app");
+
+		bucket
+			.set_pin(Some(&Pin::Tag("stable".to_owned())))
+			.unwrap();
+
+		assert!(matches!(
+			bucket.pull(None, None).unwrap(),
+			Pull::FastForward { .. }
+		));
+		assert_eq!(bucket.commit().unwrap().unwrap().id, stable);
+		assert_eq!(read_app(&bucket), json);
+
+		// Moving the tag moves the bucket.
+		let json = test::manifest_json("3.0", "");
+		let fixed = test::commit_manifests(&upstream, &[("app", &json)], "Fix app");
+		tag(fixed);
+
+		bucket.pull(None, None).unwrap();
+		assert_eq!(bucket.commit().unwrap().unwrap().id, fixed);
+	}
+
+	#[test]
+	fn index() {
+		let temp_dir = tempfile::tempdir().unwrap();
+
+		let app = test::manifest_json("1.0", r#""bin": ["bin\\app.exe", ["tool.exe", "t"]]"#);
+		let lib = test::manifest_json("1.0", "");
+
+		let repo = test::create_bucket(temp_dir.path(), &[("app", &app), ("lib", &lib)]);
+		let bucket = Bucket::open(temp_dir.path()).unwrap();
+
+		let index = bucket.index().unwrap();
+		assert_eq!(
+			index.commit,
+			bucket.commit().unwrap().unwrap().id.to_string()
+		);
+		assert_eq!(index.manifests["app"].bins, ["app", "t"]);
+		assert!(bucket.index_path().unwrap().exists());
+
+		// Update one manifest, add another and remove a third.
+		let app = test::manifest_json("2.0", "");
+		let new = test::manifest_json("1.0", "");
+		test::commit_manifests(&repo, &[("app", &app), ("new", &new)], "Update");
+
+		let mut git_index = repo.index().unwrap();
+		git_index.remove_path(Path::new("bucket/lib.json")).unwrap();
+		git_index.write().unwrap();
+		test::commit_index(&repo, &mut git_index, "Remove lib");
+
+		let index = bucket.index().unwrap();
+		let names: Vec<_> = index.manifests.keys().map(String::as_str).collect();
+
+		assert_eq!(names, ["app", "new"]);
+		assert_eq!(index.manifests["app"].version, "2.0");
+		assert_eq!(index, Index::load(&bucket.index_path().unwrap()).unwrap());
+
+		// Manifests that fail to parse are reported instead of being left out.
+		test::commit_manifests(&repo, &[("bad", "{")], "Break");
+
+		assert!(bucket.index().unwrap().failures.contains_key("bad"));
+
+		let items = bucket.summaries(&()).unwrap();
+		let names: Vec<_> = items.iter().map(|item| item.name.as_str()).collect();
+
+		assert_eq!(names, ["app", "bad", "new"]);
+		assert!(matches!(items[1].summary, Err(Error::InvalidManifest(_))));
+	}
+
+	#[test]
+	fn deprecated() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let json = test::manifest_json("1.0", "");
+
+		test::create_bucket(temp_dir.path(), &[("app", &json), ("old", &json)]);
+		let bucket = Bucket::open(temp_dir.path()).unwrap();
+
+		// Retire `old` like the main buckets do.
+		let deprecated = temp_dir.path().join(DEPRECATED_DIR);
+		fs::create_dir(&deprecated).unwrap();
+		fs::rename(bucket.manifest_path("old"), deprecated.join("old.json")).unwrap();
+
+		assert_eq!(bucket.layout(), Layout::Standard);
+		assert!(bucket.is_deprecated("old"));
+		assert!(!bucket.is_deprecated("app"));
+
+		assert!(matches!(
+			bucket.manifest("old", false),
+			Err(Error::ManifestNotFound)
+		));
+		assert_eq!(bucket.manifest("old", true).unwrap().version, "1.0");
+
+		let names: Vec<_> = bucket.manifests().unwrap().map(|item| item.name).collect();
+		assert_eq!(names, ["app"]);
+	}
+
 	fn create_repo(name: &str) -> (tempfile::TempDir, git2::Repository) {
 		let temp_dir = tempfile::Builder::new()
 			// Disable randomizing the name.
@@ -1,3 +1,4 @@
+use std::env;
 use std::fs;
 use std::iter;
 use std::iter::Flatten;
@@ -5,21 +6,33 @@ use std::iter::Repeat;
 use std::iter::Zip;
 use std::path::Path;
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
+use std::vec;
 use std::vec::IntoIter;
 
-use git2::build;
+use rayon::prelude::*;
 
+use crate::bucket::bucket::manifest_from_file;
 use crate::bucket::Bucket;
+use crate::bucket::CloneOptions;
 use crate::bucket::Error;
 use crate::bucket::Name;
 use crate::bucket::Result;
 use crate::bucket::Search;
 use crate::bucket::SearchItem;
+use crate::bucket::SummaryItem;
+use crate::json;
 use crate::util;
 
 use crate::bucket::Criteria;
 
+/// The name of the default bucket.
+const MAIN: &str = "main";
+
+/// The file in the buckets directory that records the names of buckets in the order they were added.
+/// It is not a directory, so it is never mistaken for a bucket.
+const ORDER_FILE: &str = ".shovel-order.json";
+
 /// A bucket manager.
 ///
 /// Buckets are stored as sub-directories. For example:
@@ -27,8 +40,13 @@ use crate::bucket::Criteria;
 ///   * `bucket1`
 ///   * `bucket2`
 ///   * `...`
+///
+/// Buckets are yielded in priority order, so an unqualified manifest name resolves to the same bucket every time.
+/// Buckets in the priority list come first, then `main`, then the rest in the order they were added.
+/// The order is recorded in a file in `dir` when buckets are added, renamed or removed.
 pub struct Buckets {
 	dir: PathBuf,
+	priority: Vec<String>,
 }
 
 impl Buckets {
@@ -43,10 +61,22 @@ impl Buckets {
 	{
 		Self {
 			dir: dir.as_ref().to_owned(),
+			priority: vec![],
 		}
 	}
 
-	/// Yields all buckets.
+	/// Sets the names of buckets to yield first, from highest to lowest priority.
+	///
+	/// # Arguments
+	///
+	/// * `priority` - The bucket names. Names of buckets that do not exist are ignored.
+	#[must_use]
+	pub fn with_priority(mut self, priority: Vec<String>) -> Self {
+		self.priority = priority;
+		self
+	}
+
+	/// Yields all buckets in priority order.
 	///
 	/// # Errors
 	///
@@ -54,7 +84,69 @@ impl Buckets {
 	///
 	/// [`Error::Io`]: crate::bucket::Error::Io
 	pub fn iter(&self) -> Result<Iter> {
-		Iter::new(self.dir.clone())
+		Ok(Iter {
+			dirs: self.dirs()?.into_iter(),
+		})
+	}
+
+	/// Returns the names of all buckets in priority order.
+	/// Unlike [`iter`], this does not open the buckets, so names are returned even for buckets that cannot be opened.
+	///
+	/// # Errors
+	///
+	/// If the directory containing the buckets cannot be read, [`Error::Io`] is returned.
+	///
+	/// [`iter`]: Buckets::iter
+	/// [`Error::Io`]: crate::bucket::Error::Io
+	pub fn names(&self) -> Result<Vec<String>> {
+		let names = self
+			.dirs()?
+			.iter()
+			.map(|dir| util::osstr_to_string(dir.file_name().unwrap_or_default()))
+			.collect();
+
+		Ok(names)
+	}
+
+	/// Returns the directories of all buckets in priority order.
+	fn dirs(&self) -> Result<Vec<PathBuf>> {
+		let mut dirs: Vec<_> = util::dirs(&self.dir)?.collect();
+		let order = self.order();
+
+		dirs.sort_by_cached_key(|dir| {
+			let name = util::osstr_to_string(dir.file_name().unwrap_or_default());
+			let rank = self.priority.iter().position(|p| *p == name);
+
+			// Buckets that were not recorded, i.e., added by Scoop, come before the recorded ones, by name.
+			let added = order.iter().position(|o| *o == name);
+
+			(rank.unwrap_or(usize::MAX), name != MAIN, added, name)
+		});
+
+		Ok(dirs)
+	}
+
+	/// Returns the names of buckets in the order they were added.
+	/// If the order was never recorded or cannot be read, it is empty.
+	fn order(&self) -> Vec<String> {
+		fs::File::open(self.dir.join(ORDER_FILE))
+			.ok()
+			.and_then(|file| json::from_reader(file).ok())
+			.unwrap_or_default()
+	}
+
+	/// Updates the names of buckets in the order they were added.
+	fn set_order<F>(&self, update: F) -> Result<()>
+	where
+		F: FnOnce(&mut Vec<String>),
+	{
+		let mut order = self.order();
+		update(&mut order);
+
+		let file = fs::File::create(self.dir.join(ORDER_FILE))?;
+		json::to_writer(file, &order)?;
+
+		Ok(())
 	}
 
 	/// Returns the path to a bucket.
@@ -63,6 +155,21 @@ impl Buckets {
 		self.dir.join(name)
 	}
 
+	/// Checks that a new bucket name is a single directory name, so the bucket stays in `dir`.
+	fn validate_name(name: &str) -> Result<()> {
+		let invalid = name.is_empty()
+			|| name == "."
+			|| name == ".."
+			|| name.contains(['/', '\\'])
+			|| Path::new(name).is_absolute();
+
+		if invalid {
+			Err(Error::InvalidName(name.to_owned()))
+		} else {
+			Ok(())
+		}
+	}
+
 	/// Opens and returns a bucket.
 	///
 	/// # Arguments
@@ -91,31 +198,90 @@ impl Buckets {
 	///
 	/// * `name` - The name to add the remote bucket as.
 	/// * `url` - The Git URL of the remote bucket.
-	/// * `builder` - The builder to clone with.
+	/// * `options` - The options to clone with.
 	///
 	/// # Errors
 	///
+	/// If the bucket name is not valid, [`Error::InvalidName`] is returned.
+	///
 	/// If the bucket name already exists, [`Error::BucketExists`] is returned.
 	///
+	/// [`Error::InvalidName`]: crate::bucket::Error::InvalidName
 	/// [`Error::BucketExists`]: crate::bucket::Error::BucketExists
-	pub fn add(
-		&self,
-		name: &str,
-		url: &str,
-		builder: Option<&mut build::RepoBuilder>,
-	) -> Result<Bucket> {
+	pub fn add(&self, name: &str, url: &str, options: CloneOptions) -> Result<Bucket> {
+		Self::validate_name(name)?;
+
 		let dir = self.path(name);
 
 		if dir.try_exists()? {
 			Err(Error::Exists)
 		} else {
 			let dir = self.dir.join(name);
+			let bucket = Bucket::clone(url, dir, options)?;
+
+			self.set_order(|order| order.push(name.to_owned()))?;
+
+			Ok(bucket)
+		}
+	}
+
+	/// Adds a local directory of manifests as a bucket, i.e., a folder on a network share. See [`Kind::Directory`].
+	///
+	/// The bucket records the path to the directory, so changes to the directory are seen without updating.
+	/// Unlike a link, this works on Windows without Developer Mode or administrator rights.
+	///
+	/// # Arguments
+	///
+	/// * `name` - The name to add the directory as.
+	/// * `path` - The path to the directory. Relative paths are resolved against the current directory.
+	///
+	/// # Errors
+	///
+	/// If the bucket name is not valid, [`Error::InvalidName`] is returned.
+	///
+	/// If the bucket name already exists, [`Error::Exists`] is returned.
+	///
+	/// If the directory does not exist, [`Error::NotFound`] is returned.
+	///
+	/// If the bucket cannot be created, [`Error::Io`] is returned.
+	///
+	/// [`Kind::Directory`]: crate::bucket::Kind::Directory
+	/// [`Error::InvalidName`]: crate::bucket::Error::InvalidName
+	/// [`Error::Exists`]: crate::bucket::Error::Exists
+	/// [`Error::NotFound`]: crate::bucket::Error::NotFound
+	/// [`Error::Io`]: crate::bucket::Error::Io
+	pub fn add_dir<P>(&self, name: &str, path: P) -> Result<Bucket>
+	where
+		P: AsRef<Path>,
+	{
+		Self::validate_name(name)?;
+
+		let dir = self.path(name);
+
+		if dir.try_exists()? {
+			return Err(Error::Exists);
+		}
 
-			Bucket::clone(url, dir, builder)
+		let path = path.as_ref();
+
+		if !path.is_dir() {
+			return Err(Error::NotFound);
 		}
+
+		// The bucket is used from other directories, so the path must be absolute.
+		let root = if path.is_absolute() {
+			path.to_owned()
+		} else {
+			env::current_dir()?.join(path)
+		};
+
+		let bucket = Bucket::create_dir(dir, root)?;
+		self.set_order(|order| order.push(name.to_owned()))?;
+
+		Ok(bucket)
 	}
 
-	/// Removes a bucket.
+	/// Removes a bucket, even if installed apps came from it. See [`Shovel::remove_bucket`].
 	///
 	/// # Arguments
 	///
@@ -125,17 +291,96 @@ impl Buckets {
 	///
 	/// If the bucket does not exist, [`Error::NotFound`] is returned.
 	///
+	/// [`Shovel::remove_bucket`]: crate::Shovel::remove_bucket
 	/// [`Error::NotFound`]: crate::bucket::Error::NotFound
 	pub fn remove(&self, name: &str) -> Result<()> {
 		let dir = self.path(name);
 
-		if dir.try_exists()? {
-			fs::remove_dir_all(dir)?;
+		// A directory bucket only records where its manifests are, so they are never removed with it.
+		if !dir.try_exists()? {
+			return Err(Error::NotFound);
+		}
 
-			Ok(())
-		} else {
-			Err(Error::NotFound)
+		fs::remove_dir_all(dir)?;
+
+		self.set_order(|order| order.retain(|o| o != name))
+	}
+
+	/// Renames a bucket. Installed apps still refer to the old name, see [`Shovel::rename_bucket`].
+	///
+	/// The bucket priority is not rewritten, as it comes from the config, which is owned by the caller.
+	/// Until the priority is updated to the new name, the bucket loses its priority.
+	///
+	/// # Arguments
+	///
+	/// * `from` - The name of the bucket.
+	/// * `to` - The new name of the bucket.
+	///
+	/// # Errors
+	///
+	/// If `to` is not a valid bucket name, i.e., it contains a path separator, [`Error::InvalidName`] is returned.
+	///
+	/// If the bucket does not exist, [`Error::NotFound`] is returned.
+	///
+	/// If a bucket named `to` already exists, [`Error::Exists`] is returned.
+	///
+	/// [`Shovel::rename_bucket`]: crate::Shovel::rename_bucket
+	/// [`Error::InvalidName`]: crate::bucket::Error::InvalidName
+	/// [`Error::NotFound`]: crate::bucket::Error::NotFound
+	/// [`Error::Exists`]: crate::bucket::Error::Exists
+	pub fn rename(&self, from: &str, to: &str) -> Result<Bucket> {
+		Self::validate_name(to)?;
+
+		let from_dir = self.path(from);
+		let to_dir = self.path(to);
+
+		if !from_dir.try_exists()? {
+			return Err(Error::NotFound);
 		}
+
+		if to_dir.try_exists()? {
+			return Err(Error::Exists);
+		}
+
+		fs::rename(&from_dir, &to_dir)?;
+
+		let renamed = Bucket::open(&to_dir).and_then(|bucket| {
+			// The bucket keeps its place in the order.
+			self.set_order(|order| {
+				for name in order.iter_mut().filter(|name| *name == from) {
+					to.clone_into(name);
+				}
+			})?;
+
+			Ok(bucket)
+		});
+
+		// Undo the rename, so a failed rename leaves the bucket as it was.
+		// The original error is more useful than any error undoing, so that is ignored.
+		if renamed.is_err() {
+			let _ = fs::rename(&to_dir, &from_dir);
+		}
+
+		renamed
+	}
+
+	/// Sets the URL a bucket pulls from. See [`Bucket::set_url`].
+	///
+	/// # Arguments
+	///
+	/// * `name` - The name of the bucket.
+	/// * `url` - The new Git URL of the remote bucket.
+	///
+	/// # Errors
+	///
+	/// If the bucket does not exist, [`Error::NotFound`] is returned.
+	///
+	/// If the URL cannot be set, [`Error::Git`] is returned.
+	///
+	/// [`Error::NotFound`]: crate::bucket::Error::NotFound
+	/// [`Error::Git`]: crate::bucket::Error::Git
+	pub fn set_url(&self, name: &str, url: &str) -> Result<()> {
+		self.open(name)?.set_url(url)
 	}
 
 	/// Parses and yields each manifest in all buckets where `filter(bucket)` and `predicate(manifest_name)` returns true.
@@ -156,6 +401,93 @@ impl Buckets {
 		SearchAll::new(buckets?, criteria)
 	}
 
+	/// Parses each manifest in all buckets where the criteria is satisfied, using all available cores.
+	///
+	/// Buckets are searched in priority order, like [`search_all`], so earlier results take precedence.
+	/// Unlike [`search_all`], the manifests in each bucket are sorted by name.
+	///
+	/// # Arguments
+	///
+	/// * `criteria` - The criteria that determines if a bucket or manifest should be parsed.
+	///
+	/// # Errors
+	///
+	/// If the directory containing the buckets or any bucket directory cannot be read, [`Error::Io`] is returned.
+	///
+	/// [`search_all`]: Buckets::search_all
+	/// [`Error::Io`]: crate::bucket::Error::Io
+	pub fn par_search_all<C>(&self, criteria: &C) -> Result<Vec<(Arc<Bucket>, SearchItem)>>
+	where
+		C: Criteria + Sync,
+	{
+		let buckets: Vec<_> = self
+			.iter()?
+			.filter(|bucket| bucket.as_ref().map_or(true, |b| criteria.filter_bucket(b)))
+			.collect::<Result<_>>()?;
+
+		let mut paths = vec![];
+
+		// Listing the manifests is cheap compared to parsing them, so it is done serially.
+		for bucket in buckets {
+			let bucket = Arc::new(bucket);
+
+			for (name, path) in bucket.manifest_paths(criteria)? {
+				paths.push((Arc::clone(&bucket), name, path));
+			}
+		}
+
+		// Collecting an indexed parallel iterator preserves the order of the paths.
+		let items = paths
+			.into_par_iter()
+			.filter_map(|(bucket, name, path)| {
+				let manifest = manifest_from_file(path);
+
+				// Manifests that failed to parse are always returned, so the error is not lost.
+				match &manifest {
+					Ok(parsed) if !criteria.filter_item(&bucket, &name, parsed) => None,
+					_ => Some((bucket, SearchItem { name, manifest })),
+				}
+			})
+			.collect();
+
+		Ok(items)
+	}
+
+	/// Returns the summaries of the manifests in all buckets where the criteria is satisfied,
+	/// using each bucket's index. See [`Bucket::summaries`].
+	///
+	/// # Arguments
+	///
+	/// * `criteria` - The criteria that determines if a bucket or manifest should be returned.
+	///
+	/// # Errors
+	///
+	/// If the directory containing the buckets cannot be read, [`Error::Io`] is returned.
+	///
+	/// If any bucket's index cannot be read or updated, [`Error::Git`] is returned.
+	///
+	/// [`Error::Io`]: crate::bucket::Error::Io
+	/// [`Error::Git`]: crate::bucket::Error::Git
+	pub fn summaries<C: Criteria>(&self, criteria: &C) -> Result<Vec<(Arc<Bucket>, SummaryItem)>> {
+		let mut summaries = vec![];
+
+		for bucket in self.iter()? {
+			let bucket = bucket?;
+
+			if !criteria.filter_bucket(&bucket) {
+				continue;
+			}
+
+			let items = bucket.summaries(criteria)?;
+
+			// Each item has a handle to the bucket, so the bucket is shared.
+			let bucket = Arc::new(bucket);
+			summaries.extend(items.into_iter().map(|item| (Arc::clone(&bucket), item)));
+		}
+
+		Ok(summaries)
+	}
+
 	/// Parses and yields each manifest in all buckets.
 	/// This is a convenience function over [`search_all`].
 	///
@@ -184,29 +516,65 @@ impl Buckets {
 	///
 	/// [`Error::Io`]: crate::bucket::Error::Io
 	/// [`Error::ManifestNotFound`]: crate::bucket::Error::ManifestNotFound
-	pub fn manifest(&self, name: &Name) -> Result<(Rc<Bucket>, SearchItem)> {
+	pub fn manifest(&self, name: &Name) -> Result<(Arc<Bucket>, SearchItem)> {
 		let mut search = self.search_all(name)?;
 
 		search.next().ok_or(Error::ManifestNotFound)
 	}
-}
 
-/// An iterator over buckets. Created by `Buckets::iter`.
-pub struct Iter {
-	dirs: util::Dirs,
-}
+	/// Parses and returns a manifest by name in every bucket, in priority order.
+	/// For an unqualified name, the first manifest is the one [`manifest`] returns, and the rest are shadowed by it.
+	///
+	/// # Arguments
+	///
+	/// * `name` - The name of the manifest.
+	///
+	/// # Errors
+	///
+	/// If the directory containing the buckets or any bucket directory cannot be read, [`Error::Io`] is returned.
+	///
+	/// [`manifest`]: Buckets::manifest
+	/// [`Error::Io`]: crate::bucket::Error::Io
+	pub fn candidates(&self, name: &Name) -> Result<Vec<(Arc<Bucket>, SearchItem)>> {
+		Ok(self.search_all(name)?.collect())
+	}
 
-impl Iter {
-	fn new<P>(dir: P) -> Result<Self>
-	where
-		P: AsRef<Path>,
-	{
-		Ok(Self {
-			dirs: util::dirs(dir)?,
-		})
+	/// Parses and returns a single manifest in any bucket, without guessing between buckets.
+	/// Use this instead of [`manifest`] where picking the wrong bucket is unsafe, such as installing apps.
+	///
+	/// # Arguments
+	///
+	/// * `name` - The name of the manifest.
+	///
+	/// # Errors
+	///
+	/// If the name is not qualified and the manifest is in more than one bucket, [`Error::Ambiguous`] is returned.
+	///
+	/// See [`manifest`] for other errors.
+	///
+	/// [`manifest`]: Buckets::manifest
+	/// [`Error::Ambiguous`]: crate::bucket::Error::Ambiguous
+	pub fn unique_manifest(&self, name: &Name) -> Result<(Arc<Bucket>, SearchItem)> {
+		let mut candidates = self.candidates(name)?;
+
+		if candidates.len() > 1 {
+			let names = candidates
+				.iter()
+				.map(|(bucket, item)| format!("{}/{}", bucket.name(), item.name))
+				.collect();
+
+			return Err(Error::Ambiguous(names));
+		}
+
+		candidates.pop().ok_or(Error::ManifestNotFound)
 	}
 }
 
+/// An iterator over buckets in priority order. Created by `Buckets::iter`.
+pub struct Iter {
+	dirs: vec::IntoIter<PathBuf>,
+}
+
 impl Iterator for Iter {
 	type Item = Result<Bucket>;
 
@@ -217,7 +585,7 @@ impl Iterator for Iter {
 	}
 }
 
-type SearchAllInner<C> = Zip<Repeat<Rc<Bucket>>, Search<C>>;
+type SearchAllInner<C> = Zip<Repeat<Arc<Bucket>>, Search<C>>;
 
 /// An iterator over manifests in all buckets, filtered by criteria of type `C`. Created by `Buckets::search_all`.
 pub struct SearchAll<C: Criteria> {
@@ -229,10 +597,10 @@ impl<C: Criteria> SearchAll<C> {
 		let manifests: Result<Vec<_>> = buckets
 			.into_iter()
 			.filter(|bucket| criteria.clone().filter_bucket(bucket))
-			// Since each item has a handle to the bucket, the handle is wrapped in an Rc to avoid duplication.
+			// Since each item has a handle to the bucket, the handle is wrapped in an Arc to avoid duplication.
 			.map(|bucket| {
-				// Since each item has a handle to the bucket, the handle is wrapped in an Rc to avoid duplication.
-				let bucket = Rc::new(bucket);
+				// Since each item has a handle to the bucket, the handle is wrapped in an Arc to avoid duplication.
+				let bucket = Arc::new(bucket);
 				let manifests = bucket.search(criteria.clone())?;
 
 				// Zip the bucket and its manifests together.
@@ -247,7 +615,7 @@ impl<C: Criteria> SearchAll<C> {
 }
 
 impl<C: Criteria> Iterator for SearchAll<C> {
-	type Item = (Rc<Bucket>, SearchItem);
+	type Item = (Arc<Bucket>, SearchItem);
 
 	fn next(&mut self) -> Option<Self::Item> {
 		self.inner.next()
@@ -291,6 +659,198 @@ mod tests {
 		assert_eq!(names_from_buckets, names_from_fs);
 	}
 
+	#[test]
+	fn par_search_all() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let manifest = test::manifest_json("1.0", "");
+
+		for bucket in ["main", "extras"] {
+			test::create_bucket(
+				&temp_dir.path().join(bucket),
+				&[("b", &manifest), ("c", &manifest), ("a", &manifest)],
+			);
+		}
+
+		let items = |buckets: Buckets| -> Vec<_> {
+			buckets
+				.par_search_all(&())
+				.unwrap()
+				.into_iter()
+				.map(|(bucket, item)| {
+					item.manifest.unwrap();
+
+					format!("{}/{}", bucket.name(), item.name)
+				})
+				.collect()
+		};
+
+		// Buckets are in priority order, like name resolution, with main first by default.
+		assert_eq!(
+			items(Buckets::new(temp_dir.path())),
+			["main/a", "main/b", "main/c", "extras/a", "extras/b", "extras/c"]
+		);
+		assert_eq!(
+			items(Buckets::new(temp_dir.path()).with_priority(vec!["extras".to_owned()])),
+			["extras/a", "extras/b", "extras/c", "main/a", "main/b", "main/c"]
+		);
+	}
+
+	#[test]
+	fn filter_parsed() {
+		#[derive(Clone)]
+		struct Version;
+
+		impl Criteria for Version {
+			fn filter_bucket(&self, _bucket: &Bucket) -> bool {
+				true
+			}
+
+			fn filter_manifest(&self, _manifest: &str) -> bool {
+				true
+			}
+
+			fn filter_parsed(&self, manifest: &crate::manifest::Manifest) -> bool {
+				manifest.version == "2.0"
+			}
+		}
+
+		let temp_dir = tempfile::tempdir().unwrap();
+		let old = test::manifest_json("1.0", "");
+		let new = test::manifest_json("2.0", "");
+
+		test::create_bucket(
+			&temp_dir.path().join("main"),
+			&[("old", &old), ("new", &new), ("broken", "{")],
+		);
+
+		let buckets = Buckets::new(temp_dir.path());
+
+		let mut names: Vec<_> = buckets
+			.search_all(&Version)
+			.unwrap()
+			.map(|(_, item)| item.name)
+			.collect();
+		names.sort();
+
+		assert_eq!(names, ["broken", "new"]);
+
+		let names: Vec<_> = buckets
+			.par_search_all(&Version)
+			.unwrap()
+			.into_iter()
+			.map(|(_, item)| item.name)
+			.collect();
+
+		assert_eq!(names, ["broken", "new"]);
+	}
+
+	#[test]
+	fn priority() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let json = test::manifest_json("1.0", "");
+
+		for bucket in ["alpha", "main", "versions"] {
+			test::create_bucket(&temp_dir.path().join(bucket), &[("git", &json)]);
+		}
+
+		let buckets = Buckets::new(temp_dir.path());
+		let names: Vec<_> = buckets.iter().unwrap().map(|b| b.unwrap().name()).collect();
+
+		assert_eq!(names[0], "main");
+
+		let buckets = buckets.with_priority(vec!["versions".to_owned(), "missing".to_owned()]);
+		let names: Vec<_> = buckets.iter().unwrap().map(|b| b.unwrap().name()).collect();
+
+		assert_eq!(names, ["versions", "main", "alpha"]);
+
+		let (bucket, _) = buckets.manifest(&Name::new("git".to_owned())).unwrap();
+		assert_eq!(bucket.name(), "versions");
+
+		let Err(Error::Ambiguous(candidates)) =
+			buckets.unique_manifest(&Name::new("git".to_owned()))
+		else {
+			panic!("expected an ambiguous manifest");
+		};
+		assert_eq!(candidates, ["versions/git", "main/git", "alpha/git"]);
+
+		let (bucket, _) = buckets
+			.unique_manifest(&Name::new("alpha/git".to_owned()))
+			.unwrap();
+		assert_eq!(bucket.name(), "alpha");
+	}
+
+	#[test]
+	fn order() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let json = test::manifest_json("1.0", "");
+
+		for bucket in ["alpha", "main", "versions", "scoop"] {
+			test::create_bucket(&temp_dir.path().join(bucket), &[("git", &json)]);
+		}
+
+		let buckets = Buckets::new(temp_dir.path());
+		buckets
+			.set_order(|order| order.extend(["versions".to_owned(), "alpha".to_owned()]))
+			.unwrap();
+
+		// Buckets that were not recorded were added before the order was.
+		assert_eq!(
+			buckets.names().unwrap(),
+			["main", "scoop", "versions", "alpha"]
+		);
+
+		buckets.rename("versions", "old").unwrap();
+		assert_eq!(buckets.names().unwrap(), ["main", "scoop", "old", "alpha"]);
+
+		buckets.remove("old").unwrap();
+		assert_eq!(buckets.order(), ["alpha"]);
+
+		// A directory that cannot be opened as a bucket is renamed back.
+		fs::create_dir(temp_dir.path().join("broken")).unwrap();
+		assert!(matches!(
+			buckets.rename("broken", "fixed"),
+			Err(Error::NotBucket)
+		));
+		assert!(buckets.path("broken").exists());
+		assert!(!buckets.path("fixed").exists());
+	}
+
+	#[test]
+	fn add_dir() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let share = temp_dir.path().join("share");
+		let json = test::manifest_json("1.0", "");
+
+		fs::create_dir_all(share.join("bucket")).unwrap();
+		fs::write(share.join("bucket").join("app.json"), &json).unwrap();
+
+		let buckets = Buckets::new(temp_dir.path().join("buckets"));
+		fs::create_dir_all(temp_dir.path().join("buckets")).unwrap();
+
+		let bucket = buckets.add_dir("share", &share).unwrap();
+		assert_eq!(bucket.url().unwrap(), util::path_to_string(&share));
+		assert!(matches!(
+			buckets.add_dir("share", &share),
+			Err(Error::Exists)
+		));
+
+		let (bucket, _) = buckets.manifest(&Name::new("app".to_owned())).unwrap();
+		assert_eq!(bucket.name(), "share");
+
+		// Removing the bucket leaves the directory it points to.
+		buckets.remove("share").unwrap();
+		assert!(!buckets.path("share").exists());
+		assert!(share.join("bucket").join("app.json").exists());
+	}
+
+	#[test]
+	fn search_is_send() {
+		fn assert_send<T: Send>() {}
+
+		assert_send::<AllManifests>();
+		assert_send::<Arc<Bucket>>();
+	}
+
 	#[test]
 	fn buckets_manifests() {
 		let buckets = Buckets::new(buckets_dir());
@@ -0,0 +1,214 @@
+use crate::bucket::Layout;
+use crate::bucket::Result;
+use crate::json;
+
+json::json_struct! {
+	/// The part of a manifest needed to compare versions.
+	/// Other fields are not parsed, so changes to manifests that are otherwise invalid are still reported.
+	struct Versioned {
+		version: String,
+	}
+}
+
+/// Returns the version of a manifest in a Git blob, if it can be read.
+pub(crate) fn blob_version(repo: &git2::Repository, id: git2::Oid) -> Option<String> {
+	let blob = repo.find_blob(id).ok()?;
+	let manifest: Versioned = json::from_reader(blob.content()).ok()?;
+
+	Some(manifest.version)
+}
+
+fn version_of(repo: &git2::Repository, file: &git2::DiffFile) -> Option<String> {
+	blob_version(repo, file.id())
+}
+
+/// A change to a manifest between two commits of a bucket. See [`Bucket::changes`].
+///
+/// Versions are None if the manifest's version cannot be read.
+///
+/// [`Bucket::changes`]: crate::bucket::Bucket::changes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+	/// A manifest was added.
+	Added {
+		name: String,
+		version: Option<String>,
+	},
+
+	/// A manifest was removed.
+	Removed {
+		name: String,
+		version: Option<String>,
+	},
+
+	/// A manifest was renamed, possibly with other changes.
+	Renamed { from: String, to: String },
+
+	/// A manifest's version was changed.
+	Updated {
+		name: String,
+		from: Option<String>,
+		to: Option<String>,
+	},
+}
+
+impl Change {
+	/// Returns the name of the manifest after the change, or before the change if it was removed.
+	#[must_use]
+	pub fn name(&self) -> &str {
+		match self {
+			Self::Added { name, .. } | Self::Removed { name, .. } | Self::Updated { name, .. } => {
+				name
+			}
+			Self::Renamed { to, .. } => to,
+		}
+	}
+}
+
+/// Returns the changes to manifests between two trees, sorted by name.
+/// Manifests that changed without a version change are not included.
+pub(crate) fn diff(
+	repo: &git2::Repository,
+	layout: Layout,
+	old: &git2::Tree,
+	new: &git2::Tree,
+) -> Result<Vec<Change>> {
+	let mut options = git2::DiffOptions::new();
+
+	if let Some(pathspec) = layout.pathspec() {
+		options.pathspec(pathspec);
+	}
+
+	let mut diff = repo.diff_tree_to_tree(Some(old), Some(new), Some(&mut options))?;
+
+	// Rename detection is opt-in, as it compares the content of every added and removed file.
+	// Manifests share a lot of boilerplate, so only near-identical files are treated as renames.
+	let mut find_options = git2::DiffFindOptions::new();
+	find_options.renames(true).rename_threshold(90);
+
+	diff.find_similar(Some(&mut find_options))?;
+
+	let name = |file: &git2::DiffFile| file.path().and_then(|path| layout.manifest_name(path));
+	let mut changes = vec![];
+
+	for delta in diff.deltas() {
+		let (old_file, new_file) = (delta.old_file(), delta.new_file());
+
+		let change = match (delta.status(), name(&old_file), name(&new_file)) {
+			// A manifest moved into or out of the manifest directory is added or removed.
+			(git2::Delta::Added, _, Some(name)) | (git2::Delta::Renamed, None, Some(name)) => {
+				Change::Added {
+					name,
+					version: version_of(repo, &new_file),
+				}
+			}
+			(git2::Delta::Deleted, Some(name), _) | (git2::Delta::Renamed, Some(name), None) => {
+				Change::Removed {
+					name,
+					version: version_of(repo, &old_file),
+				}
+			}
+			(git2::Delta::Renamed, Some(from), Some(to)) => Change::Renamed { from, to },
+			(git2::Delta::Modified, Some(name), Some(_)) => {
+				let from = version_of(repo, &old_file);
+				let to = version_of(repo, &new_file);
+
+				if from == to {
+					continue;
+				}
+
+				Change::Updated { name, from, to }
+			}
+			_ => continue,
+		};
+
+		changes.push(change);
+	}
+
+	changes.sort_by(|a, b| a.name().cmp(b.name()));
+
+	Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::path::Path;
+
+	use super::*;
+	use crate::bucket::Bucket;
+	use crate::test;
+
+	#[test]
+	fn changes() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let v1 = test::manifest_json("1.0", "");
+		let v2 = test::manifest_json("2.0", "");
+		let added = test::manifest_json(
+			"3.1.4",
+			r#""description": "A new app", "bin": ["app.exe", "tool.exe"], "depends": "lib""#,
+		);
+		let described = test::manifest_json("1.0", r#""description": "Renamed""#);
+		let fixed = test::manifest_json("1.0", r#""description": "Fixed""#);
+
+		let repo = test::create_bucket(
+			temp_dir.path(),
+			&[
+				("updated", &v1),
+				("removed", &v1),
+				("old", &described),
+				("fixed", &v1),
+			],
+		);
+		let from = repo.head().unwrap().peel_to_commit().unwrap().id();
+
+		// Rename `old` and remove `removed`.
+		let mut index = repo.index().unwrap();
+
+		for name in ["old", "removed"] {
+			let path = Path::new("bucket").join(format!("{name}.json"));
+
+			fs::remove_file(temp_dir.path().join(&path)).unwrap();
+			index.remove_path(&path).unwrap();
+		}
+
+		index.write().unwrap();
+		test::commit_index(&repo, &mut index, "Remove manifests");
+
+		let to = test::commit_manifests(
+			&repo,
+			&[
+				("updated", &v2),
+				("new", &described),
+				("added", &added),
+				("fixed", &fixed),
+			],
+			"Update manifests",
+		);
+
+		let bucket = Bucket::open(temp_dir.path()).unwrap();
+
+		assert_eq!(
+			bucket.changes(from, to).unwrap(),
+			[
+				Change::Added {
+					name: "added".to_owned(),
+					version: Some("3.1.4".to_owned()),
+				},
+				Change::Renamed {
+					from: "old".to_owned(),
+					to: "new".to_owned(),
+				},
+				Change::Removed {
+					name: "removed".to_owned(),
+					version: Some("1.0".to_owned()),
+				},
+				Change::Updated {
+					name: "updated".to_owned(),
+					from: Some("1.0".to_owned()),
+					to: Some("2.0".to_owned()),
+				},
+			]
+		);
+	}
+}
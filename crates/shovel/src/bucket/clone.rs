@@ -0,0 +1,268 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+
+use git2::build::CheckoutBuilder;
+use git2::FetchOptions;
+
+use crate::bucket::layout::DEPRECATED_DIR;
+use crate::bucket::Layout;
+use crate::bucket::Result;
+
+/// The directories checked out by a sparse clone.
+/// Scripts are kept, as manifests may refer to them, and retired manifests are kept for deprecation warnings.
+const SPARSE_DIRS: [&str; 3] = ["bucket", DEPRECATED_DIR, "scripts"];
+
+/// The fetch depth that converts a shallow clone into a full clone.
+pub(crate) const UNSHALLOW: i32 = i32::MAX;
+
+/// Options for cloning a bucket. See [`Buckets::add`].
+///
+/// [`Buckets::add`]: crate::bucket::Buckets::add
+#[derive(Default)]
+pub struct CloneOptions<'a> {
+	pub(crate) depth: Option<u32>,
+	pub(crate) sparse: bool,
+	pub(crate) fetch_options: Option<FetchOptions<'a>>,
+	pub(crate) checkout_builder: Option<CheckoutBuilder<'a>>,
+}
+
+impl<'a> CloneOptions<'a> {
+	/// Creates a new set of clone options. By default, the full history and working tree is cloned.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Limits the history to the last `depth` commits, i.e., a shallow clone.
+	///
+	/// Features that need older history, such as [`Bucket::manifest_commit`], report [`Error::ShallowHistory`]
+	/// until the bucket is [`unshallow`]ed.
+	///
+	/// [`Bucket::manifest_commit`]: crate::bucket::Bucket::manifest_commit
+	/// [`Error::ShallowHistory`]: crate::bucket::Error::ShallowHistory
+	/// [`unshallow`]: crate::bucket::Bucket::unshallow
+	pub fn depth(&mut self, depth: u32) -> &mut Self {
+		self.depth = Some(depth);
+		self
+	}
+
+	/// Sets whether to check out only the `bucket` and `scripts` directories.
+	///
+	/// Buckets with manifests at the root of the repository are always checked out in full.
+	pub fn sparse(&mut self, sparse: bool) -> &mut Self {
+		self.sparse = sparse;
+		self
+	}
+
+	/// Sets the Git fetch options to clone with.
+	pub fn fetch_options(&mut self, fetch_options: FetchOptions<'a>) -> &mut Self {
+		self.fetch_options = Some(fetch_options);
+		self
+	}
+
+	/// Sets the Git checkout builder to clone with.
+	pub fn checkout_builder(&mut self, checkout_builder: CheckoutBuilder<'a>) -> &mut Self {
+		self.checkout_builder = Some(checkout_builder);
+		self
+	}
+}
+
+/// Checks if a path in a bucket's tree is outside of a sparse checkout.
+fn is_excluded(path: &[u8]) -> bool {
+	!SPARSE_DIRS.iter().any(|dir| {
+		path.strip_prefix(dir.as_bytes())
+			.is_some_and(|rest| rest.first() == Some(&b'/'))
+	})
+}
+
+/// Limits a checkout to the directories of a sparse checkout.
+pub(crate) fn limit_checkout(checkout_builder: &mut CheckoutBuilder) {
+	for dir in SPARSE_DIRS {
+		checkout_builder.path(dir);
+	}
+}
+
+/// Checks if a repository has a sparse checkout.
+pub(crate) fn is_sparse(repo: &git2::Repository) -> bool {
+	repo.config()
+		.and_then(|config| config.get_bool("core.sparseCheckout"))
+		.unwrap_or(false)
+}
+
+/// Enables a sparse checkout in a cloned repository, in a way Git also understands.
+/// If the bucket does not use the standard layout, the rest of the working tree is checked out instead.
+pub(crate) fn enable_sparse(repo: &git2::Repository) -> Result<()> {
+	let tree = repo.head()?.peel_to_tree()?;
+
+	if tree.get_path(Layout::Standard.manifest_dir()).is_err() {
+		let mut checkout_builder = CheckoutBuilder::new();
+		repo.checkout_head(Some(checkout_builder.force()))?;
+
+		return Ok(());
+	}
+
+	let info = repo.path().join("info");
+	fs::create_dir_all(&info)?;
+
+	let patterns: Vec<_> = SPARSE_DIRS.iter().map(|dir| format!("/{dir}/")).collect();
+	fs::write(info.join("sparse-checkout"), patterns.join("\n") + "\n")?;
+
+	repo.config()?.set_bool("core.sparseCheckout", true)?;
+
+	apply_sparse(repo)
+}
+
+/// Resets the index to HEAD, and marks the paths outside of the sparse checkout to be skipped in the working tree.
+/// Otherwise, paths that were not checked out are reported as deleted.
+pub(crate) fn apply_sparse(repo: &git2::Repository) -> Result<()> {
+	let tree = repo.head()?.peel_to_tree()?;
+
+	let mut index = repo.index()?;
+	index.read_tree(&tree)?;
+
+	let excluded: Vec<_> = index
+		.iter()
+		.filter(|entry| is_excluded(&entry.path))
+		.collect();
+
+	for mut entry in excluded {
+		entry.flags |= git2::IndexEntryFlag::EXTENDED.bits();
+		entry.flags_extended |= git2::IndexEntryExtendedFlag::SKIP_WORKTREE.bits();
+
+		index.add(&entry)?;
+	}
+
+	index.write()?;
+
+	Ok(())
+}
+
+/// Returns the shallow commits of a repository, i.e., the commits whose parents were not fetched.
+pub(crate) fn shallow_roots(repo: &git2::Repository) -> Result<HashSet<git2::Oid>> {
+	let content = match fs::read_to_string(repo.path().join("shallow")) {
+		Ok(content) => content,
+		Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(HashSet::new()),
+		Err(err) => return Err(err.into()),
+	};
+
+	let roots = content
+		.lines()
+		.filter_map(|line| git2::Oid::from_str(line.trim()).ok())
+		.collect();
+
+	Ok(roots)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+
+	use super::*;
+	use crate::bucket::Bucket;
+	use crate::bucket::Error;
+	use crate::test;
+	use crate::util;
+
+	#[test]
+	fn excluded() {
+		assert!(!is_excluded(b"bucket/app.json"));
+		assert!(!is_excluded(b"scripts/app/install.ps1"));
+		assert!(!is_excluded(b"deprecated/app.json"));
+		assert!(is_excluded(b"README.md"));
+		assert!(is_excluded(b"bucketeer/app.json"));
+	}
+
+	/// Returns the paths in the index that are skipped in the working tree.
+	fn skipped(repo: &git2::Repository) -> Vec<String> {
+		repo.index()
+			.unwrap()
+			.iter()
+			.filter(|entry| {
+				entry.flags_extended & git2::IndexEntryExtendedFlag::SKIP_WORKTREE.bits() != 0
+			})
+			.map(|entry| String::from_utf8(entry.path).unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn sparse() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let upstream_dir = temp_dir.path().join("upstream");
+		let json = test::manifest_json("1.0", "");
+
+		let upstream = test::create_bucket(&upstream_dir, &[("app", &json)]);
+
+		fs::write(upstream_dir.join("README.md"), "# Bucket").unwrap();
+		fs::create_dir(upstream_dir.join(DEPRECATED_DIR)).unwrap();
+		fs::write(upstream_dir.join(DEPRECATED_DIR).join("old.json"), &json).unwrap();
+		let mut index = upstream.index().unwrap();
+		index.add_path(Path::new("README.md")).unwrap();
+		index.add_path(Path::new("deprecated/old.json")).unwrap();
+		index.write().unwrap();
+		test::commit_index(&upstream, &mut index, "Add readme and deprecated manifest");
+
+		let mut options = CloneOptions::new();
+		options.sparse(true);
+
+		let dir = temp_dir.path().join("bucket");
+		let bucket = Bucket::clone(&util::path_to_string(&upstream_dir), &dir, options).unwrap();
+
+		assert!(bucket.is_sparse());
+		assert!(dir.join("bucket").join("app.json").exists());
+		assert!(!dir.join("README.md").exists());
+
+		// Retired manifests are still found.
+		assert!(bucket.is_deprecated("old"));
+		assert_eq!(bucket.manifest("old", true).unwrap().version, "1.0");
+
+		let repo = bucket.repo().unwrap();
+		assert_eq!(skipped(&repo), ["README.md"]);
+
+		// Pulling keeps the checkout sparse.
+		let v2 = test::manifest_json("2.0", "");
+		test::commit_manifests(&upstream, &[("app", &v2)], "Update app");
+		fs::write(upstream_dir.join("README.md"), "# Updated").unwrap();
+		let mut index = upstream.index().unwrap();
+		index.add_path(Path::new("README.md")).unwrap();
+		index.write().unwrap();
+		test::commit_index(&upstream, &mut index, "Update readme");
+
+		bucket.pull(None, None).unwrap();
+
+		assert_eq!(bucket.manifest("app", false).unwrap().version, "2.0");
+		assert!(!dir.join("README.md").exists());
+		assert_eq!(skipped(&repo), ["README.md"]);
+		assert!(repo
+			.status_file(Path::new("bucket/app.json"))
+			.unwrap()
+			.is_empty());
+	}
+
+	#[test]
+	fn shallow() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let v1 = test::manifest_json("1.0", "");
+		let v2 = test::manifest_json("2.0", "");
+
+		let repo = test::create_bucket(temp_dir.path(), &[("app", &v1), ("lib", &v1)]);
+		let root = test::commit_manifests(&repo, &[("other", &v1)], "Add other");
+		let updated = test::commit_manifests(&repo, &[("app", &v2)], "Update app");
+
+		// Local clones cannot be shallow, so cut the history like a shallow fetch does.
+		fs::write(repo.path().join("shallow"), format!("{root}\n")).unwrap();
+
+		let bucket = Bucket::open(temp_dir.path()).unwrap();
+
+		assert!(bucket.is_shallow());
+		assert_eq!(bucket.manifest_commit("app").unwrap().unwrap().id, updated);
+		assert!(matches!(
+			bucket.manifest_commit("lib"),
+			Err(Error::ShallowHistory)
+		));
+		assert!(matches!(
+			bucket.manifest_version("app", "1.0"),
+			Err(Error::ShallowHistory)
+		));
+	}
+}
@@ -1,14 +1,75 @@
 use crate::bucket::Bucket;
+use crate::manifest::Arch;
+use crate::manifest::Manifest;
 
 /// A filter for buckets and manifests.
 ///
-/// For a no-op filter, use `()`.
+/// Each stage ([`filter_bucket`], [`filter_manifest`] and [`filter_parsed`]) is checked as early as possible to skip work,
+/// and [`filter_item`] makes the final decision once the manifest is parsed.
+/// A stage may accept items that are rejected later, but must not reject items that [`filter_item`] would accept.
+///
+/// For a no-op filter, use `()`. Criteria can be combined with [`and`], [`or`] and [`not`].
+///
+/// [`filter_bucket`]: Criteria::filter_bucket
+/// [`filter_manifest`]: Criteria::filter_manifest
+/// [`filter_parsed`]: Criteria::filter_parsed
+/// [`filter_item`]: Criteria::filter_item
+/// [`and`]: Criteria::and
+/// [`or`]: Criteria::or
+/// [`not`]: Criteria::not
 pub trait Criteria: Clone {
 	/// Determines if the bucket should be yielded.
 	fn filter_bucket(&self, bucket: &Bucket) -> bool;
 
 	/// Determines if the manifest by name should be yielded.
 	fn filter_manifest(&self, manifest: &str) -> bool;
+
+	/// Determines if the manifest should be yielded after it has been parsed.
+	/// This is only called for manifests that passed [`filter_manifest`] and parsed successfully.
+	///
+	/// By default, all manifests are yielded.
+	///
+	/// [`filter_manifest`]: Criteria::filter_manifest
+	fn filter_parsed(&self, _manifest: &Manifest) -> bool {
+		true
+	}
+
+	/// Determines if the parsed manifest by name in the bucket should be yielded.
+	/// This is the final decision, as every part of the item is known.
+	///
+	/// By default, every stage must be satisfied.
+	fn filter_item(&self, bucket: &Bucket, name: &str, manifest: &Manifest) -> bool {
+		self.filter_bucket(bucket) && self.filter_manifest(name) && self.filter_parsed(manifest)
+	}
+
+	/// Determines if the manifest must be parsed to make the final decision, i.e., if [`filter_parsed`] or
+	/// [`filter_item`] may reject an item that passed [`filter_bucket`] and [`filter_manifest`].
+	/// If not, summaries can be taken from a bucket's index without parsing the manifest.
+	///
+	/// By default, manifests are always parsed.
+	///
+	/// [`filter_bucket`]: Criteria::filter_bucket
+	/// [`filter_manifest`]: Criteria::filter_manifest
+	/// [`filter_parsed`]: Criteria::filter_parsed
+	/// [`filter_item`]: Criteria::filter_item
+	fn needs_manifest(&self) -> bool {
+		true
+	}
+
+	/// Returns a criteria that is satisfied when both `self` and `other` are satisfied.
+	fn and<C: Criteria>(self, other: C) -> And<Self, C> {
+		And(self, other)
+	}
+
+	/// Returns a criteria that is satisfied when either `self` or `other` is satisfied.
+	fn or<C: Criteria>(self, other: C) -> Or<Self, C> {
+		Or(self, other)
+	}
+
+	/// Returns a criteria that is satisfied when `self` is not satisfied.
+	fn not(self) -> Not<Self> {
+		Not(self)
+	}
 }
 
 impl Criteria for () {
@@ -19,6 +80,10 @@ impl Criteria for () {
 	fn filter_manifest(&self, _manifest: &str) -> bool {
 		true
 	}
+
+	fn needs_manifest(&self) -> bool {
+		false
+	}
 }
 
 /// A predicate filter.
@@ -60,4 +125,292 @@ where
 	fn filter_manifest(&self, manifest: &str) -> bool {
 		(self.manifest)(manifest)
 	}
+
+	fn needs_manifest(&self) -> bool {
+		false
+	}
+}
+
+/// A criteria that is satisfied when both criteria are satisfied. Created by [`Criteria::and`].
+#[derive(Clone)]
+pub struct And<A: Criteria, B: Criteria>(A, B);
+
+impl<A: Criteria, B: Criteria> Criteria for And<A, B> {
+	fn filter_bucket(&self, bucket: &Bucket) -> bool {
+		self.0.filter_bucket(bucket) && self.1.filter_bucket(bucket)
+	}
+
+	fn filter_manifest(&self, manifest: &str) -> bool {
+		self.0.filter_manifest(manifest) && self.1.filter_manifest(manifest)
+	}
+
+	fn filter_parsed(&self, manifest: &Manifest) -> bool {
+		self.0.filter_parsed(manifest) && self.1.filter_parsed(manifest)
+	}
+
+	fn filter_item(&self, bucket: &Bucket, name: &str, manifest: &Manifest) -> bool {
+		self.0.filter_item(bucket, name, manifest) && self.1.filter_item(bucket, name, manifest)
+	}
+
+	fn needs_manifest(&self) -> bool {
+		self.0.needs_manifest() || self.1.needs_manifest()
+	}
+}
+
+/// A criteria that is satisfied when either criteria is satisfied. Created by [`Criteria::or`].
+///
+/// Each stage only rejects what both criteria reject. For example, a manifest may pass
+/// the bucket stage because of one criteria and the name stage because of the other,
+/// so the final decision is left to [`Criteria::filter_item`], and the manifest is always needed.
+#[derive(Clone)]
+pub struct Or<A: Criteria, B: Criteria>(A, B);
+
+impl<A: Criteria, B: Criteria> Criteria for Or<A, B> {
+	fn filter_bucket(&self, bucket: &Bucket) -> bool {
+		self.0.filter_bucket(bucket) || self.1.filter_bucket(bucket)
+	}
+
+	fn filter_manifest(&self, manifest: &str) -> bool {
+		self.0.filter_manifest(manifest) || self.1.filter_manifest(manifest)
+	}
+
+	fn filter_parsed(&self, manifest: &Manifest) -> bool {
+		self.0.filter_parsed(manifest) || self.1.filter_parsed(manifest)
+	}
+
+	fn filter_item(&self, bucket: &Bucket, name: &str, manifest: &Manifest) -> bool {
+		self.0.filter_item(bucket, name, manifest) || self.1.filter_item(bucket, name, manifest)
+	}
+}
+
+/// A criteria that is satisfied when the inner criteria is not satisfied. Created by [`Criteria::not`].
+///
+/// An item rejected by any stage of the inner criteria satisfies this criteria,
+/// so no stage can reject anything before the final decision in [`Criteria::filter_item`].
+#[derive(Clone)]
+pub struct Not<C: Criteria>(C);
+
+impl<C: Criteria> Criteria for Not<C> {
+	fn filter_bucket(&self, _bucket: &Bucket) -> bool {
+		true
+	}
+
+	fn filter_manifest(&self, _manifest: &str) -> bool {
+		true
+	}
+
+	fn filter_item(&self, bucket: &Bucket, name: &str, manifest: &Manifest) -> bool {
+		!self.0.filter_item(bucket, name, manifest)
+	}
+}
+
+/// A filter for manifest names by a regular expression.
+#[derive(Clone)]
+pub struct Pattern(regex::Regex);
+
+impl Pattern {
+	/// Creates a new pattern filter.
+	///
+	/// # Arguments
+	///
+	/// * `regex` - The regex that manifest names must match. It is not anchored, so use `^` and `$` to match whole names.
+	#[must_use]
+	pub fn new(regex: regex::Regex) -> Self {
+		Self(regex)
+	}
+}
+
+impl Criteria for Pattern {
+	fn filter_bucket(&self, _bucket: &Bucket) -> bool {
+		true
+	}
+
+	fn filter_manifest(&self, manifest: &str) -> bool {
+		self.0.is_match(manifest)
+	}
+
+	fn needs_manifest(&self) -> bool {
+		false
+	}
+}
+
+/// A filter for buckets by name.
+#[derive(Clone, Debug)]
+pub enum BucketList {
+	/// Only buckets in the list are yielded.
+	Allow(Vec<String>),
+
+	/// Buckets in the list are skipped.
+	Deny(Vec<String>),
+}
+
+impl BucketList {
+	/// Creates a filter that only yields the named buckets.
+	pub fn allow<I, S>(names: I) -> Self
+	where
+		I: IntoIterator<Item = S>,
+		S: Into<String>,
+	{
+		Self::Allow(names.into_iter().map(Into::into).collect())
+	}
+
+	/// Creates a filter that skips the named buckets.
+	pub fn deny<I, S>(names: I) -> Self
+	where
+		I: IntoIterator<Item = S>,
+		S: Into<String>,
+	{
+		Self::Deny(names.into_iter().map(Into::into).collect())
+	}
+}
+
+impl Criteria for BucketList {
+	fn filter_bucket(&self, bucket: &Bucket) -> bool {
+		let name = bucket.name();
+
+		match self {
+			Self::Allow(names) => names.contains(&name),
+			Self::Deny(names) => !names.contains(&name),
+		}
+	}
+
+	fn filter_manifest(&self, _manifest: &str) -> bool {
+		true
+	}
+
+	fn needs_manifest(&self) -> bool {
+		false
+	}
+}
+
+/// A filter for parsed manifests by the value of a field.
+#[derive(Clone, Debug)]
+pub enum Field {
+	/// The manifest's license identifier is one of the identifiers in the license expression
+	/// (e.g., `MIT` for `MIT|Apache-2.0`). Comparison is case-insensitive.
+	License(String),
+
+	/// The manifest has a download for the architecture.
+	Arch(Arch),
+
+	/// The manifest adds at least one command for its compatible architecture.
+	HasBin,
+
+	/// The manifest can be updated automatically.
+	HasAutoupdate,
+}
+
+impl Criteria for Field {
+	fn filter_bucket(&self, _bucket: &Bucket) -> bool {
+		true
+	}
+
+	fn filter_manifest(&self, _manifest: &str) -> bool {
+		true
+	}
+
+	fn filter_parsed(&self, manifest: &Manifest) -> bool {
+		match self {
+			Self::License(id) => manifest
+				.license
+				.to_string()
+				.split(['|', ',', ' '])
+				.any(|license| license.eq_ignore_ascii_case(id)),
+			Self::Arch(arch) => manifest.url(*arch).is_some(),
+			Self::HasBin => manifest.bin(manifest.compatible()).is_some(),
+			Self::HasAutoupdate => manifest.autoupdate.is_some(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::bucket::Buckets;
+	use crate::test;
+
+	/// Searches buckets with `main/{git, curl, 7zip}` and `extras/{git, vim}`, returning `bucket/name` for each match.
+	fn search<C: Criteria + Sync>(criteria: &C) -> Vec<String> {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let plain = test::manifest_json("1.0", "");
+		let bin = r#"{"version": "1.0", "homepage": "https://example.test", "license": "GPL-2.0-only", "url": "https://example.test/app.zip", "bin": "git.exe"}"#;
+		let autoupdate = test::manifest_json(
+			"1.0",
+			r#""autoupdate": {"url": "https://example.test/$version.zip"}"#,
+		);
+
+		test::create_bucket(
+			&temp_dir.path().join("main"),
+			&[("git", bin), ("curl", &plain), ("7zip", &autoupdate)],
+		);
+		// Only 64-bit downloads are available for vim.
+		let x86_64 = r#"{"version": "1.0", "homepage": "https://example.test", "license": "MIT", "architecture": {"64bit": {"url": "https://example.test/app.zip"}}}"#;
+
+		test::create_bucket(
+			&temp_dir.path().join("extras"),
+			&[("git", &plain), ("vim", x86_64)],
+		);
+
+		let buckets = Buckets::new(temp_dir.path());
+
+		let mut found: Vec<_> = buckets
+			.par_search_all(criteria)
+			.unwrap()
+			.into_iter()
+			.map(|(bucket, item)| format!("{}/{}", bucket.name(), item.name))
+			.collect();
+
+		// Buckets are searched in priority order, but only which manifests are found matters here.
+		found.sort();
+
+		// The index must give the same results as parsing every manifest.
+		let mut summarized: Vec<_> = buckets
+			.summaries(criteria)
+			.unwrap()
+			.into_iter()
+			.map(|(bucket, item)| format!("{}/{}", bucket.name(), item.name))
+			.collect();
+		summarized.sort();
+		assert_eq!(found, summarized);
+
+		found
+	}
+
+	#[test]
+	fn combinators() {
+		let git = Pattern::new(regex::Regex::new("^git$").unwrap());
+		let main = BucketList::allow(["main"]);
+
+		assert_eq!(search(&git.clone().and(main.clone())), ["main/git"]);
+		assert_eq!(
+			search(&git.clone().or(main.clone())),
+			["extras/git", "main/7zip", "main/curl", "main/git"]
+		);
+		assert_eq!(
+			search(&git.clone().not()),
+			["extras/vim", "main/7zip", "main/curl"]
+		);
+
+		// Negating a conjunction is not the same as negating each stage.
+		assert_eq!(
+			search(&git.and(main).not()),
+			["extras/git", "extras/vim", "main/7zip", "main/curl"]
+		);
+		assert_eq!(
+			search(&BucketList::deny(["main"])),
+			["extras/git", "extras/vim"]
+		);
+	}
+
+	#[test]
+	fn fields() {
+		assert_eq!(search(&Field::HasBin), ["main/git"]);
+		assert_eq!(search(&Field::HasAutoupdate), ["main/7zip"]);
+		assert_eq!(
+			search(&Field::License("gpl-2.0-only".to_owned())),
+			["main/git"]
+		);
+		assert_eq!(search(&Field::Arch(Arch::X86_64)).len(), 5);
+		assert_eq!(search(&Field::Arch(Arch::X86)).len(), 4);
+	}
 }
@@ -13,10 +13,59 @@ pub enum Error {
 	#[error("Bucket already exists")]
 	Exists,
 
+	/// A bucket name is not a single directory name, i.e., it contains a path separator or is `..`.
+	#[error("Invalid bucket name {0}")]
+	InvalidName(String),
+
+	/// Installed apps came from a bucket that is being removed.
+	#[error("Bucket is used by installed apps: {}", .0.join(", "))]
+	InUse(Vec<String>),
+
+	/// A bucket directory is neither a Git repository nor a directory added as a bucket, i.e., a clone that did not finish.
+	#[error("Bucket is not a Git repository or an added directory")]
+	NotBucket,
+
+	/// A Git operation was attempted on a bucket that is a plain directory.
+	#[error("Bucket is a local directory, not a Git repository")]
+	NotGit,
+
 	/// A manifest does not exist.
 	#[error("Manifest not found")]
 	ManifestNotFound,
 
+	/// A manifest in a bucket's index failed to parse when it was indexed.
+	#[error("Manifest is invalid: {0}")]
+	InvalidManifest(String),
+
+	/// A version of a manifest is not in the bucket's history, and the manifest does not have `autoupdate`.
+	#[error("Version {version} of {name} not found")]
+	VersionNotFound { name: String, version: String },
+
+	/// An unqualified manifest name exists in more than one bucket, so the bucket must be specified.
+	#[error("Manifest is in more than one bucket, specify one of: {}", .0.join(", "))]
+	Ambiguous(Vec<String>),
+
+	/// A bucket has local commits that are not in its upstream or pin, so pulling would lose them.
+	/// The target is the upstream branch, i.e., `origin/master`, or the pin, i.e., `tag:v1.0`.
+	#[error("Bucket has local commits that are not in {target}")]
+	Diverged { target: String },
+
+	/// A pin is not in the form `<branch|tag|commit>:<name>`.
+	#[error("Invalid pin {0}, expected branch:<name>, tag:<name> or commit:<id>")]
+	InvalidPin(String),
+
+	/// A trusted key is not in the form `ssh:<public key>` or `gpg:<fingerprint>`.
+	#[error("Invalid key {0}, expected ssh:<public key> or gpg:<fingerprint>")]
+	InvalidKey(String),
+
+	/// A new commit in a bucket with trusted keys is not signed by one of them.
+	#[error("Commit {commit} is not trusted, as {reason}")]
+	UntrustedCommit { commit: String, reason: String },
+
+	/// The history needed is older than the commits in a shallow clone.
+	#[error("History is not available in a shallow clone, unshallow the bucket first")]
+	ShallowHistory,
+
 	/// An IO error occurred.
 	#[error(transparent)]
 	Io(#[from] io::Error),
@@ -0,0 +1,169 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::bucket::changes;
+use crate::bucket::clone;
+use crate::bucket::Commit;
+use crate::bucket::Error;
+use crate::bucket::Result;
+
+/// Returns the ID of the blob at a path in a commit's tree, if any.
+fn blob_id(commit: &git2::Commit, path: &Path) -> Result<Option<git2::Oid>> {
+	let tree = commit.tree()?;
+
+	Ok(tree.get_path(path).ok().map(|entry| entry.id()))
+}
+
+/// A commit that changed a manifest. See [`Bucket::manifest_history`].
+///
+/// [`Bucket::manifest_history`]: crate::bucket::Bucket::manifest_history
+#[derive(Clone, Debug)]
+pub struct Revision {
+	/// The commit that changed the manifest.
+	pub commit: Commit,
+
+	/// The manifest's version after the commit.
+	/// This is None if the commit removed the manifest, or the version cannot be read.
+	pub version: Option<String>,
+}
+
+/// An iterator over the commits from HEAD that changed a file, newest first. Created by [`History::new`].
+///
+/// Instead of diffing trees, only the file's blob IDs are compared, so each commit costs a few tree lookups.
+/// A commit changed the file if its blob differs from the blob in every parent,
+/// so merges that took the file from one side are skipped, like `git log <path>`.
+///
+/// Yields a two-tuple (commit, blob) where blob is None if the commit removed the file.
+/// In a shallow clone, [`Error::ShallowHistory`] is yielded if the file's history goes past the shallow commits.
+///
+/// [`Error::ShallowHistory`]: crate::bucket::Error::ShallowHistory
+pub(crate) struct History<'r> {
+	revwalk: git2::Revwalk<'r>,
+	repo: &'r git2::Repository,
+	path: PathBuf,
+	shallow: HashSet<git2::Oid>,
+}
+
+impl<'r> History<'r> {
+	pub(crate) fn new(repo: &'r git2::Repository, path: PathBuf) -> Result<Self> {
+		let mut revwalk = repo.revwalk()?;
+		revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+		revwalk.push_head()?;
+
+		Ok(Self {
+			revwalk,
+			repo,
+			path,
+			shallow: clone::shallow_roots(repo)?,
+		})
+	}
+
+	fn changed(&self, oid: git2::Oid) -> Result<Option<(git2::Commit<'r>, Option<git2::Oid>)>> {
+		let commit = self.repo.find_commit(oid)?;
+		let blob = blob_id(&commit, &self.path)?;
+
+		let mut parents = commit.parents().peekable();
+
+		// A root commit changed the file if it added it.
+		// The parents of a shallow commit are unknown, so whether it changed the file is too.
+		if parents.peek().is_none() {
+			if blob.is_some() && self.shallow.contains(&oid) {
+				return Err(Error::ShallowHistory);
+			}
+
+			return Ok(blob.is_some().then_some((commit, blob)));
+		}
+
+		for parent in parents {
+			if blob_id(&parent, &self.path)? == blob {
+				return Ok(None);
+			}
+		}
+
+		Ok(Some((commit, blob)))
+	}
+
+	/// Returns the revisions of a manifest at `path` from its history.
+	pub(crate) fn revisions(self) -> Result<Vec<Revision>> {
+		let repo = self.repo;
+
+		self.map(|res| {
+			let (commit, blob) = res?;
+
+			Ok(Revision {
+				commit: Commit::from(&commit),
+				version: blob.and_then(|id| changes::blob_version(repo, id)),
+			})
+		})
+		.collect()
+	}
+}
+
+impl<'r> Iterator for History<'r> {
+	type Item = Result<(git2::Commit<'r>, Option<git2::Oid>)>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let oid = match self.revwalk.next()? {
+				Ok(oid) => oid,
+				Err(err) => return Some(Err(err.into())),
+			};
+
+			// Skip commits that did not change the file.
+			if let Some(item) = self.changed(oid).transpose() {
+				return Some(item);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use crate::bucket::Bucket;
+	use crate::bucket::Layout;
+	use crate::test;
+
+	#[test]
+	fn history() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let v1 = test::manifest_json("1.0", "");
+		let v2 = test::manifest_json("2.0", "");
+
+		let repo = test::create_bucket(temp_dir.path(), &[("app", &v1)]);
+		let added = repo.head().unwrap().target().unwrap();
+		let other = test::commit_manifests(&repo, &[("other", &v1)], "Add other");
+		let updated = test::commit_manifests(&repo, &[("app", &v2)], "Update app");
+
+		let bucket = Bucket::open(temp_dir.path()).unwrap();
+
+		assert_eq!(bucket.manifest_commit("app").unwrap().unwrap().id, updated);
+		assert_eq!(bucket.manifest_commit("other").unwrap().unwrap().id, other);
+
+		// Remove the manifest.
+		let mut index = repo.index().unwrap();
+		fs::remove_file(temp_dir.path().join("bucket").join("app.json")).unwrap();
+		index
+			.remove_path(&Layout::Standard.tree_path("app"))
+			.unwrap();
+		let removed = test::commit_index(&repo, &mut index, "Remove app");
+
+		let history: Vec<_> = bucket
+			.manifest_history("app")
+			.unwrap()
+			.into_iter()
+			.map(|revision| (revision.commit.id, revision.version))
+			.collect();
+
+		assert_eq!(
+			history,
+			[
+				(removed, None),
+				(updated, Some("2.0".to_owned())),
+				(added, Some("1.0".to_owned())),
+			]
+		);
+	}
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::bucket::Result;
+use crate::json;
+use crate::manifest::Bin;
+use crate::manifest::Bins;
+use crate::manifest::Manifest;
+use crate::util;
+
+/// The file name of the index.
+/// It is stored in the bucket's Git directory, so it is never part of the working tree.
+pub(crate) const INDEX_FILE: &str = "shovel-index.json";
+
+/// Returns the command name of an executable, i.e., its file name without the directory or extension.
+fn command_name(path: &str) -> String {
+	// Manifests are written for Windows, so both kinds of separators are handled.
+	let file = path.rsplit(['/', '\\']).next().unwrap_or(path);
+
+	match file.rsplit_once('.') {
+		Some((stem, _)) if !stem.is_empty() => stem.to_owned(),
+		_ => file.to_owned(),
+	}
+}
+
+json::json_struct! {
+	/// A summary of a manifest, containing the fields needed to search for and list apps.
+	pub struct Summary {
+		/// The app's version.
+		pub version: String,
+
+		/// The app's description.
+		pub description: Option<String>,
+
+		/// The names of the commands the app adds, i.e., `rg` for `rg.exe`.
+		pub bins: Vec<String>,
+
+		/// The app's homepage.
+		pub homepage: String,
+
+		/// The app's license.
+		pub license: String,
+	}
+}
+
+impl Summary {
+	/// Creates a new summary of a manifest.
+	#[must_use]
+	pub fn new(manifest: &Manifest) -> Self {
+		let bins = match manifest.bin(manifest.compatible()) {
+			Some(Bins::One(path)) => vec![command_name(path)],
+			Some(Bins::Many(bins)) => bins
+				.iter()
+				.map(|bin| match bin {
+					Bin::Path(path) => command_name(path),
+					Bin::Shim(shim) => shim.name.clone(),
+				})
+				.collect(),
+			None => vec![],
+		};
+
+		Self {
+			version: manifest.version.clone(),
+			description: manifest.description.clone(),
+			bins,
+			homepage: manifest.homepage.clone(),
+			license: manifest.license.to_string(),
+		}
+	}
+}
+
+json::json_struct! {
+	/// An index of the manifests in a bucket at a specific commit.
+	///
+	/// Manifests that cannot be parsed are not summarized, but their errors are recorded so they can be reported.
+	pub struct Index {
+		/// The ID of the commit the index was built from.
+		pub commit: String,
+
+		/// A map of manifest names to their summaries, sorted by name.
+		pub manifests: BTreeMap<String, Summary>,
+
+		/// A map of manifest names to the errors that occurred when parsing them, sorted by name.
+		#[serde(default)]
+		pub failures: BTreeMap<String, String>,
+	}
+}
+
+impl Index {
+	/// Loads an index from a file.
+	/// If the index does not exist or is invalid, None is returned so it can be rebuilt.
+	pub(crate) fn load(path: &Path) -> Option<Self> {
+		let file = fs::File::open(path).ok()?;
+
+		json::from_reader(file).ok()
+	}
+
+	/// Saves the index to a file. The file is replaced atomically, so an interrupted save never leaves it truncated.
+	pub(crate) fn save(&self, path: &Path) -> Result<()> {
+		util::write_atomic(path, json::to_string(self)?.as_bytes())?;
+
+		Ok(())
+	}
+
+	/// Parses and indexes a manifest from a Git blob. See [`insert`].
+	///
+	/// [`insert`]: Index::insert
+	pub(crate) fn insert_blob(&mut self, name: String, blob: &git2::Blob) {
+		self.insert(name, blob.content());
+	}
+
+	/// Parses and indexes a manifest from its JSON content.
+	/// If the manifest cannot be parsed, any existing summary is replaced by the error.
+	pub(crate) fn insert(&mut self, name: String, content: &[u8]) {
+		match json::from_reader::<_, Manifest>(content) {
+			Ok(manifest) => {
+				self.failures.remove(&name);
+				self.manifests.insert(name, Summary::new(&manifest));
+			}
+			Err(err) => {
+				self.manifests.remove(&name);
+				self.failures.insert(name, err.to_string());
+			}
+		}
+	}
+
+	/// Removes a manifest from the index, whether it was parsed or not.
+	pub(crate) fn remove(&mut self, name: &str) {
+		self.manifests.remove(name);
+		self.failures.remove(name);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test;
+
+	#[test]
+	fn command_names() {
+		assert_eq!(command_name("rg.exe"), "rg");
+		assert_eq!(command_name(r"bin\tool.cmd"), "tool");
+		assert_eq!(command_name("lib/app"), "app");
+		assert_eq!(command_name(".hidden"), ".hidden");
+	}
+
+	#[test]
+	fn failures() {
+		let mut index = Index::default();
+
+		index.insert("app".to_owned(), b"{");
+		assert!(!index.manifests.contains_key("app"));
+		assert!(index.failures.contains_key("app"));
+
+		// Fixing the manifest clears the error.
+		let json = test::manifest_json("1.0", "");
+		index.insert("app".to_owned(), json.as_bytes());
+		assert_eq!(index.manifests["app"].version, "1.0");
+		assert!(index.failures.is_empty());
+
+		index.insert("app".to_owned(), b"{");
+		index.remove("app");
+		assert!(index.failures.is_empty());
+	}
+}
@@ -0,0 +1,226 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use indexmap::IndexMap;
+
+use crate::bucket::Result;
+use crate::json;
+use crate::Config;
+
+/// The built-in known buckets.
+/// Derived from <https://github.com/ScoopInstaller/Scoop/blob/master/buckets.json>
+static BUILT_IN: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/buckets.json"));
+
+json::json_enum! {
+	/// A known bucket, as written in a registry of known buckets.
+	/// Like Scoop's `buckets.json`, the URL may be given on its own.
+	#[derive(Clone)]
+	pub enum KnownEntry {
+		/// The bucket's URL.
+		Url(String),
+
+		/// The bucket's URL and a description.
+		Detailed {
+			url: String,
+			#[serde(default)]
+			description: Option<String>,
+		},
+	}
+}
+
+impl KnownEntry {
+	/// Returns the bucket's URL.
+	#[must_use]
+	pub fn url(&self) -> &str {
+		match self {
+			Self::Url(url) | Self::Detailed { url, .. } => url,
+		}
+	}
+
+	/// Returns the bucket's description, if any.
+	#[must_use]
+	pub fn description(&self) -> Option<&str> {
+		match self {
+			Self::Url(_) => None,
+			Self::Detailed { description, .. } => description.as_deref(),
+		}
+	}
+}
+
+/// Where a known bucket came from. See [`KnownBuckets::load`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Origin {
+	/// The list built into Shovel.
+	BuiltIn,
+
+	/// The file at [`Config::known_buckets_file`].
+	File(PathBuf),
+
+	/// [`Config::known_buckets`].
+	Config,
+}
+
+impl fmt::Display for Origin {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::BuiltIn => write!(f, "built-in"),
+			Self::File(path) => write!(f, "{}", path.display()),
+			Self::Config => write!(f, "config"),
+		}
+	}
+}
+
+/// A known bucket, which can be added by name alone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KnownBucket {
+	/// The bucket's URL.
+	pub url: String,
+
+	/// The bucket's description, if any.
+	pub description: Option<String>,
+
+	/// Where the bucket came from.
+	pub origin: Origin,
+}
+
+/// The registry of known buckets.
+pub struct KnownBuckets {
+	buckets: IndexMap<String, KnownBucket>,
+}
+
+impl KnownBuckets {
+	/// Returns the known buckets built into Shovel.
+	#[must_use]
+	#[allow(clippy::missing_panics_doc)]
+	pub fn built_in() -> Self {
+		let entries =
+			serde_json::from_str(BUILT_IN).expect("built-in known buckets should be valid");
+
+		let mut known = Self {
+			buckets: IndexMap::new(),
+		};
+		known.extend(entries, &Origin::BuiltIn);
+
+		known
+	}
+
+	/// Loads the known buckets, merging the built-in list with the entries from a config.
+	///
+	/// Entries from [`Config::known_buckets_file`] replace built-in entries of the same name,
+	/// and entries from [`Config::known_buckets`] replace both.
+	///
+	/// # Arguments
+	///
+	/// * `config` - The config with additional entries.
+	///
+	/// # Errors
+	///
+	/// If the known buckets file cannot be read, [`Error::Io`] is returned.
+	///
+	/// If the known buckets file is not a valid registry, [`Error::Json`] is returned.
+	///
+	/// [`Error::Io`]: crate::bucket::Error::Io
+	/// [`Error::Json`]: crate::bucket::Error::Json
+	pub fn load(config: &Config) -> Result<Self> {
+		let mut known = Self::built_in();
+
+		if let Some(path) = &config.known_buckets_file {
+			let path = Path::new(path);
+			let entries = json::from_reader(fs::File::open(path)?)?;
+
+			known.extend(entries, &Origin::File(path.to_owned()));
+		}
+
+		known.extend(config.known_buckets.clone(), &Origin::Config);
+
+		Ok(known)
+	}
+
+	/// Adds entries, replacing existing entries of the same name in place.
+	fn extend(&mut self, entries: IndexMap<String, KnownEntry>, origin: &Origin) {
+		for (name, entry) in entries {
+			let bucket = KnownBucket {
+				url: entry.url().to_owned(),
+				description: entry.description().map(str::to_owned),
+				origin: origin.clone(),
+			};
+
+			self.buckets.insert(name, bucket);
+		}
+	}
+
+	/// Returns a known bucket by name.
+	///
+	/// # Arguments
+	///
+	/// * `name` - The bucket's name.
+	#[must_use]
+	pub fn get(&self, name: &str) -> Option<&KnownBucket> {
+		self.buckets.get(name)
+	}
+
+	/// Returns an iterator over the names and known buckets, with the built-in buckets first.
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &KnownBucket)> {
+		self.buckets
+			.iter()
+			.map(|(name, bucket)| (name.as_str(), bucket))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn built_in() {
+		let known = KnownBuckets::built_in();
+		let main = known.get("main").unwrap();
+
+		assert_eq!(main.url, "https://github.com/ScoopInstaller/Main");
+		assert!(main.description.is_some());
+		assert_eq!(main.origin, Origin::BuiltIn);
+		assert_eq!(known.iter().next().unwrap().0, "main");
+	}
+
+	#[test]
+	fn load() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let file = temp_dir.path().join("buckets.json");
+
+		// Scoop's format, with URLs only, is also accepted.
+		fs::write(
+			&file,
+			r#"{
+				"main": "https://git.example.test/mirrors/main",
+				"tools": {"url": "https://git.example.test/tools", "description": "Internal tools"}
+			}"#,
+		)
+		.unwrap();
+
+		let config = Config {
+			known_buckets: IndexMap::from([(
+				"tools".to_owned(),
+				KnownEntry::Url("https://example.test/my-tools".to_owned()),
+			)]),
+			known_buckets_file: Some(file.to_string_lossy().into_owned()),
+			..Config::default()
+		};
+
+		let known = KnownBuckets::load(&config).unwrap();
+
+		let main = known.get("main").unwrap();
+		assert_eq!(main.url, "https://git.example.test/mirrors/main");
+		assert_eq!(main.description, None);
+		assert_eq!(main.origin, Origin::File(file));
+
+		let tools = known.get("tools").unwrap();
+		assert_eq!(tools.url, "https://example.test/my-tools");
+		assert_eq!(tools.origin, Origin::Config);
+
+		// Replaced entries keep their place.
+		assert_eq!(known.iter().next().unwrap().0, "main");
+		assert_eq!(known.get("extras").unwrap().origin, Origin::BuiltIn);
+	}
+}
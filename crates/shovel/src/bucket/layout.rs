@@ -0,0 +1,150 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The directory in a standard bucket where manifests are stored.
+const MANIFEST_DIR: &str = "bucket";
+
+/// The directory in a bucket where retired manifests are moved to, regardless of the layout.
+pub(crate) const DEPRECATED_DIR: &str = "deprecated";
+
+/// Where a bucket stores its manifests. See [`Bucket::layout`].
+///
+/// [`Bucket::layout`]: crate::bucket::Bucket::layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+	/// Manifests are in the `bucket` directory.
+	Standard,
+
+	/// Manifests are at the root of the repository.
+	/// Like Scoop, this is assumed for buckets without a `bucket` directory.
+	Root,
+}
+
+impl Layout {
+	/// Detects the layout of a bucket from its working tree.
+	///
+	/// # Arguments
+	///
+	/// * `dir` - The path to the bucket.
+	#[must_use]
+	pub fn detect(dir: &Path) -> Self {
+		if dir.join(MANIFEST_DIR).is_dir() {
+			Self::Standard
+		} else {
+			Self::Root
+		}
+	}
+
+	/// Returns the directory where manifests are stored, relative to the bucket.
+	#[must_use]
+	pub fn manifest_dir(self) -> &'static Path {
+		match self {
+			Self::Standard => Path::new(MANIFEST_DIR),
+			Self::Root => Path::new(""),
+		}
+	}
+
+	/// Returns the path to a manifest in the bucket's tree, i.e., `bucket/<name>.json`.
+	/// Tree paths always use `/`, regardless of the platform.
+	pub(crate) fn tree_path(self, name: &str) -> PathBuf {
+		match self {
+			Self::Standard => PathBuf::from(format!("{MANIFEST_DIR}/{name}.json")),
+			Self::Root => PathBuf::from(format!("{name}.json")),
+		}
+	}
+
+	/// Returns the pathspec that limits a diff to the manifest directory.
+	/// Manifests at the root cannot be selected by a pathspec, so None is returned for [`Layout::Root`].
+	pub(crate) fn pathspec(self) -> Option<&'static str> {
+		match self {
+			Self::Standard => Some(MANIFEST_DIR),
+			Self::Root => None,
+		}
+	}
+
+	/// Returns the name of a manifest if `path` is a manifest file in the bucket's tree.
+	pub(crate) fn manifest_name(self, path: &Path) -> Option<String> {
+		let parent = path.parent()?;
+
+		if parent != self.manifest_dir() {
+			return None;
+		}
+
+		let name = path.file_name()?.to_str()?.strip_suffix(".json")?;
+
+		Some(name.to_owned())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use super::*;
+	use crate::bucket::Bucket;
+	use crate::test;
+
+	#[test]
+	fn manifest_names() {
+		let standard = Layout::Standard;
+
+		assert_eq!(
+			standard.manifest_name(Path::new("bucket/app.json")),
+			Some("app".to_owned())
+		);
+		assert_eq!(standard.manifest_name(Path::new("bucket/app.txt")), None);
+		assert_eq!(standard.manifest_name(Path::new("scripts/app.json")), None);
+		assert_eq!(
+			standard.manifest_name(Path::new("bucket/nested/app.json")),
+			None
+		);
+
+		let root = Layout::Root;
+
+		assert_eq!(
+			root.manifest_name(Path::new("app.json")),
+			Some("app".to_owned())
+		);
+		assert_eq!(root.manifest_name(Path::new("bucket/app.json")), None);
+		assert_eq!(root.tree_path("app"), Path::new("app.json"));
+	}
+
+	#[test]
+	fn root() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let v1 = test::manifest_json("1.0", "");
+		let v2 = test::manifest_json("2.0", "");
+
+		let repo = git2::Repository::init(temp_dir.path()).unwrap();
+		let mut index = repo.index().unwrap();
+
+		for (name, json) in [("app", &v1), ("lib", &v1)] {
+			let path = format!("{name}.json");
+
+			fs::write(temp_dir.path().join(&path), json).unwrap();
+			index.add_path(Path::new(&path)).unwrap();
+		}
+
+		index.write().unwrap();
+		let from = test::commit_index(&repo, &mut index, "Initial commit");
+
+		fs::write(temp_dir.path().join("app.json"), &v2).unwrap();
+		index.add_path(Path::new("app.json")).unwrap();
+		index.write().unwrap();
+		let to = test::commit_index(&repo, &mut index, "Update app");
+
+		let bucket = Bucket::open(temp_dir.path()).unwrap();
+		assert_eq!(bucket.layout(), Layout::Root);
+
+		let names: Vec<_> = bucket.manifests().unwrap().map(|item| item.name).collect();
+		assert_eq!(names.len(), 2);
+
+		assert_eq!(bucket.manifest("app", false).unwrap().version, "2.0");
+		assert_eq!(bucket.manifest_commit("app").unwrap().unwrap().id, to);
+		assert_eq!(bucket.changes(from, to).unwrap().len(), 1);
+		assert_eq!(
+			bucket.index().unwrap().manifests.keys().collect::<Vec<_>>(),
+			["app", "lib"]
+		);
+	}
+}
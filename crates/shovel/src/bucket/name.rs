@@ -3,11 +3,12 @@ use crate::bucket::Criteria;
 
 /// A name for a manifest in a bucket.
 ///
-/// The syntax is `(bucket/)manifest`, where bucket is optional.
+/// The syntax is `(bucket/)manifest(@version)`, where bucket and version are optional.
 #[derive(Clone)]
 pub struct Name {
 	full: String,
 	sep: Option<usize>,
+	at: Option<usize>,
 }
 
 impl Name {
@@ -16,7 +17,14 @@ impl Name {
 	pub fn new(full: String) -> Self {
 		let sep = full.find('/');
 
-		Self { full, sep }
+		// The version is only looked for after the bucket, and must not be empty.
+		let start = sep.map_or(0, |sep| sep + 1);
+		let at = full[start..]
+			.rfind('@')
+			.map(|at| start + at)
+			.filter(|&at| at + 1 < full.len());
+
+		Self { full, sep, at }
 	}
 
 	/// Returns the full name.
@@ -25,18 +33,28 @@ impl Name {
 		&self.full
 	}
 
-	/// Returns the manifest for this name.
+	/// Returns the manifest for this name, without the version.
 	#[must_use]
 	pub fn manifest(&self) -> &str {
+		let end = self.at.unwrap_or(self.full.len());
+
 		match self.sep {
 			Some(sep) => {
 				// sep is the start of the slash, so advance by 1.
-				&self.full[sep + 1..]
+				&self.full[sep + 1..end]
 			}
-			None => &self.full,
+			None => &self.full[..end],
 		}
 	}
 
+	/// Returns the version for this name, i.e., `1.2.3` for `app@1.2.3`.
+	///
+	/// If the version is not present, None is returned.
+	#[must_use]
+	pub fn version(&self) -> Option<&str> {
+		self.at.map(|at| &self.full[at + 1..])
+	}
+
 	/// Returns the bucket for this name.
 	///
 	/// If the bucket is not present, None is returned.
@@ -65,3 +83,32 @@ impl Criteria for Name {
 		manifest == self.manifest()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parts() {
+		let parts = |name: &str| {
+			let name = Name::new(name.to_owned());
+
+			(
+				name.bucket().map(str::to_owned),
+				name.manifest().to_owned(),
+				name.version().map(str::to_owned),
+			)
+		};
+
+		let some = |s: &str| Some(s.to_owned());
+
+		assert_eq!(parts("app"), (None, "app".to_owned(), None));
+		assert_eq!(parts("main/app"), (some("main"), "app".to_owned(), None));
+		assert_eq!(parts("app@1.2.3"), (None, "app".to_owned(), some("1.2.3")));
+		assert_eq!(
+			parts("main/app@1.0-beta"),
+			(some("main"), "app".to_owned(), some("1.0-beta"))
+		);
+		assert_eq!(parts("app@"), (None, "app@".to_owned(), None));
+	}
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::bucket::Error;
+use crate::bucket::Result;
+
+/// The key in a bucket's Git config where its pin is stored.
+const PIN_KEY: &str = "shovel.pin";
+
+/// What a bucket is pinned to. See [`Bucket::pin`].
+///
+/// Pins are written as `<kind>:<name>`, i.e., `tag:v1.2.0`, which is also how they are stored.
+///
+/// [`Bucket::pin`]: crate::bucket::Bucket::pin
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Pin {
+	/// Follow a branch in the upstream remote, instead of the upstream branch.
+	Branch(String),
+
+	/// Follow a tag in the upstream remote. If the tag is moved, the bucket moves with it.
+	Tag(String),
+
+	/// Stay at a commit. The ID may be abbreviated.
+	Commit(String),
+}
+
+impl fmt::Display for Pin {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Branch(name) => write!(f, "branch:{name}"),
+			Self::Tag(name) => write!(f, "tag:{name}"),
+			Self::Commit(id) => write!(f, "commit:{id}"),
+		}
+	}
+}
+
+impl FromStr for Pin {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let pin = match s.split_once(':') {
+			Some(("branch", name)) if !name.is_empty() => Self::Branch(name.to_owned()),
+			Some(("tag", name)) if !name.is_empty() => Self::Tag(name.to_owned()),
+			Some(("commit", id)) if git2::Oid::from_str(id).is_ok() => Self::Commit(id.to_owned()),
+			_ => return Err(Error::InvalidPin(s.to_owned())),
+		};
+
+		Ok(pin)
+	}
+}
+
+/// Returns the pin of a bucket's repository, if any.
+/// Only the repository's own config is read, as that is where [`set`] writes it.
+pub(crate) fn get(repo: &git2::Repository) -> Result<Option<Pin>> {
+	let config = repo.config()?.open_level(git2::ConfigLevel::Local)?;
+
+	match config.get_string(PIN_KEY) {
+		Ok(pin) => Ok(Some(pin.parse()?)),
+		Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+		Err(err) => Err(err.into()),
+	}
+}
+
+/// Sets or removes the pin of a bucket's repository.
+pub(crate) fn set(repo: &git2::Repository, pin: Option<&Pin>) -> Result<()> {
+	let mut config = repo.config()?.open_level(git2::ConfigLevel::Local)?;
+
+	match pin {
+		Some(pin) => config.set_str(PIN_KEY, &pin.to_string())?,
+		None => match config.remove(PIN_KEY) {
+			Err(err) if err.code() != git2::ErrorCode::NotFound => return Err(err.into()),
+			_ => {}
+		},
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse() {
+		assert_eq!(
+			"tag:v1.2.0".parse::<Pin>().unwrap(),
+			Pin::Tag("v1.2.0".to_owned())
+		);
+		assert_eq!(
+			"branch:release/2024".parse::<Pin>().unwrap(),
+			Pin::Branch("release/2024".to_owned())
+		);
+		assert_eq!(
+			"commit:1a2b3c4".parse::<Pin>().unwrap().to_string(),
+			"commit:1a2b3c4"
+		);
+
+		for invalid in ["v1.2.0", "tag:", "commit:main", "remote:origin"] {
+			assert!(matches!(invalid.parse::<Pin>(), Err(Error::InvalidPin(_))));
+		}
+	}
+}
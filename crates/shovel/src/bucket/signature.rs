@@ -0,0 +1,671 @@
+use std::fmt;
+use std::io::prelude::*;
+use std::process;
+use std::str::FromStr;
+
+use crate::bucket::Error;
+use crate::bucket::Result;
+
+/// The key in a bucket's Git config where trusted keys are stored, with one value per key.
+const TRUSTED_KEY: &str = "shovel.trustedKey";
+
+/// The namespace Git signs commits in. SSH signatures are only valid in the namespace they were made for.
+const SSH_NAMESPACE: &str = "git";
+
+/// The principal trusted SSH keys are listed under, as the allowed signers file requires one.
+const SSH_PRINCIPAL: &str = "shovel";
+
+const SSH_HEADER: &str = "-----BEGIN SSH SIGNATURE-----";
+const GPG_HEADER: &str = "-----BEGIN PGP SIGNATURE-----";
+
+/// A key that commits to a bucket may be signed with. See [`Bucket::trusted_keys`].
+///
+/// Keys are written as `<kind>:<key>`, i.e., `gpg:<fingerprint>` or `ssh:ssh-ed25519 AAAA...`,
+/// which is also how they are stored.
+///
+/// [`Bucket::trusted_keys`]: crate::bucket::Bucket::trusted_keys
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrustedKey {
+	/// An SSH public key in OpenSSH format, i.e., `ssh-ed25519 AAAA...`. The comment is dropped.
+	Ssh(String),
+
+	/// The fingerprint of a GPG key, in upper-case hex. The public key must be in the user's keyring.
+	Gpg(String),
+}
+
+impl fmt::Display for TrustedKey {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Ssh(key) => write!(f, "ssh:{key}"),
+			Self::Gpg(fingerprint) => write!(f, "gpg:{fingerprint}"),
+		}
+	}
+}
+
+impl FromStr for TrustedKey {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let invalid = || Error::InvalidKey(s.to_owned());
+
+		match s.split_once(':') {
+			Some(("ssh", key)) => {
+				let mut parts = key.split_whitespace();
+
+				let (Some(kind), Some(data)) = (parts.next(), parts.next()) else {
+					return Err(invalid());
+				};
+
+				Ok(Self::Ssh(format!("{kind} {data}")))
+			}
+			Some(("gpg", fingerprint)) => {
+				// Fingerprints are often shown in groups of four.
+				let fingerprint: String =
+					fingerprint.chars().filter(|c| !c.is_whitespace()).collect();
+
+				// v4 fingerprints are 40 characters long, and v5 fingerprints are 64.
+				if ![40, 64].contains(&fingerprint.len())
+					|| !fingerprint.chars().all(|c| c.is_ascii_hexdigit())
+				{
+					return Err(invalid());
+				}
+
+				Ok(Self::Gpg(fingerprint.to_ascii_uppercase()))
+			}
+			_ => Err(invalid()),
+		}
+	}
+}
+
+/// Returns the keys trusted to sign commits to a bucket's repository.
+/// Only the repository's own config is read, as that is where [`set_trusted_keys`] writes them.
+pub(crate) fn trusted_keys(repo: &git2::Repository) -> Result<Vec<TrustedKey>> {
+	let config = repo.config()?.open_level(git2::ConfigLevel::Local)?;
+	let mut entries = config.multivar(TRUSTED_KEY, None)?;
+
+	let mut keys = vec![];
+
+	while let Some(entry) = entries.next() {
+		if let Some(value) = entry?.value() {
+			keys.push(value.parse()?);
+		}
+	}
+
+	Ok(keys)
+}
+
+/// Replaces the keys trusted to sign commits to a bucket's repository.
+pub(crate) fn set_trusted_keys(repo: &git2::Repository, keys: &[TrustedKey]) -> Result<()> {
+	let mut config = repo.config()?.open_level(git2::ConfigLevel::Local)?;
+
+	match config.remove_multivar(TRUSTED_KEY, ".*") {
+		Err(err) if err.code() != git2::ErrorCode::NotFound => return Err(err.into()),
+		_ => {}
+	}
+
+	for key in keys {
+		// A pattern that matches no existing value adds a new value.
+		config.set_multivar(TRUSTED_KEY, "^$", &key.to_string())?;
+	}
+
+	Ok(())
+}
+
+/// Checks that every commit reachable from `to`, but not from `from`, is signed by a trusted key.
+/// If no keys are trusted, verification is disabled, and every commit is accepted.
+///
+/// When `to` is reachable from `from`, i.e., a pin moves the bucket back, `to` itself is checked,
+/// as it may have been accepted before any keys were trusted.
+///
+/// If a commit is not signed by a trusted key, [`Error::UntrustedCommit`] is returned for the newest such commit.
+pub(crate) fn verify(repo: &git2::Repository, from: git2::Oid, to: git2::Oid) -> Result<()> {
+	let keys = trusted_keys(repo)?;
+
+	if keys.is_empty() {
+		return Ok(());
+	}
+
+	let verifier = Verifier::new(repo, &keys)?;
+
+	let mut revwalk = repo.revwalk()?;
+	revwalk.push(to)?;
+	revwalk.hide(from)?;
+
+	let mut verified_to = false;
+
+	for id in revwalk {
+		let id = id?;
+		verifier.verify(id)?;
+
+		verified_to |= id == to;
+	}
+
+	if !verified_to && to != from {
+		verifier.verify(to)?;
+	}
+
+	Ok(())
+}
+
+/// Verifies signatures with the programs Git uses, i.e., `ssh-keygen` and `gpg`.
+/// Like Git, the `gpg.ssh.program` and `gpg.program` config values are respected.
+struct Verifier<'r> {
+	repo: &'r git2::Repository,
+	ssh_program: String,
+	gpg_program: String,
+	allowed_signers: Option<tempfile::NamedTempFile>,
+	fingerprints: Vec<String>,
+}
+
+impl<'r> Verifier<'r> {
+	fn new(repo: &'r git2::Repository, keys: &[TrustedKey]) -> Result<Self> {
+		let config = repo.config()?;
+		let program = |key: &str, default: &str| {
+			config
+				.get_string(key)
+				.unwrap_or_else(|_| default.to_owned())
+		};
+
+		let mut allowed_signers = None;
+		let mut fingerprints = vec![];
+
+		for key in keys {
+			match key {
+				TrustedKey::Ssh(key) => {
+					let file = match &mut allowed_signers {
+						Some(file) => file,
+						None => allowed_signers.insert(tempfile::NamedTempFile::new()?),
+					};
+
+					writeln!(file, "{SSH_PRINCIPAL} namespaces=\"{SSH_NAMESPACE}\" {key}")?;
+				}
+				TrustedKey::Gpg(fingerprint) => fingerprints.push(fingerprint.clone()),
+			}
+		}
+
+		Ok(Self {
+			repo,
+			ssh_program: program("gpg.ssh.program", "ssh-keygen"),
+			gpg_program: program("gpg.program", "gpg"),
+			allowed_signers,
+			fingerprints,
+		})
+	}
+
+	fn verify(&self, id: git2::Oid) -> Result<()> {
+		let untrusted = |reason: String| Error::UntrustedCommit {
+			commit: id.to_string(),
+			reason,
+		};
+
+		let (signature, data) = match self.repo.extract_signature(&id, None) {
+			Ok(signed) => signed,
+			Err(err) if err.code() == git2::ErrorCode::NotFound => {
+				return Err(untrusted("it is not signed".to_owned()));
+			}
+			Err(err) => return Err(err.into()),
+		};
+
+		let signature = signature.as_str().unwrap_or_default();
+
+		let result = if signature.starts_with(SSH_HEADER) {
+			self.verify_ssh(signature, &data)
+		} else if signature.starts_with(GPG_HEADER) {
+			self.verify_gpg(signature, &data)
+		} else {
+			return Err(untrusted(
+				"its signature is not an SSH or GPG signature".to_owned(),
+			));
+		};
+
+		match result {
+			Ok(true) => Ok(()),
+			Ok(false) => Err(untrusted("it is not signed by a trusted key".to_owned())),
+			Err(err) => Err(untrusted(format!(
+				"its signature cannot be verified: {err}"
+			))),
+		}
+	}
+
+	fn verify_ssh(&self, signature: &str, data: &[u8]) -> std::io::Result<bool> {
+		let Some(allowed_signers) = &self.allowed_signers else {
+			return Ok(false);
+		};
+
+		let signature_file = signature_file(signature)?;
+
+		let mut cmd = process::Command::new(&self.ssh_program);
+		cmd.args(["-Y", "verify", "-I", SSH_PRINCIPAL, "-n", SSH_NAMESPACE])
+			.arg("-f")
+			.arg(allowed_signers.path())
+			.arg("-s")
+			.arg(signature_file.path());
+
+		let output = run(cmd, data)?;
+
+		Ok(output.status.success())
+	}
+
+	fn verify_gpg(&self, signature: &str, data: &[u8]) -> std::io::Result<bool> {
+		if self.fingerprints.is_empty() {
+			return Ok(false);
+		}
+
+		let signature_file = signature_file(signature)?;
+
+		let mut cmd = process::Command::new(&self.gpg_program);
+		cmd.args(["--status-fd=1", "--verify"])
+			.arg(signature_file.path())
+			.arg("-");
+
+		let output = run(cmd, data)?;
+		let status = String::from_utf8_lossy(&output.stdout);
+
+		// See `doc/DETAILS` in GnuPG. `VALIDSIG` is also printed for signatures by expired or revoked keys,
+		// so `GOODSIG` is required as well, and any status that taints the signature rejects it.
+		let mut good = false;
+		let mut valid = false;
+
+		for line in status.lines() {
+			let fields: Vec<_> = line.split_whitespace().collect();
+
+			match fields.get(..2) {
+				Some(["[GNUPG:]", "GOODSIG"]) => good = true,
+				// `VALIDSIG <fingerprint> ... <primary key fingerprint>`.
+				// Either the signing subkey or its primary key may be trusted.
+				Some(["[GNUPG:]", "VALIDSIG"]) => {
+					valid |= fields[2..]
+						.iter()
+						.any(|field| self.fingerprints.iter().any(|f| f == field));
+				}
+				Some(["[GNUPG:]", "BADSIG" | "EXPSIG" | "EXPKEYSIG" | "REVKEYSIG" | "ERRSIG"]) => {
+					return Ok(false);
+				}
+				_ => {}
+			}
+		}
+
+		Ok(output.status.success() && good && valid)
+	}
+}
+
+/// Writes a signature to a temporary file, as the verifying programs read the signed data from stdin.
+fn signature_file(signature: &str) -> std::io::Result<tempfile::NamedTempFile> {
+	let mut file = tempfile::NamedTempFile::new()?;
+	file.write_all(signature.as_bytes())?;
+
+	Ok(file)
+}
+
+/// Runs a program with data on stdin, and returns its output.
+fn run(mut cmd: process::Command, data: &[u8]) -> std::io::Result<process::Output> {
+	cmd.stdin(process::Stdio::piped());
+	cmd.stdout(process::Stdio::piped());
+	cmd.stderr(process::Stdio::piped());
+
+	let mut child = cmd.spawn()?;
+
+	if let Some(mut stdin) = child.stdin.take() {
+		stdin.write_all(data)?;
+	}
+
+	child.wait_with_output()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::path::Path;
+
+	use super::*;
+	use crate::bucket::Bucket;
+	use crate::bucket::CloneOptions;
+	use crate::bucket::Pin;
+	use crate::bucket::Pull;
+	use crate::test;
+	use crate::util;
+
+	#[test]
+	fn parse() {
+		assert_eq!(
+			"ssh:ssh-ed25519 AAAAC3Nza me@example.test"
+				.parse::<TrustedKey>()
+				.unwrap(),
+			TrustedKey::Ssh("ssh-ed25519 AAAAC3Nza".to_owned())
+		);
+		assert_eq!(
+			"gpg:0123 4567 89ab cdef 0123 4567 89ab cdef 0123 4567"
+				.parse::<TrustedKey>()
+				.unwrap()
+				.to_string(),
+			"gpg:0123456789ABCDEF0123456789ABCDEF01234567"
+		);
+
+		for invalid in [
+			"ssh:ssh-ed25519",
+			"gpg:0123",
+			"gpg:not a fingerprint",
+			"AAAAC3Nza",
+		] {
+			assert!(matches!(
+				invalid.parse::<TrustedKey>(),
+				Err(Error::InvalidKey(_))
+			));
+		}
+	}
+
+	/// Generates an SSH key pair, and returns the path to the private key and the trusted public key.
+	fn ssh_key(dir: &Path, name: &str) -> (std::path::PathBuf, TrustedKey) {
+		let path = dir.join(name);
+
+		let status = process::Command::new("ssh-keygen")
+			.args(["-q", "-t", "ed25519", "-N", "", "-f"])
+			.arg(&path)
+			.status()
+			.unwrap();
+		assert!(status.success());
+
+		let public = fs::read_to_string(path.with_extension("pub")).unwrap();
+
+		(path, format!("ssh:{public}").parse().unwrap())
+	}
+
+	/// Signs a file with an SSH key, and returns the signature.
+	fn ssh_sign(key: &Path, path: &Path) -> String {
+		let status = process::Command::new("ssh-keygen")
+			.args(["-q", "-Y", "sign", "-n", SSH_NAMESPACE, "-f"])
+			.arg(key)
+			.arg(path)
+			.status()
+			.unwrap();
+		assert!(status.success());
+
+		// ssh-keygen asks before overwriting a signature, so each one is removed once read.
+		let signature_path = path.with_extension("sig");
+		let signature = fs::read_to_string(&signature_path).unwrap();
+		fs::remove_file(signature_path).unwrap();
+
+		signature
+	}
+
+	/// Commits a manifest to the HEAD branch, signed by `sign`, which is given the path to the commit's data.
+	fn commit_signed(
+		repo: &git2::Repository,
+		sign: impl FnOnce(&Path) -> String,
+		json: &str,
+	) -> git2::Oid {
+		let dir = repo.workdir().unwrap();
+		fs::write(dir.join("bucket").join("app.json"), json).unwrap();
+
+		let mut index = repo.index().unwrap();
+		index.add_path(Path::new("bucket/app.json")).unwrap();
+		index.write().unwrap();
+
+		let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+		let parent = repo.head().unwrap().peel_to_commit().unwrap();
+		let sig = git2::Signature::now("Maintainer", "maintainer@example.test").unwrap();
+
+		let buffer = repo
+			.commit_create_buffer(&sig, &sig, "Update app", &tree, &[&parent])
+			.unwrap();
+		let data = buffer.as_str().unwrap();
+
+		let data_path = repo.path().join("commit");
+		fs::write(&data_path, data).unwrap();
+
+		let signature = sign(&data_path);
+		let id = repo.commit_signed(data, &signature, None).unwrap();
+
+		repo.head().unwrap().set_target(id, "Update app").unwrap();
+
+		id
+	}
+
+	/// A GnuPG home directory, used through a wrapper script set as `gpg.program`,
+	/// so that tests do not touch the user's keyring.
+	#[cfg(unix)]
+	struct Gpg {
+		home: std::path::PathBuf,
+		program: std::path::PathBuf,
+	}
+
+	#[cfg(unix)]
+	impl Gpg {
+		fn new(dir: &Path) -> Self {
+			use std::os::unix::fs::PermissionsExt;
+
+			let home = dir.join("gnupg");
+			fs::create_dir(&home).unwrap();
+			fs::set_permissions(&home, fs::Permissions::from_mode(0o700)).unwrap();
+
+			let program = dir.join("gpg.sh");
+			let script = format!(
+				"#!/bin/sh\nexec gpg --homedir '{}' \"$@\"\n",
+				home.display()
+			);
+			fs::write(&program, script).unwrap();
+			fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+
+			Self { home, program }
+		}
+
+		/// Returns a non-interactive command, optionally running at a faked time, i.e., `20200101T000000`.
+		fn command(&self, time: Option<&str>) -> process::Command {
+			let mut cmd = process::Command::new(&self.program);
+			cmd.args(["--batch", "--pinentry-mode", "loopback", "--passphrase", ""]);
+
+			if let Some(time) = time {
+				cmd.args(["--faked-system-time", time]);
+			}
+
+			cmd.stdout(process::Stdio::piped())
+				.stderr(process::Stdio::null());
+			cmd
+		}
+
+		/// Generates a key for `user` that expires after `expire`, i.e., `1d` or `never`,
+		/// and returns it as a trusted key.
+		fn key(&self, user: &str, expire: &str, time: Option<&str>) -> TrustedKey {
+			let status = self
+				.command(time)
+				.args(["--quick-gen-key", user, "default", "default", expire])
+				.status()
+				.unwrap();
+			assert!(status.success());
+
+			let output = self
+				.command(None)
+				.args(["--with-colons", "--list-keys", user])
+				.output()
+				.unwrap();
+			let keys = String::from_utf8(output.stdout).unwrap();
+
+			// The first fingerprint is the primary key's.
+			let fingerprint = keys
+				.lines()
+				.find_map(|line| line.strip_prefix("fpr:"))
+				.and_then(|fields| fields.split(':').find(|field| !field.is_empty()))
+				.unwrap();
+
+			format!("gpg:{fingerprint}").parse().unwrap()
+		}
+
+		/// Signs a file with the key of `user`, and returns the signature.
+		fn sign(&self, user: &str, time: Option<&str>, path: &Path) -> String {
+			let output = self
+				.command(time)
+				.args([
+					"--armor",
+					"--detach-sign",
+					"--local-user",
+					user,
+					"--output",
+					"-",
+				])
+				.arg(path)
+				.output()
+				.unwrap();
+			assert!(output.status.success());
+
+			String::from_utf8(output.stdout).unwrap()
+		}
+	}
+
+	#[cfg(unix)]
+	impl Drop for Gpg {
+		fn drop(&mut self) {
+			// The agent started by gpg would otherwise outlive the test.
+			let _ = process::Command::new("gpgconf")
+				.arg("--homedir")
+				.arg(&self.home)
+				.args(["--kill", "gpg-agent"])
+				.status();
+		}
+	}
+
+	#[test]
+	fn verify_ssh() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let upstream_dir = temp_dir.path().join("upstream");
+		let json = test::manifest_json("1.0", "");
+
+		let upstream = test::create_bucket(&upstream_dir, &[("app", &json)]);
+		let bucket = Bucket::clone(
+			&util::path_to_string(&upstream_dir),
+			temp_dir.path().join("bucket"),
+			CloneOptions::new(),
+		)
+		.unwrap();
+
+		let (maintainer, trusted) = ssh_key(temp_dir.path(), "maintainer");
+		let (stranger, _) = ssh_key(temp_dir.path(), "stranger");
+
+		bucket
+			.set_trusted_keys(std::slice::from_ref(&trusted))
+			.unwrap();
+		assert_eq!(bucket.trusted_keys().unwrap(), [trusted]);
+
+		let signed = commit_signed(
+			&upstream,
+			|path| ssh_sign(&maintainer, path),
+			&test::manifest_json("2.0", ""),
+		);
+
+		assert!(matches!(
+			bucket.pull(None, None).unwrap(),
+			Pull::FastForward { to, .. } if to == signed
+		));
+
+		// Neither an unsigned commit, nor a commit signed by another key, is pulled.
+		let untrusted = commit_signed(
+			&upstream,
+			|path| ssh_sign(&stranger, path),
+			&test::manifest_json("3.0", ""),
+		);
+
+		assert!(matches!(
+			bucket.pull(None, None),
+			Err(Error::UntrustedCommit { commit, .. }) if commit == untrusted.to_string()
+		));
+
+		let unsigned = test::commit_manifests(
+			&upstream,
+			&[("app", &test::manifest_json("4.0", ""))],
+			"Update app",
+		);
+
+		assert!(matches!(
+			bucket.pull(None, None),
+			Err(Error::UntrustedCommit { commit, .. }) if commit == unsigned.to_string()
+		));
+		assert_eq!(bucket.commit().unwrap().unwrap().id, signed);
+
+		// Moving back to a commit from before the key was trusted verifies that commit.
+		let initial = upstream.find_commit(signed).unwrap().parent_id(0).unwrap();
+		bucket
+			.set_pin(Some(&Pin::Commit(initial.to_string())))
+			.unwrap();
+
+		assert!(matches!(
+			bucket.pull(None, None),
+			Err(Error::UntrustedCommit { commit, .. }) if commit == initial.to_string()
+		));
+		assert_eq!(bucket.commit().unwrap().unwrap().id, signed);
+
+		bucket.set_pin(None).unwrap();
+
+		// Without trusted keys, commits are not verified.
+		bucket.set_trusted_keys(&[]).unwrap();
+		assert!(matches!(
+			bucket.pull(None, None).unwrap(),
+			Pull::FastForward { to, .. } if to == unsigned
+		));
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn verify_gpg() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let upstream_dir = temp_dir.path().join("upstream");
+		let json = test::manifest_json("1.0", "");
+
+		let upstream = test::create_bucket(&upstream_dir, &[("app", &json)]);
+		let bucket = Bucket::clone(
+			&util::path_to_string(&upstream_dir),
+			temp_dir.path().join("bucket"),
+			CloneOptions::new(),
+		)
+		.unwrap();
+
+		let gpg = Gpg::new(temp_dir.path());
+		git2::Repository::open(bucket.dir())
+			.unwrap()
+			.config()
+			.unwrap()
+			.set_str("gpg.program", &util::path_to_string(&gpg.program))
+			.unwrap();
+
+		// The expired key was valid when it signed, which gpg still reports with `VALIDSIG`.
+		let past = Some("20200101T000000");
+		let trusted = gpg.key("Maintainer <maintainer@example.test>", "never", None);
+		let expired = gpg.key("Former <former@example.test>", "1d", past);
+		gpg.key("Stranger <stranger@example.test>", "never", None);
+
+		bucket.set_trusted_keys(&[trusted, expired]).unwrap();
+
+		let signed = commit_signed(
+			&upstream,
+			|path| gpg.sign("maintainer@example.test", None, path),
+			&test::manifest_json("2.0", ""),
+		);
+
+		assert!(matches!(
+			bucket.pull(None, None).unwrap(),
+			Pull::FastForward { to, .. } if to == signed
+		));
+
+		// The newest untrusted commit is reported, so each commit is rejected on its own.
+		let untrusted = commit_signed(
+			&upstream,
+			|path| gpg.sign("stranger@example.test", None, path),
+			&test::manifest_json("3.0", ""),
+		);
+
+		assert!(matches!(
+			bucket.pull(None, None),
+			Err(Error::UntrustedCommit { commit, .. }) if commit == untrusted.to_string()
+		));
+
+		let expired = commit_signed(
+			&upstream,
+			|path| gpg.sign("former@example.test", past, path),
+			&test::manifest_json("4.0", ""),
+		);
+
+		assert!(matches!(
+			bucket.pull(None, None),
+			Err(Error::UntrustedCommit { commit, .. }) if commit == expired.to_string()
+		));
+		assert_eq!(bucket.commit().unwrap().unwrap().id, signed);
+	}
+}
@@ -0,0 +1,244 @@
+use std::sync::OnceLock;
+
+use serde_json::Map;
+use serde_json::Value;
+
+/// Where the manifest for an app came from.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum Source {
+	/// The manifest is the latest version in the bucket.
+	Latest,
+
+	/// The manifest was read from the bucket's history.
+	History {
+		/// The ID of the commit the manifest was read from.
+		commit: String,
+	},
+
+	/// The manifest was generated from the `autoupdate` field of the latest manifest.
+	///
+	/// Hashes are not known for generated manifests, so they must be computed after downloading.
+	Autoupdate {
+		/// The version of the manifest it was generated from.
+		from: String,
+	},
+}
+
+fn re_match_head() -> &'static regex::Regex {
+	static RE_MATCH_HEAD: OnceLock<regex::Regex> = OnceLock::new();
+
+	RE_MATCH_HEAD.get_or_init(|| regex::Regex::new(r"^\d+\.\d+(\.\d+)?").unwrap())
+}
+
+/// Returns the variables that can be used in `autoupdate` templates for a version, as (variable, value).
+///
+/// See Scoop's `Get-VersionSubstitution`.
+fn variables(version: &str) -> Vec<(&'static str, String)> {
+	let parts: Vec<_> = version.split(['.', '-', '_']).collect();
+	let part = |i: usize| parts.get(i).copied().unwrap_or_default().to_owned();
+
+	let head = re_match_head()
+		.find(version)
+		.map_or("", |head| head.as_str());
+
+	vec![
+		("$version", version.to_owned()),
+		("$underscoreVersion", version.replace('.', "_")),
+		("$dashVersion", version.replace('.', "-")),
+		("$dotVersion", version.replace(['-', '_'], ".")),
+		("$cleanVersion", version.replace(['.', '-'], "")),
+		("$majorVersion", part(0)),
+		("$minorVersion", part(1)),
+		("$patchVersion", part(2)),
+		("$buildVersion", part(3)),
+		(
+			"$preReleaseVersion",
+			version
+				.split_once('-')
+				.map(|(_, pre)| pre.to_owned())
+				.unwrap_or_default(),
+		),
+		("$matchHead", head.to_owned()),
+		("$matchTail", version[head.len()..].to_owned()),
+	]
+}
+
+/// Replaces the variables in every string in a JSON value.
+fn substitute(value: &mut Value, variables: &[(&str, String)]) {
+	match value {
+		Value::String(s) => {
+			for (variable, replacement) in variables {
+				*s = s.replace(variable, replacement);
+			}
+		}
+		Value::Array(values) => values.iter_mut().for_each(|v| substitute(v, variables)),
+		Value::Object(map) => map.values_mut().for_each(|v| substitute(v, variables)),
+		_ => {}
+	}
+}
+
+/// Copies fields from an `autoupdate` object into a manifest object, except for hashes.
+/// Hash extraction needs the network, so hashes are left out.
+fn merge(target: &mut Map<String, Value>, fields: Map<String, Value>) {
+	for (key, value) in fields {
+		if key != "hash" {
+			target.insert(key, value);
+		}
+	}
+}
+
+/// Generates a manifest for a version from the `autoupdate` field of another manifest.
+/// If the manifest does not have `autoupdate`, None is returned.
+///
+/// # Arguments
+///
+/// * `manifest` - The manifest as a JSON object.
+/// * `version` - The version to generate the manifest for.
+pub(crate) fn generate(mut manifest: Value, version: &str) -> Option<Value> {
+	let mut autoupdate = manifest.get("autoupdate")?.as_object()?.clone();
+	let object = manifest.as_object_mut()?;
+
+	let variables = variables(version);
+
+	for value in autoupdate.values_mut() {
+		substitute(value, &variables);
+	}
+
+	// The hashes are for the other version.
+	object.remove("hash");
+
+	if let Some(Value::Object(archs)) = object.get_mut("architecture") {
+		for arch in archs.values_mut().filter_map(Value::as_object_mut) {
+			arch.remove("hash");
+		}
+	}
+
+	if let Some(Value::Object(archs)) = autoupdate.remove("architecture") {
+		let targets = object
+			.entry("architecture")
+			.or_insert_with(|| Value::Object(Map::new()));
+
+		let targets = targets.as_object_mut()?;
+
+		for (arch, fields) in archs {
+			let Value::Object(fields) = fields else {
+				continue;
+			};
+
+			let target = targets
+				.entry(arch)
+				.or_insert_with(|| Value::Object(Map::new()));
+
+			merge(target.as_object_mut()?, fields);
+		}
+	}
+
+	merge(object, autoupdate);
+	object.insert("version".to_owned(), Value::String(version.to_owned()));
+
+	Some(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::bucket::Bucket;
+	use crate::bucket::Error;
+	use crate::bucket::Source;
+	use crate::test;
+
+	#[test]
+	fn variables() {
+		let value = |variable: &str| {
+			super::variables("1.2.3-beta")
+				.into_iter()
+				.find(|(v, _)| *v == variable)
+				.unwrap()
+				.1
+		};
+
+		assert_eq!(value("$underscoreVersion"), "1_2_3-beta");
+		assert_eq!(value("$cleanVersion"), "123beta");
+		assert_eq!(value("$patchVersion"), "3");
+		assert_eq!(value("$preReleaseVersion"), "beta");
+		assert_eq!(value("$matchHead"), "1.2.3");
+		assert_eq!(value("$matchTail"), "-beta");
+	}
+
+	#[test]
+	fn generate() {
+		let manifest = serde_json::json!({
+			"version": "1.0",
+			"url": "https://example.test/app-1.0.zip",
+			"hash": "abc",
+			"architecture": {
+				"64bit": { "hash": "def", "bin": "app.exe" }
+			},
+			"autoupdate": {
+				"url": "https://example.test/app-$version.zip",
+				"hash": { "url": "$url.sha256" },
+				"architecture": {
+					"64bit": { "extract_dir": "app-$majorVersion" }
+				}
+			}
+		});
+
+		let generated = super::generate(manifest, "2.5").unwrap();
+
+		assert_eq!(generated["version"], "2.5");
+		assert_eq!(generated["url"], "https://example.test/app-2.5.zip");
+		assert_eq!(generated.get("hash"), None);
+		assert_eq!(
+			generated["architecture"]["64bit"],
+			serde_json::json!({ "bin": "app.exe", "extract_dir": "app-2" })
+		);
+
+		assert_eq!(
+			super::generate(serde_json::json!({ "version": "1.0" }), "2.0"),
+			None
+		);
+	}
+
+	#[test]
+	fn manifest_version() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let v1 = test::manifest_json("1.0", "");
+		let v2 = test::manifest_json(
+			"2.0",
+			r#""autoupdate": { "url": "https://example.test/app-$version.zip" }"#,
+		);
+
+		let repo = test::create_bucket(temp_dir.path(), &[("app", &v1), ("lib", &v1)]);
+		let added = repo.head().unwrap().target().unwrap();
+		test::commit_manifests(&repo, &[("app", &v2)], "Update app");
+
+		let bucket = Bucket::open(temp_dir.path()).unwrap();
+
+		let (manifest, source) = bucket.manifest_version("app", "2.0").unwrap();
+		assert_eq!(manifest.version, "2.0");
+		assert_eq!(source, Source::Latest);
+
+		let (manifest, source) = bucket.manifest_version("app", "1.0").unwrap();
+		assert_eq!(manifest.version, "1.0");
+		assert_eq!(
+			source,
+			Source::History {
+				commit: added.to_string()
+			}
+		);
+
+		let (manifest, source) = bucket.manifest_version("app", "3.0").unwrap();
+		assert_eq!(manifest.version, "3.0");
+		assert_eq!(
+			source,
+			Source::Autoupdate {
+				from: "2.0".to_owned()
+			}
+		);
+
+		assert!(matches!(
+			bucket.manifest_version("lib", "3.0"),
+			Err(Error::VersionNotFound { .. })
+		));
+	}
+}
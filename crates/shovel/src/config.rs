@@ -4,7 +4,9 @@ use std::path::PathBuf;
 use std::sync::OnceLock;
 
 use home;
+use indexmap::IndexMap;
 
+use crate::bucket::KnownEntry;
 use crate::json;
 use crate::util;
 
@@ -22,15 +24,22 @@ pub fn user_install_dir() -> &'static Path {
 }
 
 /// Returns the global installation directory at `%ProgramData%\scoop`.
+///
+/// If `ProgramData` is not set, e.g., on non-Windows hosts, the default of `C:\ProgramData` is used on Windows,
+/// and `/var/lib` elsewhere.
 #[must_use]
-#[allow(clippy::missing_panics_doc)]
 pub fn global_install_dir() -> &'static Path {
 	static GLOBAL_INSTALL_DIR: OnceLock<PathBuf> = OnceLock::new();
 
 	GLOBAL_INSTALL_DIR.get_or_init(|| {
-		env::var("ProgramData")
-			.map(PathBuf::from)
-			.expect("ProgramData env var should exist")
+		let default = if cfg!(windows) {
+			r"C:\ProgramData"
+		} else {
+			"/var/lib"
+		};
+
+		env::var_os("ProgramData")
+			.map_or_else(|| PathBuf::from(default), PathBuf::from)
 			.join("scoop")
 	})
 }
@@ -41,6 +50,21 @@ json::json_struct_nodefault! {
 	pub struct Config {
 		/// The installation directory where apps, buckets, etc. are stored.
 		pub install_dir: String,
+
+		/// The names of buckets to search first for unqualified manifest names, from highest to lowest priority.
+		/// Other buckets come after, with `main` first and the rest in the order they were added.
+		#[serde(default)]
+		pub bucket_priority: Vec<String>,
+
+		/// Known buckets to add by name, i.e., an organization's buckets, on top of the built-in list.
+		/// These replace entries of the same name in the built-in list and `known_buckets_file`.
+		#[serde(default)]
+		pub known_buckets: IndexMap<String, KnownEntry>,
+
+		/// The path to a JSON file of known buckets, in the same format as `known_buckets`.
+		/// Its entries replace entries of the same name in the built-in list.
+		#[serde(default)]
+		pub known_buckets_file: Option<String>,
 	}
 }
 
@@ -81,6 +105,12 @@ impl Config {
 		self.install_dir().join("persist")
 	}
 
+	/// Returns the directory where shims for app executables are stored.
+	#[must_use]
+	pub fn shim_dir(&self) -> PathBuf {
+		self.install_dir().join("shims")
+	}
+
 	/// Returns the directory where PowerShell modules are symlinked.
 	#[must_use]
 	pub fn module_dir(&self) -> PathBuf {
@@ -92,6 +122,9 @@ impl Default for Config {
 	fn default() -> Self {
 		Config {
 			install_dir: util::path_to_string(user_install_dir()),
+			bucket_priority: vec![],
+			known_buckets: IndexMap::new(),
+			known_buckets_file: None,
 		}
 	}
 }
@@ -0,0 +1,392 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::bucket;
+use crate::bucket::Buckets;
+use crate::bucket::Name;
+use crate::bucket::Source;
+use crate::manifest::Manifest;
+use crate::util;
+
+pub(crate) fn re_7zip() -> &'static regex::Regex {
+	static RE_7ZIP: OnceLock<regex::Regex> = OnceLock::new();
+
+	// Taken from Scoop's `Test-7zipRequirement`.
+	RE_7ZIP.get_or_init(|| {
+		regex::Regex::new(
+			r"\.((gz)|(tar)|(t[abgpx]z2?)|(lzma)|(bz2?)|(7z)|(001)|(rar)|(iso)|(xz)|(lzh)|(nupkg))(\.[^\d.]+)?$",
+		)
+		.unwrap()
+	})
+}
+
+/// A dependency error.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// An app depends on itself, directly or indirectly.
+	/// The chain starts and ends with the same app.
+	#[error("Dependency cycle found: {}", .0.join(" -> "))]
+	Cycle(Vec<String>),
+
+	/// An app in the chain does not exist in any bucket.
+	/// The missing app is the last in the chain.
+	#[error("App not found: {}", .0.join(" -> "))]
+	NotFound(Vec<String>),
+
+	/// A bucket error.
+	#[error(transparent)]
+	Bucket(#[from] bucket::Error),
+}
+
+/// A dependency result.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The reason an app is part of an install plan.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+	/// The app was requested by the user.
+	Requested,
+
+	/// The app is listed in a manifest's `depends`.
+	Depends,
+
+	/// The app is implicitly needed to extract or install another app.
+	Helper,
+}
+
+impl fmt::Display for Kind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use Kind::*;
+
+		write!(
+			f,
+			"{}",
+			match self {
+				Requested => "requested",
+				Depends => "depends",
+				Helper => "helper",
+			}
+		)
+	}
+}
+
+/// A node in a dependency tree.
+#[derive(Debug)]
+pub struct Node {
+	/// The bucket the app was found in.
+	pub bucket: String,
+
+	/// The app's name.
+	pub name: String,
+
+	/// The reason the app is in the tree.
+	pub kind: Kind,
+
+	/// The app's dependencies, in the order they are declared.
+	pub dependencies: Vec<Node>,
+}
+
+/// An app in an install plan.
+#[derive(Debug)]
+pub struct Resolved {
+	/// The bucket the app was found in.
+	pub bucket: String,
+
+	/// The app's name.
+	pub name: String,
+
+	/// The reason the app is in the plan.
+	/// If the app appears more than once in the tree, this is the reason it first appeared for.
+	pub kind: Kind,
+
+	/// The app's manifest.
+	pub manifest: Manifest,
+
+	/// Where the app's manifest came from.
+	pub source: Source,
+}
+
+/// The result of resolving an app's dependencies.
+#[derive(Debug)]
+pub struct Resolution {
+	/// The dependency tree, rooted at the requested app.
+	pub tree: Node,
+
+	/// The apps to install in topological order, i.e., each app comes after all of its dependencies.
+	/// Each app appears only once, and the requested app is always last.
+	pub order: Vec<Resolved>,
+}
+
+/// Returns the names of the helper apps needed to extract or install an app.
+///
+/// # Arguments
+///
+/// * `app` - The app's name. Helpers never depend on themselves.
+/// * `manifest` - The app's manifest.
+#[must_use]
+pub fn helpers(app: &str, manifest: &Manifest) -> Vec<&'static str> {
+	let arch = manifest.compatible();
+
+	let files: Vec<_> = manifest
+		.url(arch)
+		.unwrap_or_default()
+		.iter()
+		.filter_map(|url| util::url_to_filename(url).ok())
+		.collect();
+
+	let scripts: Vec<_> = [
+		manifest.pre_install(arch),
+		manifest.installer_script(arch),
+		manifest.post_install(arch),
+	]
+	.into_iter()
+	.flatten()
+	.flatten()
+	.collect();
+
+	let uses = |function: &str| scripts.iter().any(|line| line.contains(function));
+
+	let mut helpers = vec![];
+
+	if files.iter().any(|f| re_7zip().is_match(f)) || uses("Expand-7zipArchive") {
+		helpers.push("7zip");
+	}
+
+	let is_msi = |file: &String| {
+		Path::new(file)
+			.extension()
+			.is_some_and(|ext| ext.eq_ignore_ascii_case("msi"))
+	};
+
+	if files.iter().any(is_msi) || uses("Expand-MsiArchive") {
+		helpers.push("lessmsi");
+	}
+
+	if uses("Expand-DarkArchive") {
+		helpers.push("dark");
+	}
+
+	if manifest.innosetup.unwrap_or_default() || uses("Expand-InnoArchive") {
+		helpers.push("innounp");
+	}
+
+	helpers.retain(|helper| *helper != app);
+
+	helpers
+}
+
+/// A dependency resolver.
+///
+/// Dependencies are expanded recursively from each manifest's `depends`, as well as any [`helpers`] the app needs.
+/// Names may be qualified with a bucket, i.e., `bucket/app`.
+pub struct Resolver<'b> {
+	buckets: &'b Buckets,
+}
+
+struct State {
+	stack: Vec<String>,
+	seen: HashSet<String>,
+	order: Vec<Resolved>,
+}
+
+impl<'b> Resolver<'b> {
+	/// Creates a new resolver.
+	///
+	/// # Arguments
+	///
+	/// * `buckets` - The buckets to find apps in.
+	#[must_use]
+	pub fn new(buckets: &'b Buckets) -> Self {
+		Self { buckets }
+	}
+
+	/// Resolves the dependencies of an app.
+	///
+	/// # Arguments
+	///
+	/// * `name` - The app's name.
+	///
+	/// # Errors
+	///
+	/// [`Error::NotFound`] is returned if the app or any of its dependencies does not exist.
+	///
+	/// [`Error::Cycle`] is returned if the app depends on itself, directly or indirectly.
+	///
+	/// [`Error::Bucket`] is returned if a bucket or manifest cannot be read.
+	pub fn resolve(&self, name: &Name) -> Result<Resolution> {
+		let mut state = State {
+			stack: vec![],
+			seen: HashSet::new(),
+			order: vec![],
+		};
+
+		let tree = self.visit(name, Kind::Requested, &mut state)?;
+
+		Ok(Resolution {
+			tree,
+			order: state.order,
+		})
+	}
+
+	fn visit(&self, name: &Name, kind: Kind, state: &mut State) -> Result<Node> {
+		// Guessing the bucket of an app the user asked for is unsafe, but dependencies resolve by priority.
+		let found = if kind == Kind::Requested {
+			self.buckets.unique_manifest(name)
+		} else {
+			self.buckets.manifest(name)
+		};
+
+		let (bucket, item) = match found {
+			Ok(found) => found,
+			Err(bucket::Error::ManifestNotFound) => {
+				let mut chain = state.stack.clone();
+				chain.push(name.full().to_owned());
+
+				return Err(Error::NotFound(chain));
+			}
+			Err(err) => return Err(err.into()),
+		};
+
+		// Only the requested app can be pinned to a version, as dependencies have no version constraints.
+		let (manifest, source) = match name.version() {
+			Some(version) if kind == Kind::Requested => {
+				bucket.manifest_version(&item.name, version)?
+			}
+			_ => (item.manifest?, Source::Latest),
+		};
+
+		let bucket = bucket.name();
+		let id = format!("{bucket}/{}", item.name);
+
+		if let Some(pos) = state.stack.iter().position(|s| *s == id) {
+			let mut chain = state.stack[pos..].to_vec();
+			chain.push(id);
+
+			return Err(Error::Cycle(chain));
+		}
+
+		// Helpers come first, as they may be needed to install the other dependencies.
+		let dependencies: Vec<_> = helpers(&item.name, &manifest)
+			.into_iter()
+			.map(|helper| (Name::new(helper.to_owned()), Kind::Helper))
+			.chain(
+				manifest
+					.depends
+					.as_deref()
+					.unwrap_or_default()
+					.iter()
+					.map(|dep| (Name::new(dep.clone()), Kind::Depends)),
+			)
+			.collect();
+
+		state.stack.push(id.clone());
+
+		let dependencies: Result<Vec<_>> = dependencies
+			.iter()
+			.map(|(dep, kind)| self.visit(dep, *kind, state))
+			.collect();
+
+		state.stack.pop();
+
+		let node = Node {
+			bucket: bucket.clone(),
+			name: item.name.clone(),
+			kind,
+			dependencies: dependencies?,
+		};
+
+		if state.seen.insert(id) {
+			state.order.push(Resolved {
+				bucket,
+				name: item.name,
+				kind,
+				manifest,
+				source,
+			});
+		}
+
+		Ok(node)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test;
+
+	fn resolve(manifests: &[(&str, &str)], name: &str) -> Result<Resolution> {
+		let temp_dir = tempfile::tempdir().unwrap();
+		test::create_bucket(&temp_dir.path().join("main"), manifests);
+
+		let buckets = Buckets::new(temp_dir.path());
+
+		Resolver::new(&buckets).resolve(&Name::new(name.to_owned()))
+	}
+
+	#[test]
+	fn order() {
+		let app = test::manifest_json("1.0", r#""depends": ["lib", "main/tool"]"#);
+		let lib = test::manifest_json("1.0", r#""depends": "tool", "innosetup": true"#);
+		let tool = test::manifest_json("1.0", "");
+		let innounp = test::manifest_json("1.0", "");
+
+		let resolution = resolve(
+			&[
+				("app", &app),
+				("lib", &lib),
+				("tool", &tool),
+				("innounp", &innounp),
+			],
+			"app",
+		)
+		.unwrap();
+
+		let order: Vec<_> = resolution.order.iter().map(|r| r.name.as_str()).collect();
+		assert_eq!(order, ["innounp", "tool", "lib", "app"]);
+
+		let lib = &resolution.tree.dependencies[0];
+		assert_eq!(lib.name, "lib");
+		assert_eq!(lib.dependencies[0].kind, Kind::Helper);
+	}
+
+	#[test]
+	fn cycle() {
+		let a = test::manifest_json("1.0", r#""depends": "b""#);
+		let b = test::manifest_json("1.0", r#""depends": "a""#);
+
+		let result = resolve(&[("a", &a), ("b", &b)], "a");
+
+		match result {
+			Err(Error::Cycle(chain)) => assert_eq!(chain, ["main/a", "main/b", "main/a"]),
+			_ => panic!("expected a cycle"),
+		}
+	}
+
+	#[test]
+	fn not_found() {
+		let a = test::manifest_json("1.0", r#""depends": "b""#);
+		let b = test::manifest_json("1.0", r#""depends": "c""#);
+
+		let result = resolve(&[("a", &a), ("b", &b)], "a");
+
+		match result {
+			Err(Error::NotFound(chain)) => assert_eq!(chain, ["main/a", "main/b", "c"]),
+			_ => panic!("expected a missing app"),
+		}
+	}
+
+	#[test]
+	fn helpers_from_urls() {
+		let manifest: Manifest = serde_json::from_str(&test::manifest_json(
+			"1.0",
+			r#""architecture": {"64bit": {"url": "https://example.test/app.tar.gz"}, "32bit": {"url": "https://example.test/app.tar.gz"}, "arm64": {"url": "https://example.test/app.tar.gz"}}"#,
+		))
+		.unwrap();
+
+		assert_eq!(helpers("app", &manifest), ["7zip"]);
+		assert_eq!(helpers("7zip", &manifest), Vec::<&str>::new());
+	}
+}
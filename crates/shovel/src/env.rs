@@ -0,0 +1,269 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+
+use powershell::Expression;
+
+use crate::config::Config;
+
+/// The separator between entries in a path-like environment variable.
+pub const PATH_SEPARATOR: char = ';';
+
+/// An environment error.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// An environment variable could not be read or written.
+	#[error("Failed to access environment variable {name:?} - got {output:?}")]
+	Failure {
+		name: String,
+		output: powershell::Output,
+	},
+
+	/// An IO error occurred.
+	#[error(transparent)]
+	Io(#[from] io::Error),
+}
+
+/// An environment result.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A store of persistent environment variables.
+///
+/// Unlike `std::env`, changes are expected to outlive the current process.
+pub trait Environment {
+	/// Returns the value of a variable, or None if it is not set.
+	///
+	/// # Errors
+	///
+	/// If the variable cannot be read, an error is returned.
+	fn get(&self, name: &str) -> Result<Option<String>>;
+
+	/// Sets the value of a variable. If `value` is None, the variable is removed.
+	///
+	/// # Errors
+	///
+	/// If the variable cannot be written, an error is returned.
+	fn set(&self, name: &str, value: Option<&str>) -> Result<()>;
+
+	/// Checks if a path-like variable contains an entry. Entries are compared case-insensitively.
+	///
+	/// # Errors
+	///
+	/// See [`get`].
+	///
+	/// [`get`]: Environment::get
+	fn contains_path(&self, name: &str, entry: &str) -> Result<bool> {
+		let value = self.get(name)?.unwrap_or_default();
+		let contains = split_paths(&value).any(|e| e.eq_ignore_ascii_case(entry));
+
+		Ok(contains)
+	}
+
+	/// Prepends an entry to a path-like variable.
+	/// If the entry already exists, this is a no-op and `Ok(false)` is returned, otherwise `Ok(true)`.
+	///
+	/// # Errors
+	///
+	/// See [`get`] and [`set`].
+	///
+	/// [`get`]: Environment::get
+	/// [`set`]: Environment::set
+	fn add_path(&self, name: &str, entry: &str) -> Result<bool> {
+		if self.contains_path(name, entry)? {
+			return Ok(false);
+		}
+
+		let value = self.get(name)?.unwrap_or_default();
+		let paths: Vec<_> = [entry].into_iter().chain(split_paths(&value)).collect();
+		let sep = PATH_SEPARATOR.to_string();
+
+		self.set(name, Some(&paths.join(&sep)))?;
+
+		Ok(true)
+	}
+
+	/// Removes an entry from a path-like variable.
+	/// If the entry does not exist, this is a no-op and `Ok(false)` is returned, otherwise `Ok(true)`.
+	///
+	/// # Errors
+	///
+	/// See [`get`] and [`set`].
+	///
+	/// [`get`]: Environment::get
+	/// [`set`]: Environment::set
+	fn remove_path(&self, name: &str, entry: &str) -> Result<bool> {
+		let value = self.get(name)?.unwrap_or_default();
+
+		let paths: Vec<_> = split_paths(&value)
+			.filter(|e| !e.eq_ignore_ascii_case(entry))
+			.collect();
+
+		if paths.len() == split_paths(&value).count() {
+			return Ok(false);
+		}
+
+		let sep = PATH_SEPARATOR.to_string();
+		let value = paths.join(&sep);
+
+		self.set(name, (!value.is_empty()).then_some(value.as_str()))?;
+
+		Ok(true)
+	}
+}
+
+/// Yields the non-empty entries in a path-like value.
+fn split_paths(value: &str) -> impl Iterator<Item = &str> {
+	value.split(PATH_SEPARATOR).filter(|e| !e.is_empty())
+}
+
+/// The scope of a system environment variable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+	/// Variables for the current user.
+	User,
+
+	/// Variables for all users on the machine.
+	Machine,
+}
+
+impl Scope {
+	/// Returns the scope matching the installation directory of a config.
+	#[must_use]
+	pub fn of(config: &Config) -> Self {
+		if config.is_global() {
+			Self::Machine
+		} else {
+			Self::User
+		}
+	}
+
+	fn as_str(self) -> &'static str {
+		match self {
+			Self::User => "User",
+			Self::Machine => "Machine",
+		}
+	}
+}
+
+/// The environment variables of the system, stored in the registry.
+///
+/// Variables are read and written through PowerShell.
+pub struct System {
+	scope: Scope,
+}
+
+impl System {
+	/// Creates a new system environment.
+	///
+	/// # Arguments
+	///
+	/// * `scope` - The scope of the variables.
+	#[must_use]
+	pub fn new(scope: Scope) -> Self {
+		Self { scope }
+	}
+
+	fn run(&self, name: &str, value: Option<&str>, script: &str) -> Result<String> {
+		let mut runner = powershell::Runner::default();
+
+		let value = match value {
+			Some(value) => value.to_owned().into(),
+			None => Expression::Raw("$null".to_owned()),
+		};
+
+		runner.vars([
+			("name", name.to_owned().into()),
+			("value", value),
+			("scope", self.scope.as_str().to_owned().into()),
+		]);
+
+		let output = runner.run(script)?;
+
+		if output.status.success() {
+			Ok(output.stdout.trim().to_owned())
+		} else {
+			Err(Error::Failure {
+				name: name.to_owned(),
+				output,
+			})
+		}
+	}
+}
+
+impl Environment for System {
+	fn get(&self, name: &str) -> Result<Option<String>> {
+		let value = self.run(
+			name,
+			None,
+			"[Environment]::GetEnvironmentVariable($name, $scope)",
+		)?;
+
+		Ok((!value.is_empty()).then_some(value))
+	}
+
+	fn set(&self, name: &str, value: Option<&str>) -> Result<()> {
+		self.run(
+			name,
+			value,
+			"[Environment]::SetEnvironmentVariable($name, $value, $scope)",
+		)?;
+
+		Ok(())
+	}
+}
+
+/// An in-memory environment. Changes are discarded when it is dropped.
+///
+/// This is useful for testing, or previewing changes before applying them.
+#[derive(Default)]
+pub struct Memory {
+	vars: RefCell<HashMap<String, String>>,
+}
+
+impl Memory {
+	/// Creates a new, empty in-memory environment.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+impl Environment for Memory {
+	fn get(&self, name: &str) -> Result<Option<String>> {
+		Ok(self.vars.borrow().get(name).cloned())
+	}
+
+	fn set(&self, name: &str, value: Option<&str>) -> Result<()> {
+		let mut vars = self.vars.borrow_mut();
+
+		match value {
+			Some(value) => vars.insert(name.to_owned(), value.to_owned()),
+			None => vars.remove(name),
+		};
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn paths() {
+		let env = Memory::new();
+		env.set("Path", Some(r"C:\Windows;C:\Tools")).unwrap();
+
+		assert!(env.add_path("Path", r"C:\scoop\shims").unwrap());
+		assert!(!env.add_path("Path", r"c:\SCOOP\shims").unwrap());
+		assert_eq!(
+			env.get("Path").unwrap().unwrap(),
+			r"C:\scoop\shims;C:\Windows;C:\Tools"
+		);
+
+		assert!(env.remove_path("Path", r"C:\Windows").unwrap());
+		assert!(!env.remove_path("Path", r"C:\Windows").unwrap());
+		assert!(env.contains_path("Path", r"C:\Tools").unwrap());
+		assert!(!env.contains_path("Path", r"C:\Windows").unwrap());
+	}
+}
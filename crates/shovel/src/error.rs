@@ -3,9 +3,12 @@ use std::io;
 use crate::app;
 use crate::bucket;
 use crate::cache;
+use crate::depends;
+use crate::env;
 use crate::hook;
 use crate::json;
 use crate::manifest;
+use crate::psmodule;
 use crate::util;
 
 /// A catch-all error.
@@ -23,6 +26,14 @@ pub enum Error {
 	#[error(transparent)]
 	Cache(#[from] cache::Error),
 
+	// A dependency error.
+	#[error(transparent)]
+	Depends(#[from] depends::Error),
+
+	// An environment error.
+	#[error(transparent)]
+	Env(#[from] env::Error),
+
 	// A hook error.
 	#[error(transparent)]
 	Hook(#[from] hook::Error),
@@ -31,6 +42,10 @@ pub enum Error {
 	#[error(transparent)]
 	Manifest(#[from] manifest::Error),
 
+	// A PowerShell module error.
+	#[error(transparent)]
+	Module(#[from] psmodule::Error),
+
 	// An IO error.
 	#[error(transparent)]
 	Io(#[from] io::Error),
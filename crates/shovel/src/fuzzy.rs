@@ -0,0 +1,234 @@
+use std::cmp::Ordering;
+
+/// Returns the lowercase form of a character, for case-insensitive matching.
+fn fold(c: char) -> char {
+	c.to_lowercase().next().unwrap_or(c)
+}
+
+fn folded(s: &str) -> Vec<char> {
+	s.chars().map(fold).collect()
+}
+
+/// The kind of match between a query and a target, from best to worst.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+	/// The target is the query.
+	Exact,
+
+	/// The target starts with the query.
+	Prefix,
+
+	/// The target contains the query.
+	Substring,
+
+	/// The target contains every character of the query in order, with gaps in between.
+	Fuzzy,
+}
+
+/// A match between a query and a target. Matching is case-insensitive.
+///
+/// Matches are ordered from best to worst, so sorting a list of matches ranks them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+	/// The kind of match.
+	pub kind: Kind,
+
+	/// The penalty within the kind of match. Lower is better.
+	/// This accounts for unmatched characters in the target, and gaps between matched characters.
+	pub penalty: usize,
+
+	/// The character (not byte) indices in the target that matched the query, in ascending order.
+	pub indices: Vec<usize>,
+}
+
+impl Ord for Match {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.kind
+			.cmp(&other.kind)
+			.then(self.penalty.cmp(&other.penalty))
+			.then_with(|| self.indices.cmp(&other.indices))
+	}
+}
+
+impl PartialOrd for Match {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+/// Scores a target against a query. If the target does not match, None is returned.
+///
+/// # Arguments
+///
+/// * `query` - The query. An empty query matches nothing.
+/// * `target` - The text to match the query against.
+#[must_use]
+pub fn score(query: &str, target: &str) -> Option<Match> {
+	let query = folded(query);
+	let target = folded(target);
+
+	if query.is_empty() || query.len() > target.len() {
+		return None;
+	}
+
+	// Characters in the target that are not matched.
+	let unmatched = target.len() - query.len();
+	let contiguous = |start: usize| (start..start + query.len()).collect();
+
+	if target == query {
+		return Some(Match {
+			kind: Kind::Exact,
+			penalty: 0,
+			indices: contiguous(0),
+		});
+	}
+
+	if target.starts_with(&query) {
+		return Some(Match {
+			kind: Kind::Prefix,
+			penalty: unmatched,
+			indices: contiguous(0),
+		});
+	}
+
+	if let Some(start) = target.windows(query.len()).position(|w| w == query) {
+		return Some(Match {
+			kind: Kind::Substring,
+			penalty: start + unmatched,
+			indices: contiguous(start),
+		});
+	}
+
+	let mut indices = Vec::with_capacity(query.len());
+	let mut chars = query.iter().peekable();
+
+	for (i, c) in target.iter().enumerate() {
+		if chars.next_if_eq(&c).is_some() {
+			indices.push(i);
+		}
+	}
+
+	if chars.peek().is_some() {
+		return None;
+	}
+
+	// Gaps between matched characters are penalized more than trailing characters.
+	let span = indices.last()? - indices.first()? + 1;
+	let gaps = span - query.len();
+
+	Some(Match {
+		kind: Kind::Fuzzy,
+		penalty: gaps * 2 + unmatched,
+		indices,
+	})
+}
+
+/// Returns the edit distance between two strings, i.e., the number of insertions, deletions or substitutions
+/// needed to turn one into the other. Comparison is case-insensitive.
+#[must_use]
+pub fn distance(a: &str, b: &str) -> usize {
+	let a = folded(a);
+	let b = folded(b);
+
+	// Only the previous row of the distance matrix is needed.
+	let mut prev: Vec<usize> = (0..=b.len()).collect();
+	let mut cur = vec![0; b.len() + 1];
+
+	for (i, ca) in a.iter().enumerate() {
+		cur[0] = i + 1;
+
+		for (j, cb) in b.iter().enumerate() {
+			let cost = usize::from(ca != cb);
+			cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+		}
+
+		std::mem::swap(&mut prev, &mut cur);
+	}
+
+	prev[b.len()]
+}
+
+/// Returns the candidates similar enough to a query to suggest as corrections, closest first.
+/// A candidate is similar if it matches the query, or is within a small edit distance relative to the query's length.
+///
+/// # Arguments
+///
+/// * `query` - The query, usually a misspelt name.
+/// * `candidates` - The candidates to suggest. Duplicates are removed.
+/// * `limit` - The maximum number of suggestions.
+#[must_use]
+pub fn suggest<'c, I>(query: &str, candidates: I, limit: usize) -> Vec<&'c str>
+where
+	I: IntoIterator<Item = &'c str>,
+{
+	let max_distance = (query.chars().count() / 3).max(1);
+
+	let mut ranked: Vec<_> = candidates
+		.into_iter()
+		.filter_map(|candidate| {
+			// Prefer candidates that contain the query over those that are merely close to it.
+			let rank = match score(query, candidate) {
+				Some(m) if m.kind != Kind::Fuzzy => (0, m.penalty),
+				_ => {
+					let distance = distance(query, candidate);
+					(distance <= max_distance).then_some((1, distance))?
+				}
+			};
+
+			Some((rank, candidate))
+		})
+		.collect();
+
+	ranked.sort_unstable();
+	ranked.dedup_by(|(_, a), (_, b)| a == b);
+
+	ranked
+		.into_iter()
+		.take(limit)
+		.map(|(_, candidate)| candidate)
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn kinds() {
+		let kind = |query, target| score(query, target).map(|m| m.kind);
+
+		assert_eq!(kind("git", "Git"), Some(Kind::Exact));
+		assert_eq!(kind("git", "github-cli"), Some(Kind::Prefix));
+		assert_eq!(kind("git", "lazygit"), Some(Kind::Substring));
+		assert_eq!(kind("rgp", "ripgrep"), Some(Kind::Fuzzy));
+		assert_eq!(kind("rgx", "ripgrep"), None);
+		assert_eq!(kind("", "ripgrep"), None);
+	}
+
+	#[test]
+	fn ranking() {
+		let mut targets = ["lazygit", "gitui", "git", "gui-installer-t", "gitk"];
+		targets.sort_by_key(|target| score("git", target));
+
+		assert_eq!(
+			targets,
+			["git", "gitk", "gitui", "lazygit", "gui-installer-t"]
+		);
+	}
+
+	#[test]
+	fn indices() {
+		assert_eq!(score("rgp", "RipGrep").unwrap().indices, [0, 3, 6]);
+		assert_eq!(score("grep", "ripgrep").unwrap().indices, [3, 4, 5, 6]);
+	}
+
+	#[test]
+	fn suggestions() {
+		assert_eq!(distance("ripgrep", "ripgerp"), 2);
+
+		let candidates = ["ripgrep", "grep", "ripgrep", "python", "ripgrep-all"];
+
+		assert_eq!(suggest("ripgerp", candidates, 5), ["ripgrep"]);
+		assert_eq!(suggest("grep", candidates, 2), ["grep", "ripgrep"]);
+	}
+}
@@ -37,7 +37,8 @@ pub enum Error {
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// The kind of hook being executed.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Script {
 	Install,
 	Uninstall,
@@ -47,8 +48,31 @@ pub enum Script {
 	PostUninstall,
 }
 
+impl Script {
+	/// Returns the lines of the hook script in a manifest, if any.
+	///
+	/// # Arguments
+	///
+	/// * `manifest` - The manifest to get the script from.
+	/// * `arch` - The architecture to get the script for.
+	#[must_use]
+	pub fn lines(self, manifest: &Manifest, arch: Arch) -> Option<&[String]> {
+		use Script::*;
+
+		match self {
+			Install => manifest.installer_script(arch),
+			Uninstall => manifest.uninstaller_script(arch),
+			PreInstall => manifest.pre_install(arch),
+			PostInstall => manifest.post_install(arch),
+			PreUninstall => manifest.pre_uninstall(arch),
+			PostUninstall => manifest.post_uninstall(arch),
+		}
+	}
+}
+
 /// The command invoking the hook.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Command {
 	/// An app is being installed.
 	Install,
@@ -136,7 +160,8 @@ impl Context<'_> {
 		let dir = util::path_to_string(
 			self.config
 				.app_dir()
-				.join(format!(r"{}\{}", self.app, self.manifest.version)),
+				.join(self.app)
+				.join(&self.manifest.version),
 		);
 		let persist_dir = util::path_to_string(self.config.persist_dir().join(self.app));
 
@@ -146,7 +171,8 @@ impl Context<'_> {
 		let global_dir = util::path_to_string(config::global_install_dir());
 		let module_dir = util::path_to_string(self.config.module_dir());
 		let original_dir = dir.clone();
-		let old_scoop_dir = util::path_to_string(home_dir().join(r"AppData\Local\Scoop"));
+		let old_scoop_dir =
+			util::path_to_string(home_dir().join("AppData").join("Local").join("Scoop"));
 		let scoop_dir = util::path_to_string(self.config.install_dir());
 
 		runner.vars([
@@ -217,21 +243,10 @@ impl<'h> Hook<'h> {
 	///
 	/// If the hook failed to run, `Error::Failure` is returned.
 	pub fn run(&self, script: Script) -> Result<Option<powershell::Output>> {
-		use Script::*;
-
-		let manifest = self.context.manifest;
-		let arch = self.context.arch;
-
-		let hook = match script {
-			Install => manifest.installer_script(arch),
-			Uninstall => manifest.uninstaller_script(arch),
-			PreInstall => manifest.pre_install(arch),
-			PostInstall => manifest.post_install(arch),
-			PreUninstall => manifest.pre_uninstall(arch),
-			PostUninstall => manifest.post_uninstall(arch),
-		}
-		.unwrap_or_default()
-		.join("\r\n");
+		let hook = script
+			.lines(self.context.manifest, self.context.arch)
+			.unwrap_or_default()
+			.join("\r\n");
 
 		if hook.is_empty() {
 			Ok(None)
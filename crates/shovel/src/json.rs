@@ -31,6 +31,38 @@ where
 	Ok(value)
 }
 
+/// Deserialize a type `T` from a JSON value.
+///
+/// # Arguments
+///
+/// * `value` - The value to deserialize from.
+///
+/// # Errors
+///
+/// Errors from `serde_path_to_error` are returned verbatim.
+pub fn from_value<T>(value: serde_json::Value) -> Result<T, Error>
+where
+	T: de::DeserializeOwned,
+{
+	serde_path_to_error::deserialize(value)
+}
+
+/// Serialize a type `T` to a JSON value.
+///
+/// # Arguments
+///
+/// * `value` - The value to serialize.
+///
+/// # Errors
+///
+/// Errors from `serde_path_to_error` are returned verbatim.
+pub fn to_value<T>(value: &T) -> Result<serde_json::Value, Error>
+where
+	T: serde::Serialize,
+{
+	serde_path_to_error::serialize(value, serde_json::value::Serializer)
+}
+
 /// Serialize a type `T` to a writer as JSON.
 /// The writer is wrapped in a buffered writer.
 ///
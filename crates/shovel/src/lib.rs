@@ -5,12 +5,17 @@ pub mod app;
 pub mod bucket;
 pub mod cache;
 pub mod config;
+pub mod depends;
 pub mod download;
+pub mod env;
 pub mod error;
+pub mod fuzzy;
 pub mod hook;
 pub mod json;
 pub mod manifest;
 pub mod persist;
+pub mod plan;
+pub mod psmodule;
 pub mod shovel;
 pub mod timestamp;
 
@@ -21,7 +26,9 @@ mod util;
 pub use config::Config;
 pub use error::Error;
 pub use error::Result;
+pub use shovel::BucketUpdate;
 pub use shovel::CatOptions;
 pub use shovel::Shovel;
 pub use shovel::UpdateOptions;
+pub use shovel::Updates;
 pub use timestamp::Timestamp;
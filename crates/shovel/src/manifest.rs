@@ -445,12 +445,14 @@ impl fmt::Display for Bins {
 
 json_enum_key! {
 	/// The mode to use when extracting hashes.
+	#[derive(Default)]
 	pub enum HashExtractionMode {
 		/// Download the app and hash it locally.
 		#[serde(rename = "download")]
 		Download,
 
 		/// Extract the hash from the URL directly.
+		#[default]
 		#[serde(rename = "extract")]
 		Extract,
 
@@ -482,12 +484,6 @@ json_enum_key! {
 	}
 }
 
-impl Default for HashExtractionMode {
-	fn default() -> Self {
-		Self::Extract
-	}
-}
-
 json_struct! {
 	/// A set of instructions for extracting an app's hash.
 	pub struct HashExtraction {
@@ -530,22 +526,32 @@ json_enum_key! {
 	}
 }
 
+/// Returns the build number of the running version of Windows.
+#[cfg(windows)]
+fn windows_build() -> u32 {
+	windows_version::OsVersion::current().build
+}
+
+/// Returns the build number of the running version of Windows.
+/// On non-Windows hosts there is no such version, so the latest build is assumed.
+#[cfg(not(windows))]
+fn windows_build() -> u32 {
+	u32::MAX
+}
+
 impl Arch {
 	/// Returns the target architecture at the time of compilation.
 	///
-	/// # Panics
-	///
-	/// This function panics if the target architecture is not one of (`x86`, `x86_64`, `aarch64`).
+	/// Windows only runs on (`x86`, `x86_64`, `aarch64`).
+	/// Other architectures are treated as `x86_64`, so manifests can still be read on non-Windows hosts.
 	#[must_use]
 	pub fn native() -> Self {
 		if cfg!(target_arch = "x86") {
 			Self::X86
-		} else if cfg!(target_arch = "x86_64") {
-			Self::X86_64
 		} else if cfg!(target_arch = "aarch64") {
 			Self::Arm64
 		} else {
-			panic!("Unsupported architecture")
+			Self::X86_64
 		}
 	}
 
@@ -562,9 +568,7 @@ impl Arch {
 	///
 	/// See https://learn.microsoft.com/en-us/windows/arm/overview for details.
 	///
-	/// # Panics
-	///
-	/// The same caveats apply to this function as `Self::native`.
+	/// On non-Windows hosts, the latest version of Windows is assumed.
 	pub fn compatible() -> &'static [Self] {
 		static COMPATIBLE: OnceLock<Vec<Arch>> = OnceLock::new();
 
@@ -573,11 +577,9 @@ impl Arch {
 			let mut compatible = vec![Self::native()];
 
 			if cfg!(target_arch = "aarch64") {
-				let version = windows_version::OsVersion::current();
-
 				// Check if the build is Windows 11.
 				// https://en.wikipedia.org/wiki/Windows_11_version_history
-				if version.build >= 22000 {
+				if windows_build() >= 22000 {
 					compatible.push(Self::X86_64);
 				}
 			}
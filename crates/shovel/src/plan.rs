@@ -0,0 +1,818 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::app;
+use crate::app::Suggestion;
+use crate::bucket;
+use crate::bucket::Name;
+use crate::bucket::Source;
+use crate::cache::Key;
+use crate::depends;
+use crate::depends::Kind;
+use crate::depends::Resolver;
+use crate::error::Result;
+use crate::hook::Command;
+use crate::hook::Script;
+use crate::manifest::Arch;
+use crate::manifest::Bin;
+use crate::manifest::Bins;
+use crate::manifest::Manifest;
+use crate::manifest::PersistEntry;
+use crate::psmodule;
+use crate::shovel::Shovel;
+use crate::util;
+
+/// The environment variable where executables are searched for.
+const PATH: &str = "PATH";
+
+/// Checks if a downloaded file is an archive that will be extracted, rather than copied as-is.
+fn is_archive(file: &str) -> bool {
+	let ext = Path::new(file)
+		.extension()
+		.map(util::osstr_to_string)
+		.unwrap_or_default()
+		.to_ascii_lowercase();
+
+	matches!(ext.as_str(), "zip" | "msi") || depends::re_7zip().is_match(file)
+}
+
+/// What happens to an app in a plan.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum Action {
+	/// The app is installed.
+	Install,
+
+	/// The app is updated from an older version.
+	Update {
+		/// The version currently installed.
+		from: String,
+	},
+
+	/// The app is uninstalled.
+	Uninstall,
+
+	/// The app is already installed and left as-is.
+	Skip,
+}
+
+/// A URL to download.
+#[derive(Debug, Serialize)]
+pub struct Download {
+	/// The URL.
+	pub url: String,
+
+	/// The path in the cache the URL is downloaded to.
+	pub path: PathBuf,
+
+	/// Whether or not the URL is already in the cache, and will not be downloaded again.
+	pub cached: bool,
+}
+
+/// A downloaded file to extract or copy into the app directory.
+#[derive(Debug, Serialize)]
+pub struct Extract {
+	/// The file name of the download.
+	pub file: String,
+
+	/// Whether or not the file is an archive. If not, it is copied as-is.
+	pub archive: bool,
+
+	/// The directory within the archive to extract, if any.
+	pub extract_dir: Option<String>,
+
+	/// The directory to extract to.
+	pub to: PathBuf,
+}
+
+/// A shim for an app's executable.
+#[derive(Debug, Serialize)]
+pub struct Shim {
+	/// The shim's name, i.e., the command to run.
+	pub name: String,
+
+	/// The path to the shim.
+	pub path: PathBuf,
+
+	/// The executable the shim runs.
+	pub target: PathBuf,
+
+	/// The arguments passed to the executable.
+	pub arguments: Vec<String>,
+}
+
+/// A persisted file or directory, linked from the app directory to the persist directory.
+#[derive(Debug, Serialize)]
+pub struct Persist {
+	/// The path in the app directory.
+	pub source: PathBuf,
+
+	/// The path in the persist directory.
+	pub target: PathBuf,
+}
+
+/// A change to an environment variable.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum EnvChange {
+	/// An entry is added to a path-like variable.
+	AddPath { name: String, entry: String },
+
+	/// An entry is removed from a path-like variable.
+	RemovePath { name: String, entry: String },
+
+	/// A variable is set.
+	Set { name: String, value: String },
+
+	/// A variable is removed.
+	Unset { name: String },
+}
+
+/// The actions planned for a single app.
+#[derive(Debug, Serialize)]
+pub struct Step {
+	/// The bucket the app's manifest is from.
+	pub bucket: String,
+
+	/// The app's name.
+	pub name: String,
+
+	/// The app's version after the step.
+	pub version: String,
+
+	/// Where the app's manifest came from.
+	pub source: Source,
+
+	/// The app's architecture.
+	pub arch: Arch,
+
+	/// The reason the app is in the plan.
+	pub kind: Kind,
+
+	/// What happens to the app.
+	pub action: Action,
+
+	/// The app's version directory.
+	pub dir: PathBuf,
+
+	/// The URLs to download.
+	pub downloads: Vec<Download>,
+
+	/// The layout of the downloads in the app directory.
+	pub extract: Vec<Extract>,
+
+	/// The shims added or removed.
+	pub shims: Vec<Shim>,
+
+	/// The persisted data linked or unlinked. Persisted data is never deleted.
+	pub persist: Vec<Persist>,
+
+	/// The changes to environment variables.
+	pub env: Vec<EnvChange>,
+
+	/// The PowerShell module linked or unlinked, if any.
+	pub module: Option<PathBuf>,
+
+	/// The hook scripts run, in order.
+	pub hooks: Vec<Script>,
+
+	/// The features suggested by the app. A feature is also provided by an app installed in the same plan.
+	pub suggestions: Vec<Suggestion>,
+}
+
+impl Step {
+	fn new(bucket: String, name: String, manifest: &Manifest, kind: Kind, action: Action) -> Self {
+		Self {
+			bucket,
+			name,
+			version: manifest.version.clone(),
+			source: Source::Latest,
+			arch: manifest.compatible(),
+			kind,
+			action,
+			dir: PathBuf::new(),
+			downloads: vec![],
+			extract: vec![],
+			shims: vec![],
+			persist: vec![],
+			env: vec![],
+			module: None,
+			hooks: vec![],
+			suggestions: vec![],
+		}
+	}
+}
+
+/// An app that could not be planned. The rest of the plan is unaffected.
+#[derive(Debug, Serialize)]
+pub struct Failed {
+	/// The app's name.
+	pub name: String,
+
+	/// Why the app could not be planned.
+	pub error: String,
+}
+
+/// A plan describing every action taken by a command, in order.
+#[derive(Debug, Serialize)]
+pub struct Plan {
+	/// The command being planned.
+	pub command: Command,
+
+	/// The steps for each app. Dependencies always come before the apps that depend on them.
+	pub steps: Vec<Step>,
+
+	/// The apps that could not be planned.
+	pub failed: Vec<Failed>,
+}
+
+/// A planner for commands that change installed apps.
+///
+/// Planning does not modify anything, so a plan can be shown to the user before it is executed.
+pub struct Planner<'s> {
+	shovel: &'s Shovel,
+}
+
+impl<'s> Planner<'s> {
+	/// Creates a new planner.
+	///
+	/// # Arguments
+	///
+	/// * `shovel` - The shovel to plan for.
+	#[must_use]
+	pub fn new(shovel: &'s Shovel) -> Self {
+		Self { shovel }
+	}
+
+	/// Plans the installation of apps and their dependencies.
+	/// Apps that are already installed are skipped, unless a newer version of a requested app is available.
+	///
+	/// # Arguments
+	///
+	/// * `names` - The apps to install.
+	///
+	/// # Errors
+	///
+	/// [`Error::Depends`] is returned if the dependencies of an app cannot be resolved.
+	///
+	/// [`Error::App`] is returned if an installed app cannot be read.
+	///
+	/// [`Error::Cache`] is returned if the cache cannot be read.
+	///
+	/// [`Error::Url`] is returned if an app's URL is invalid.
+	///
+	/// [`Error::Depends`]: crate::error::Error::Depends
+	/// [`Error::App`]: crate::error::Error::App
+	/// [`Error::Cache`]: crate::error::Error::Cache
+	/// [`Error::Url`]: crate::error::Error::Url
+	pub fn install(&self, names: &[Name]) -> Result<Plan> {
+		let resolver = Resolver::new(&self.shovel.buckets);
+
+		let mut seen = HashSet::new();
+		let mut steps = vec![];
+
+		for name in names {
+			let resolution = resolver.resolve(name)?;
+
+			for resolved in resolution.order {
+				if !seen.insert(format!("{}/{}", resolved.bucket, resolved.name)) {
+					continue;
+				}
+
+				let installed = self.installed_version(&resolved.name)?;
+
+				let action = match installed {
+					None => Action::Install,
+					Some(from)
+						if resolved.kind == Kind::Requested
+							&& from != resolved.manifest.version =>
+					{
+						Action::Update { from }
+					}
+					Some(_) => Action::Skip,
+				};
+
+				let mut step = self.install_step(
+					resolved.bucket,
+					resolved.name,
+					&resolved.manifest,
+					resolved.kind,
+					action,
+				)?;
+
+				step.source = resolved.source;
+
+				if step.action != Action::Skip {
+					step.suggestions = self.shovel.apps.suggestions(&resolved.manifest)?;
+				}
+
+				steps.push(step);
+			}
+		}
+
+		// Apps installed by the plan provide features too, so they are not suggested again.
+		let planned: HashSet<String> = steps.iter().map(|step| step.name.clone()).collect();
+
+		for suggestion in steps.iter_mut().flat_map(|step| &mut step.suggestions) {
+			if suggestion.installed.is_none() {
+				suggestion.installed = suggestion
+					.apps
+					.iter()
+					.find(|app| planned.contains(Name::new((*app).clone()).manifest()))
+					.cloned();
+			}
+		}
+
+		Ok(Plan {
+			command: Command::Install,
+			steps,
+			failed: vec![],
+		})
+	}
+
+	/// Plans the uninstallation of apps. Dependencies are not uninstalled.
+	///
+	/// # Arguments
+	///
+	/// * `names` - The apps to uninstall.
+	///
+	/// # Errors
+	///
+	/// If an app is not installed or cannot be read, [`Error::App`] is returned.
+	///
+	/// [`Error::App`]: crate::error::Error::App
+	pub fn uninstall(&self, names: &[&str]) -> Result<Plan> {
+		let steps: Result<Vec<_>> = names.iter().map(|name| self.uninstall_step(name)).collect();
+
+		Ok(Plan {
+			command: Command::Uninstall,
+			steps: steps?,
+			failed: vec![],
+		})
+	}
+
+	/// Plans the update of installed apps to the latest version in their bucket.
+	///
+	/// An app that cannot be planned, i.e., because it cannot be read or its bucket was removed,
+	/// is added to [`Plan::failed`] instead, so the other apps are still planned.
+	///
+	/// # Arguments
+	///
+	/// * `names` - The apps to update. If empty, all installed apps are checked and only outdated apps are included.
+	/// * `buckets` - Only apps installed from these buckets are included. If empty, apps from every bucket are.
+	///
+	/// # Errors
+	///
+	/// [`Error::App`] is returned if the installed apps cannot be listed.
+	///
+	/// [`Error::App`]: crate::error::Error::App
+	pub fn update(&self, names: &[&str], buckets: &[String]) -> Result<Plan> {
+		let all = names.is_empty();
+
+		let names: Vec<String> = if all {
+			self.shovel.apps.each()?.map(|(name, _)| name).collect()
+		} else {
+			names.iter().map(|&name| name.to_owned()).collect()
+		};
+
+		let mut steps = vec![];
+		let mut failed = vec![];
+
+		for name in names {
+			match self.update_step(&name, all, buckets) {
+				Ok(Some(step)) => steps.push(step),
+				Ok(None) => {}
+				Err(err) => failed.push(Failed {
+					name,
+					error: err.to_string(),
+				}),
+			}
+		}
+
+		Ok(Plan {
+			command: Command::Update,
+			steps,
+			failed,
+		})
+	}
+
+	/// Plans the update of an installed app.
+	/// None is returned if the app is not from one of `buckets`, or if it is up-to-date and `all` is set.
+	fn update_step(&self, name: &str, all: bool, buckets: &[String]) -> Result<Option<Step>> {
+		let app = self.shovel.apps.open_current(name)?;
+		let bucket = app.metadata()?.bucket;
+
+		if !buckets.is_empty() && !buckets.contains(&bucket) {
+			return Ok(None);
+		}
+
+		// Apps installed before buckets were recorded cannot be traced back to one.
+		if bucket.is_empty() {
+			return Err(bucket::Error::NotFound.into());
+		}
+
+		let installed = app.manifest()?.version;
+
+		let (_, item) = self
+			.shovel
+			.buckets
+			.manifest(&Name::new(format!("{bucket}/{name}")))?;
+		let manifest = item.manifest?;
+
+		let action = if installed == manifest.version {
+			if all {
+				return Ok(None);
+			}
+
+			Action::Skip
+		} else {
+			Action::Update { from: installed }
+		};
+
+		let step =
+			self.install_step(bucket, name.to_owned(), &manifest, Kind::Requested, action)?;
+
+		Ok(Some(step))
+	}
+
+	fn installed_version(&self, name: &str) -> Result<Option<String>> {
+		match self.shovel.apps.open_current(name) {
+			Ok(app) => Ok(Some(app.manifest()?.version)),
+			Err(app::Error::NotFound { .. }) => Ok(None),
+			Err(err) => Err(err.into()),
+		}
+	}
+
+	fn install_step(
+		&self,
+		bucket: String,
+		name: String,
+		manifest: &Manifest,
+		kind: Kind,
+		action: Action,
+	) -> Result<Step> {
+		let mut step = Step::new(bucket, name, manifest, kind, action);
+
+		let shovel = self.shovel;
+		let arch = step.arch;
+
+		step.dir = shovel.apps.path(&step.name, &step.version);
+
+		if step.action == Action::Skip {
+			return Ok(step);
+		}
+
+		let urls = manifest.url(arch).unwrap_or_default();
+		let extract_dirs = manifest.extract_dir(arch).unwrap_or_default();
+		let extract_tos = manifest.extract_to(arch).unwrap_or_default();
+
+		for (i, url) in urls.iter().enumerate() {
+			let key = Key {
+				name: step.name.clone(),
+				version: step.version.clone(),
+				url: url.clone(),
+			};
+
+			step.downloads.push(Download {
+				url: url.clone(),
+				path: shovel.cache.path(&key),
+				cached: shovel.cache.exists(&key)?,
+			});
+
+			let file = util::url_to_filename(url)?;
+			let to = match extract_tos.get(i) {
+				Some(extract_to) => step.dir.join(extract_to),
+				None => step.dir.clone(),
+			};
+
+			step.extract.push(Extract {
+				archive: is_archive(&file),
+				file,
+				extract_dir: extract_dirs.get(i).cloned(),
+				to,
+			});
+		}
+
+		self.link_steps(&mut step, manifest, true);
+
+		step.hooks = [Script::PreInstall, Script::Install, Script::PostInstall]
+			.into_iter()
+			.filter(|script| script.lines(manifest, arch).is_some_and(|l| !l.is_empty()))
+			.collect();
+
+		Ok(step)
+	}
+
+	fn uninstall_step(&self, name: &str) -> Result<Step> {
+		let app = self.shovel.apps.open_current(name)?;
+		let manifest = app.manifest()?;
+
+		// The bucket is informational, so a missing or corrupt install.json is not fatal.
+		let bucket = app.metadata().map(|m| m.bucket).unwrap_or_default();
+
+		let mut step = Step::new(
+			bucket,
+			name.to_owned(),
+			&manifest,
+			Kind::Requested,
+			Action::Uninstall,
+		);
+
+		let arch = step.arch;
+
+		step.dir = self.shovel.apps.path(name, &step.version);
+
+		self.link_steps(&mut step, &manifest, false);
+
+		step.hooks = [
+			Script::PreUninstall,
+			Script::Uninstall,
+			Script::PostUninstall,
+		]
+		.into_iter()
+		.filter(|script| script.lines(&manifest, arch).is_some_and(|l| !l.is_empty()))
+		.collect();
+
+		Ok(step)
+	}
+
+	/// Adds the shims, persisted data, environment changes and modules that are linked to the `current` directory.
+	fn link_steps(&self, step: &mut Step, manifest: &Manifest, install: bool) {
+		let shovel = self.shovel;
+		let config = shovel.config();
+		let arch = step.arch;
+
+		let current = shovel.apps.current_path(&step.name);
+		let persist_dir = shovel.persist.path(&step.name);
+
+		step.shims = match manifest.bin(arch) {
+			Some(Bins::One(path)) => vec![Bin::Path(path.clone())],
+			Some(Bins::Many(bins)) => bins.iter().map(Self::clone_bin).collect(),
+			None => vec![],
+		}
+		.into_iter()
+		.map(|bin| {
+			let (executable, name, arguments) = match bin {
+				Bin::Path(path) => {
+					let name = Path::new(&path)
+						.file_stem()
+						.map(util::osstr_to_string)
+						.unwrap_or_default();
+
+					(path, name, vec![])
+				}
+				Bin::Shim(shim) => (shim.executable, shim.name, shim.arguments),
+			};
+
+			Shim {
+				path: config.shim_dir().join(&name),
+				name,
+				target: current.join(executable),
+				arguments,
+			}
+		})
+		.collect();
+
+		step.persist = manifest
+			.persist
+			.as_deref()
+			.unwrap_or_default()
+			.iter()
+			.map(|entry| {
+				let (source, target) = match entry {
+					PersistEntry::Path(path) => (path, path),
+					PersistEntry::Extended(rename) => (&rename.0, &rename.1),
+				};
+
+				Persist {
+					source: current.join(source),
+					target: persist_dir.join(target),
+				}
+			})
+			.collect();
+
+		let current_str = util::path_to_string(&current);
+		let persist_str = util::path_to_string(&persist_dir);
+
+		// Environment variables may reference the app's directories.
+		let lookup = |var: &str| match var {
+			"dir" => Some(current_str.as_str()),
+			"persist_dir" => Some(persist_str.as_str()),
+			"version" => Some(step.version.as_str()),
+			"app" => Some(step.name.as_str()),
+			_ => None,
+		};
+
+		let mut env = vec![];
+
+		for path in manifest.env_add_path(arch).unwrap_or_default() {
+			let name = PATH.to_owned();
+			let entry = util::path_to_string(current.join(path));
+
+			env.push(if install {
+				EnvChange::AddPath { name, entry }
+			} else {
+				EnvChange::RemovePath { name, entry }
+			});
+		}
+
+		let mut vars: Vec<_> = manifest.env_set(arch).into_iter().flatten().collect();
+		vars.sort();
+
+		for (name, value) in vars {
+			let name = name.clone();
+
+			env.push(if install {
+				let value = powershell::format(value, lookup).into_owned();
+
+				EnvChange::Set { name, value }
+			} else {
+				EnvChange::Unset { name }
+			});
+		}
+
+		if let Some(module) = manifest.psmodule.as_ref() {
+			// The module path is only ever added to, as other modules may still be installed.
+			if install {
+				env.push(EnvChange::AddPath {
+					name: psmodule::PSMODULEPATH.to_owned(),
+					entry: util::path_to_string(config.module_dir()),
+				});
+			}
+
+			step.module = Some(shovel.modules.path(&module.name));
+		}
+
+		step.env = env;
+	}
+
+	fn clone_bin(bin: &Bin) -> Bin {
+		match bin {
+			Bin::Path(path) => Bin::Path(path.clone()),
+			Bin::Shim(shim) => Bin::Shim(shim.clone()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use super::*;
+	use crate::config::Config;
+	use crate::test;
+
+	fn shovel(temp_dir: &tempfile::TempDir, manifests: &[(&str, &str)]) -> Shovel {
+		let config = Config {
+			install_dir: util::path_to_string(temp_dir.path()),
+			..Config::default()
+		};
+
+		let shovel = Shovel::new(config).unwrap();
+		test::create_bucket(&shovel.config().bucket_dir().join("main"), manifests);
+
+		shovel
+	}
+
+	#[test]
+	fn install() {
+		let temp_dir = tempfile::tempdir().unwrap();
+
+		let app = test::manifest_json(
+			"2.0",
+			r#""depends": "lib", "bin": [["app.exe", "a", "--flag"], "tool.exe"], "env_set": {"APP_HOME": "$dir"}, "post_install": "echo done""#,
+		);
+		let lib = test::manifest_json("1.0", "");
+
+		let shovel = shovel(&temp_dir, &[("app", &app), ("lib", &lib)]);
+
+		let plan = Planner::new(&shovel)
+			.install(&[Name::new("app".to_owned())])
+			.unwrap();
+
+		let names: Vec<_> = plan.steps.iter().map(|s| s.name.as_str()).collect();
+		assert_eq!(names, ["lib", "app"]);
+
+		let lib = &plan.steps[0];
+		assert_eq!(lib.kind, Kind::Depends);
+		assert_eq!(lib.action, Action::Install);
+		assert!(lib.extract[0].archive);
+		assert!(!lib.downloads[0].cached);
+
+		let app = &plan.steps[1];
+		let shims: Vec<_> = app.shims.iter().map(|s| s.name.as_str()).collect();
+		assert_eq!(shims, ["a", "tool"]);
+		assert_eq!(app.shims[0].arguments, ["--flag"]);
+		assert_eq!(app.hooks, [Script::PostInstall]);
+		assert_eq!(
+			app.env,
+			[EnvChange::Set {
+				name: "APP_HOME".to_owned(),
+				value: util::path_to_string(shovel.apps.current_path("app")),
+			}]
+		);
+	}
+
+	#[test]
+	fn skip_installed() {
+		let temp_dir = tempfile::tempdir().unwrap();
+
+		let app = test::manifest_json("2.0", r#""depends": "lib""#);
+		let lib = test::manifest_json("1.0", "");
+
+		let shovel = shovel(&temp_dir, &[("app", &app), ("lib", &lib)]);
+
+		let current = shovel.apps.current_path("lib");
+		fs::create_dir_all(&current).unwrap();
+		fs::write(current.join("manifest.json"), &lib).unwrap();
+
+		let plan = Planner::new(&shovel)
+			.install(&[Name::new("app".to_owned())])
+			.unwrap();
+
+		assert_eq!(plan.steps[0].action, Action::Skip);
+		assert_eq!(plan.steps[1].action, Action::Install);
+	}
+
+	/// Installs an app from a manifest, recording the bucket it came from.
+	fn install_app(shovel: &Shovel, name: &str, json: &str, bucket: &str) {
+		let current = shovel.apps.current_path(name);
+		fs::create_dir_all(&current).unwrap();
+		fs::write(current.join("manifest.json"), json).unwrap();
+
+		let metadata = app::Metadata {
+			bucket: bucket.to_owned(),
+			..Default::default()
+		};
+		app::App::open(current).set_metadata(&metadata).unwrap();
+	}
+
+	#[test]
+	fn update() {
+		let temp_dir = tempfile::tempdir().unwrap();
+
+		let v1 = test::manifest_json("1.0", "");
+		let v2 = test::manifest_json("2.0", "");
+
+		let shovel = shovel(&temp_dir, &[("app", &v2), ("lib", &v1)]);
+
+		install_app(&shovel, "app", &v1, "main");
+		install_app(&shovel, "lib", &v1, "main");
+		install_app(&shovel, "orphan", &v1, "");
+		install_app(&shovel, "gone", &v1, "removed");
+
+		// Apps that cannot be planned do not stop the others from being planned.
+		let plan = Planner::new(&shovel).update(&[], &[]).unwrap();
+
+		let names: Vec<_> = plan.steps.iter().map(|s| s.name.as_str()).collect();
+		assert_eq!(names, ["app"]);
+		assert_eq!(
+			plan.steps[0].action,
+			Action::Update {
+				from: "1.0".to_owned()
+			}
+		);
+
+		let mut failed: Vec<_> = plan.failed.iter().map(|f| f.name.as_str()).collect();
+		failed.sort_unstable();
+		assert_eq!(failed, ["gone", "orphan"]);
+
+		// Only apps from the given buckets are planned.
+		let plan = Planner::new(&shovel)
+			.update(&[], &["removed".to_owned()])
+			.unwrap();
+
+		assert!(plan.steps.is_empty());
+		assert_eq!(plan.failed[0].name, "gone");
+	}
+
+	#[test]
+	fn suggestions() {
+		let temp_dir = tempfile::tempdir().unwrap();
+
+		let app = test::manifest_json(
+			"1.0",
+			r#""depends": "lib", "suggest": {"Runtime": ["main/lib"], "JDK": ["openjdk"], "Empty": []}"#,
+		);
+		let lib = test::manifest_json("1.0", "");
+
+		let shovel = shovel(&temp_dir, &[("app", &app), ("lib", &lib)]);
+
+		let plan = Planner::new(&shovel)
+			.install(&[Name::new("app".to_owned())])
+			.unwrap();
+
+		let missing: Vec<_> = plan.steps[1]
+			.suggestions
+			.iter()
+			.filter(|s| !s.is_satisfied())
+			.map(|s| s.feature.as_str())
+			.collect();
+
+		// The runtime is installed as a dependency in the same plan.
+		assert_eq!(missing, ["Empty", "JDK"]);
+	}
+}
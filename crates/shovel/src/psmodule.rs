@@ -0,0 +1,198 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::env;
+use crate::env::Environment;
+use crate::manifest::PSModule;
+use crate::util;
+
+/// The environment variable PowerShell searches for modules.
+pub const PSMODULEPATH: &str = "PSModulePath";
+
+/// A PowerShell module error.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// A module's name does not match any file in the app directory.
+	#[error("Module {name} not found in app directory {}", dir.display())]
+	NameMismatch { name: String, dir: PathBuf },
+
+	/// An environment error occurred.
+	#[error(transparent)]
+	Env(#[from] env::Error),
+
+	/// An IO error occurred.
+	#[error(transparent)]
+	Io(#[from] io::Error),
+}
+
+/// A PowerShell module result.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A PowerShell module manager.
+///
+/// Apps installed as modules are linked into the module directory by the module's name,
+/// and the module directory is registered in `PSModulePath`:
+/// * `dir`
+///   * `module1` -> `app1`
+///   * `module2` -> `app2`
+///   * `...`
+pub struct Modules {
+	dir: PathBuf,
+}
+
+impl Modules {
+	/// Returns a new module manager.
+	///
+	/// # Arguments
+	///
+	/// * `dir` - The directory where modules are linked.
+	pub fn new<P>(dir: P) -> Self
+	where
+		P: AsRef<Path>,
+	{
+		Self {
+			dir: dir.as_ref().to_owned(),
+		}
+	}
+
+	/// Returns the path to a module's link.
+	///
+	/// # Arguments
+	///
+	/// * `name` - The module's name.
+	#[must_use]
+	pub fn path(&self, name: &str) -> PathBuf {
+		self.dir.join(name)
+	}
+
+	/// Installs an app as a module and returns the path to the module's link.
+	/// If the module is already linked, the link is replaced.
+	///
+	/// # Arguments
+	///
+	/// * `app_dir` - The app directory to link to.
+	/// * `module` - The module to install, from the app's manifest.
+	/// * `env` - The environment to register the module directory in.
+	///
+	/// # Errors
+	///
+	/// [`Error::NameMismatch`] is returned if no file in `app_dir` has the module's name, ignoring the extension.
+	///
+	/// [`Error::Env`] is returned if `PSModulePath` cannot be updated.
+	///
+	/// [`Error::Io`] is returned if the module cannot be linked.
+	pub fn install<P, E>(&self, app_dir: P, module: &PSModule, env: &E) -> Result<PathBuf>
+	where
+		P: AsRef<Path>,
+		E: Environment,
+	{
+		let app_dir = app_dir.as_ref();
+
+		if !Self::has_module(app_dir, &module.name)? {
+			return Err(Error::NameMismatch {
+				name: module.name.clone(),
+				dir: app_dir.to_owned(),
+			});
+		}
+
+		fs::create_dir_all(&self.dir)?;
+
+		env.add_path(PSMODULEPATH, &util::path_to_string(&self.dir))?;
+
+		let link = self.path(&module.name);
+
+		// Replace any existing link, i.e., from an older version of the app.
+		if link.symlink_metadata().is_ok() {
+			util::unlink_dir(&link)?;
+		}
+
+		util::link_dir(app_dir, &link)?;
+
+		Ok(link)
+	}
+
+	/// Uninstalls a module by removing its link.
+	/// If the module is not linked, this is a no-op.
+	///
+	/// The module directory is kept in `PSModulePath`, as other modules may still be installed.
+	///
+	/// # Arguments
+	///
+	/// * `module` - The module to uninstall, from the app's manifest.
+	///
+	/// # Errors
+	///
+	/// If the link cannot be removed, [`Error::Io`] is returned.
+	pub fn uninstall(&self, module: &PSModule) -> Result<()> {
+		let link = self.path(&module.name);
+
+		if link.symlink_metadata().is_ok() {
+			util::unlink_dir(link)?;
+		}
+
+		Ok(())
+	}
+
+	/// Checks if a directory has a file with the module's name, i.e., `name.psd1` or `name.psm1`.
+	fn has_module(dir: &Path, name: &str) -> io::Result<bool> {
+		for entry in fs::read_dir(dir)? {
+			let path = entry?.path();
+
+			if path.is_file() && path.file_stem().is_some_and(|stem| stem == name) {
+				return Ok(true);
+			}
+		}
+
+		Ok(false)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn module(name: &str) -> PSModule {
+		PSModule {
+			name: name.to_owned(),
+		}
+	}
+
+	#[test]
+	fn install() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let app_dir = temp_dir.path().join("app");
+		fs::create_dir(&app_dir).unwrap();
+		fs::write(app_dir.join("Example.psd1"), "@{}").unwrap();
+
+		let modules = Modules::new(temp_dir.path().join("modules"));
+		let env = env::Memory::new();
+
+		let link = modules.install(&app_dir, &module("Example"), &env).unwrap();
+
+		assert!(link.join("Example.psd1").exists());
+		assert!(env
+			.contains_path(PSMODULEPATH, &util::path_to_string(&modules.dir))
+			.unwrap());
+
+		modules.uninstall(&module("Example")).unwrap();
+
+		assert!(link.symlink_metadata().is_err());
+		assert!(app_dir.join("Example.psd1").exists());
+	}
+
+	#[test]
+	fn name_mismatch() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		fs::write(temp_dir.path().join("Other.psm1"), "").unwrap();
+
+		let modules = Modules::new(temp_dir.path().join("modules"));
+		let env = env::Memory::new();
+
+		let result = modules.install(temp_dir.path(), &module("Example"), &env);
+
+		assert!(matches!(result, Err(Error::NameMismatch { .. })));
+		assert_eq!(env.get(PSMODULEPATH).unwrap(), None);
+	}
+}
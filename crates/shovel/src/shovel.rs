@@ -1,23 +1,37 @@
 use std::fs;
 use std::io;
 use std::io::prelude::*;
+use std::path::PathBuf;
 use std::vec;
 
 use git2::build::CheckoutBuilder;
 use git2::FetchOptions;
 use rayon::prelude::*;
 
+use crate::app::App;
 use crate::app::Apps;
+use crate::app::Metadata;
+use crate::bucket;
 use crate::bucket::Bucket;
 use crate::bucket::Buckets;
+use crate::bucket::Credentials;
+use crate::bucket::Kind;
+use crate::bucket::KnownBuckets;
 use crate::bucket::Name;
+use crate::bucket::Pull;
 use crate::cache::Cache;
+use crate::config;
 use crate::config::Config;
+use crate::depends::Resolved;
+use crate::env;
+use crate::env::Environment;
+use crate::error::Error;
 use crate::error::Result;
 use crate::persist::Persist;
+use crate::psmodule::Modules;
+use crate::util;
 
 /// A high-level interface to Shovel.
-#[allow(dead_code)]
 pub struct Shovel {
 	/// The app manager.
 	pub apps: Apps,
@@ -31,6 +45,9 @@ pub struct Shovel {
 	/// The data persistence manager.
 	pub persist: Persist,
 
+	/// The PowerShell module manager.
+	pub modules: Modules,
+
 	config: Config,
 }
 
@@ -47,11 +64,19 @@ impl Shovel {
 	///
 	/// [`Error::Io`]: crate::error::Error::Io
 	pub fn new(config: Config) -> Result<Self> {
-		let install_dir = config.install_dir();
-		let app_dir = config.app_dir();
 		let bucket_dir = config.bucket_dir();
 		let cache_dir = config.cache_dir();
+
+		Self::with_shared_dirs(config, bucket_dir, cache_dir)
+	}
+
+	/// Creates a new shovel whose buckets and cache are stored in the given directories,
+	/// instead of the installation directory.
+	fn with_shared_dirs(config: Config, bucket_dir: PathBuf, cache_dir: PathBuf) -> Result<Self> {
+		let install_dir = config.install_dir();
+		let app_dir = config.app_dir();
 		let persist_dir = config.persist_dir();
+		let module_dir = config.module_dir();
 
 		// Ensure the installation directory, and all sub-directories, exist.
 		for dir in [
@@ -60,19 +85,62 @@ impl Shovel {
 			&bucket_dir,
 			&cache_dir,
 			&persist_dir,
+			&module_dir,
 		] {
 			fs::create_dir_all(dir)?;
 		}
 
 		Ok(Shovel {
 			apps: Apps::new(app_dir),
-			buckets: Buckets::new(bucket_dir),
+			buckets: Buckets::new(bucket_dir).with_priority(config.bucket_priority.clone()),
 			cache: Cache::new(cache_dir),
 			persist: Persist::new(persist_dir),
+			modules: Modules::new(module_dir),
 			config,
 		})
 	}
 
+	/// Creates a new shovel for the global installation directory at [`global_install_dir`].
+	///
+	/// Like Scoop, buckets and the cache are shared with this shovel, so only apps and their data are global.
+	/// No bucket or cache directories are created in the global installation directory.
+	///
+	/// # Errors
+	///
+	/// See [`new`].
+	///
+	/// [`global_install_dir`]: crate::config::global_install_dir
+	/// [`new`]: Shovel::new
+	pub fn global(&self) -> Result<Self> {
+		Self::with_shared_dirs(
+			self.global_config(),
+			self.config.bucket_dir(),
+			self.config.cache_dir(),
+		)
+	}
+
+	/// Returns the config for the global installation directory, which shares the rest of this shovel's config.
+	fn global_config(&self) -> Config {
+		Config {
+			install_dir: util::path_to_string(config::global_install_dir()),
+			bucket_priority: self.config.bucket_priority.clone(),
+			known_buckets: self.config.known_buckets.clone(),
+			known_buckets_file: self.config.known_buckets_file.clone(),
+		}
+	}
+
+	/// Returns the known buckets, which can be added by name alone.
+	/// The built-in list is merged with the entries from the config, see [`KnownBuckets::load`].
+	///
+	/// # Errors
+	///
+	/// [`Error::Bucket`] is returned if the known buckets file cannot be read or parsed.
+	///
+	/// [`Error::Bucket`]: crate::error::Error::Bucket
+	pub fn known_buckets(&self) -> Result<KnownBuckets> {
+		Ok(KnownBuckets::load(&self.config)?)
+	}
+
 	/// Copies the contents of a manifest to a writer specified by `options`.
 	/// If the manifest exists and was copied, `Ok(true)` is returned, otherwise `Ok(false)`.
 	///
@@ -99,56 +167,329 @@ impl Shovel {
 		Ok(())
 	}
 
-	/// Updates all buckets by pulling new changes.
+	/// Returns the config in use.
+	#[must_use]
+	pub fn config(&self) -> &Config {
+		&self.config
+	}
+
+	/// Returns the system environment for the installation directory.
+	/// Global installations use machine-wide variables, otherwise user variables are used.
+	#[must_use]
+	pub fn env(&self) -> env::System {
+		env::System::new(env::Scope::of(&self.config))
+	}
+
+	/// Links an app whose files are already in its version directory, see [`Apps::link`],
+	/// and installs it as a PowerShell module if its manifest has `psmodule`.
+	/// The app's metadata records the bucket and where the manifest came from, i.e., an older commit for `app@version`.
+	/// The module is linked to the app's `current` directory, so it does not need to be re-linked on updates.
+	///
+	/// This is the last stage of installing an app. Downloading and extracting its files,
+	/// as well as shims, persisted data and hooks, are not handled here.
 	///
 	/// # Arguments
 	///
-	/// * `options` - The update options. To specify the defaults, use [`&UpdateOptions::Default`].
+	/// * `resolved` - The app to link, as resolved for an install plan.
+	/// * `env` - The environment to register modules in. To use the system environment, pass [`env`].
 	///
-	/// [`&UpdateOptions::Default`]: crate::shovel::UpdateOptions::default
+	/// # Errors
+	///
+	/// [`Error::App`] is returned if the app's version directory does not exist, or the app cannot be linked.
+	///
+	/// [`Error::Module`] is returned if the module failed to install. The app stays linked.
+	///
+	/// [`Apps::link`]: crate::app::Apps::link
+	/// [`env`]: Shovel::env
+	/// [`Error::App`]: crate::error::Error::App
+	/// [`Error::Module`]: crate::error::Error::Module
+	pub fn link_app<E: Environment>(&self, resolved: &Resolved, env: &E) -> Result<App> {
+		let manifest = &resolved.manifest;
+
+		let metadata = Metadata {
+			architecture: manifest.compatible(),
+			bucket: resolved.bucket.clone(),
+			source: Some(resolved.source.clone()),
+		};
+
+		let app = self.apps.link(&resolved.name, manifest, &metadata)?;
+
+		if let Some(module) = manifest.psmodule.as_ref() {
+			self.modules
+				.install(self.apps.current_path(&resolved.name), module, env)?;
+		}
+
+		Ok(app)
+	}
+
+	/// Unlinks the current version of an app, see [`Apps::unlink`],
+	/// and uninstalls it as a PowerShell module if its manifest has `psmodule`.
+	///
+	/// # Arguments
+	///
+	/// * `name` - The app's name.
 	///
 	/// # Errors
 	///
-	/// [`Error::Bucket`] is returned if:
-	/// * A bucket cannot be read.
-	/// * A bucket does not have a HEAD commit.
-	/// * A bucket failed to pull.
+	/// [`Error::App`] is returned if the app is not installed, its manifest cannot be read, or it cannot be unlinked.
 	///
-	/// [`Error::Bucket`]: crate::error::Error::Bucket
-	pub fn update(&self, options: &UpdateOptions) -> Result<Updates> {
-		let buckets = self.buckets.iter()?;
+	/// [`Error::Module`] is returned if the module failed to uninstall. The app stays linked.
+	///
+	/// [`Apps::unlink`]: crate::app::Apps::unlink
+	/// [`Error::App`]: crate::error::Error::App
+	/// [`Error::Module`]: crate::error::Error::Module
+	pub fn unlink_app(&self, name: &str) -> Result<()> {
+		let manifest = self.apps.open_current(name)?.manifest()?;
+
+		if let Some(module) = manifest.psmodule.as_ref() {
+			self.modules.uninstall(module)?;
+		}
 
-		let updated: Result<Vec<_>> = buckets
-			.par_bridge()
-			.map(|res| {
-				let mut bucket = res?;
+		self.apps.unlink(name)?;
 
-				// Save the original HEAD commit before pulling.
-				let head = bucket.commit()?.id();
+		Ok(())
+	}
 
-				let mut fetch_options = match &options.fetch_options {
-					Some(factory) => factory(&bucket),
-					None => FetchOptions::new(),
-				};
+	/// Returns the app managers that may have apps installed from this shovel's buckets.
+	/// Buckets are shared with the global installation directory, so its apps are included if it exists.
+	fn bucket_users(&self) -> Vec<Apps> {
+		let app_dir = self.config.app_dir();
+		let global_dir = self.global_config().app_dir();
+
+		let mut users = vec![Apps::new(&app_dir)];
 
-				let mut checkout_builder = match &options.checkout_builder {
-					Some(factory) => factory(&bucket),
-					None => CheckoutBuilder::new(),
+		if global_dir != app_dir && global_dir.is_dir() {
+			users.push(Apps::new(global_dir));
+		}
+
+		users
+	}
+
+	/// Returns the names of installed apps whose current version came from a bucket, sorted by name.
+	/// Apps in the global installation directory are included, as buckets are shared with it.
+	///
+	/// # Arguments
+	///
+	/// * `bucket` - The name of the bucket.
+	///
+	/// # Errors
+	///
+	/// If an apps directory cannot be read, [`Error::App`] is returned.
+	///
+	/// [`Error::App`]: crate::error::Error::App
+	pub fn bucket_apps(&self, bucket: &str) -> Result<Vec<String>> {
+		let mut names = vec![];
+
+		for apps in self.bucket_users() {
+			for (name, app) in apps.each()? {
+				// Apps that are not fully installed do not have metadata to check.
+				let Ok(metadata) = app.and_then(|app| app.metadata()) else {
+					continue;
 				};
 
-				// According to the git2 pull example, not including this option causes the working directory to not update.
-				checkout_builder.force();
+				if metadata.bucket == bucket {
+					names.push(name);
+				}
+			}
+		}
+
+		names.sort();
+		names.dedup();
+
+		Ok(names)
+	}
+
+	/// Removes a bucket, unless installed apps came from it. See [`bucket_apps`].
+	///
+	/// # Arguments
+	///
+	/// * `name` - The name of the bucket.
+	/// * `force` - Whether to remove the bucket even if installed apps came from it.
+	///
+	/// # Errors
+	///
+	/// [`Error::Bucket`] is returned when any of these occur:
+	/// * The bucket does not exist.
+	/// * Installed apps came from the bucket, and `force` is false.
+	/// * The bucket's directory cannot be removed.
+	///
+	/// [`bucket_apps`]: Shovel::bucket_apps
+	/// [`Error::Bucket`]: crate::error::Error::Bucket
+	pub fn remove_bucket(&self, name: &str, force: bool) -> Result<()> {
+		if !self.buckets.path(name).try_exists()? {
+			return Err(bucket::Error::NotFound.into());
+		}
+
+		if !force {
+			let apps = self.bucket_apps(name)?;
+
+			if !apps.is_empty() {
+				return Err(bucket::Error::InUse(apps).into());
+			}
+		}
+
+		self.buckets.remove(name)?;
+
+		Ok(())
+	}
+
+	/// Renames a bucket, and rewrites the bucket in the metadata of installed apps that came from it,
+	/// so they are still updated from the bucket. Every installed version is rewritten, not just the current one.
+	///
+	/// Returns the names of the apps that were rewritten, sorted by name.
+	///
+	/// The bucket priority in the config is not rewritten, see [`Buckets::rename`].
+	///
+	/// # Arguments
+	///
+	/// * `from` - The name of the bucket.
+	/// * `to` - The new name of the bucket.
+	///
+	/// # Errors
+	///
+	/// If the bucket does not exist, or a bucket named `to` exists, [`Error::Bucket`] is returned.
+	///
+	/// If an app's metadata cannot be rewritten, [`Error::App`] is returned.
+	///
+	/// On any error, the bucket and the apps already rewritten are renamed back.
+	///
+	/// [`Buckets::rename`]: crate::bucket::Buckets::rename
+	/// [`Error::Bucket`]: crate::error::Error::Bucket
+	/// [`Error::App`]: crate::error::Error::App
+	pub fn rename_bucket(&self, from: &str, to: &str) -> Result<Vec<String>> {
+		self.buckets.rename(from, to)?;
+
+		let mut rewritten = vec![];
+
+		if let Err(err) = self.rewrite_bucket(from, to, &mut rewritten) {
+			// Undo everything, so the bucket and the apps from it still agree on its name.
+			// The original error is more useful than any error undoing, so those are ignored.
+			for (_, app) in &rewritten {
+				if let Ok(mut metadata) = app.metadata() {
+					from.clone_into(&mut metadata.bucket);
+					let _ = app.set_metadata(&metadata);
+				}
+			}
+
+			let _ = self.buckets.rename(to, from);
+
+			return Err(err);
+		}
+
+		let mut names: Vec<_> = rewritten.into_iter().map(|(name, _)| name).collect();
+
+		names.sort();
+		names.dedup();
 
-				bucket.pull(Some(&mut fetch_options), Some(&mut checkout_builder))?;
+		Ok(names)
+	}
+
+	/// Rewrites the bucket of every installed version from `from` to `to`, pushing each rewritten version.
+	fn rewrite_bucket(
+		&self,
+		from: &str,
+		to: &str,
+		rewritten: &mut Vec<(String, App)>,
+	) -> Result<()> {
+		for apps in self.bucket_users() {
+			for (name, _) in apps.each()? {
+				for app in apps.versions(&name)? {
+					let Ok(mut metadata) = app.metadata() else {
+						continue;
+					};
+
+					if metadata.bucket == from {
+						to.clone_into(&mut metadata.bucket);
+						app.set_metadata(&metadata)?;
+						rewritten.push((name.clone(), app));
+					}
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Updates buckets by pulling new changes.
+	///
+	/// Buckets are updated independently, so a bucket that fails to update does not stop the others.
+	///
+	/// # Arguments
+	///
+	/// * `options` - The update options. To specify the defaults, use [`&UpdateOptions::Default`].
+	///
+	/// [`&UpdateOptions::Default`]: crate::shovel::UpdateOptions::default
+	///
+	/// # Errors
+	///
+	/// If the directory containing the buckets cannot be read, [`Error::Bucket`] is returned.
+	/// Errors updating a bucket are returned as [`BucketUpdate::Failed`] instead.
+	///
+	/// [`Error::Bucket`]: crate::error::Error::Bucket
+	pub fn update(&self, options: &UpdateOptions) -> Result<Updates> {
+		let names = match &options.buckets {
+			Some(names) => names.clone(),
+			None => self.buckets.names()?,
+		};
+
+		// Collecting an indexed parallel iterator keeps the buckets in order.
+		let updated: Vec<_> = names
+			.into_par_iter()
+			.map(|name| {
+				let update = match self.update_bucket(&name, options) {
+					Ok(None) => BucketUpdate::Local,
+					Ok(Some(Pull::UpToDate)) => BucketUpdate::UpToDate,
+					Ok(Some(Pull::FastForward { from, to })) => BucketUpdate::Updated {
+						from,
+						to,
+						forced: false,
+					},
+					Ok(Some(Pull::Reset { from, to })) => BucketUpdate::Updated {
+						from,
+						to,
+						forced: true,
+					},
+					Err(err) => BucketUpdate::Failed(err),
+				};
 
-				Ok((bucket, head))
+				(name, update)
 			})
 			.collect();
 
 		Ok(Updates {
-			inner: updated?.into_iter(),
+			inner: updated.into_iter(),
 		})
 	}
+
+	/// Pulls a bucket, and updates its index if possible. If the bucket is a local directory, None is returned.
+	fn update_bucket(&self, name: &str, options: &UpdateOptions) -> Result<Option<Pull>> {
+		let bucket = self.buckets.open(name)?;
+
+		if bucket.kind() == Kind::Directory {
+			return Ok(None);
+		}
+
+		let mut fetch_options = match &options.fetch_options {
+			Some(factory) => factory(&bucket),
+			None => Credentials::from_env().into_fetch_options(),
+		};
+
+		let mut checkout_builder = match &options.checkout_builder {
+			Some(factory) => factory(&bucket),
+			None => CheckoutBuilder::new(),
+		};
+
+		// According to the git2 pull example, not including this option causes the working directory to not update.
+		checkout_builder.force();
+
+		let pull = bucket.pull(Some(&mut fetch_options), Some(&mut checkout_builder))?;
+
+		// Update the index while the changes are known, so searching afterwards is fast.
+		// The pull succeeded either way, and the next search rebuilds an outdated index, so errors are ignored.
+		let _ = bucket.index();
+
+		Ok(Some(pull))
+	}
 }
 
 type FetchOptionsFactory<'a> = dyn Fn(&Bucket) -> FetchOptions<'a> + Send + Sync + 'a;
@@ -159,6 +500,7 @@ type CheckoutBuilderFactory<'a> = dyn Fn(&Bucket) -> CheckoutBuilder<'a> + Send
 /// [`update`]: crate::shovel::Shovel::update
 #[derive(Default)]
 pub struct UpdateOptions<'a> {
+	buckets: Option<Vec<String>>,
 	fetch_options: Option<Box<FetchOptionsFactory<'a>>>,
 	checkout_builder: Option<Box<CheckoutBuilderFactory<'a>>>,
 }
@@ -170,9 +512,22 @@ impl<'a> UpdateOptions<'a> {
 		Self::default()
 	}
 
+	/// Sets the names of the buckets to update. By default, all buckets are updated.
+	///
+	/// Buckets that do not exist are reported as [`BucketUpdate::Failed`].
+	pub fn buckets<I, S>(&mut self, names: I) -> &mut Self
+	where
+		I: IntoIterator<Item = S>,
+		S: Into<String>,
+	{
+		self.buckets = Some(names.into_iter().map(Into::into).collect());
+		self
+	}
+
 	/// Sets the Git fetch options to use for a bucket.
 	///
 	/// `factory` takes a bucket and returns the fetch options for the bucket.
+	/// By default, the options only supply [`Credentials::from_env`].
 	pub fn fetch_options<F>(&mut self, factory: F) -> &mut Self
 	where
 		F: Fn(&Bucket) -> FetchOptions<'a> + Send + Sync + 'a,
@@ -193,14 +548,44 @@ impl<'a> UpdateOptions<'a> {
 	}
 }
 
-/// An iterator over updated buckets.
-/// Yields a two-tuple (bucket, commit) where commit is the HEAD commit before the update.
+/// The outcome of updating a bucket. See [`update`].
+///
+/// [`update`]: crate::shovel::Shovel::update
+#[derive(Debug)]
+pub enum BucketUpdate {
+	/// New commits were pulled.
+	Updated {
+		/// The HEAD commit before the update.
+		from: git2::Oid,
+
+		/// The HEAD commit after the update.
+		to: git2::Oid,
+
+		/// Whether the upstream was force-pushed, so `from` may not be an ancestor of `to`.
+		forced: bool,
+	},
+
+	/// The bucket was already up to date.
+	UpToDate,
+
+	/// The bucket is a local directory without Git, so there is nothing to pull.
+	/// See [`Kind::Directory`].
+	///
+	/// [`Kind::Directory`]: crate::bucket::Kind::Directory
+	Local,
+
+	/// The bucket failed to update.
+	Failed(Error),
+}
+
+/// An iterator over the outcome of updating each bucket, in priority order.
+/// Yields a two-tuple (name, outcome).
 pub struct Updates {
-	inner: vec::IntoIter<(Bucket, git2::Oid)>,
+	inner: vec::IntoIter<(String, BucketUpdate)>,
 }
 
 impl Iterator for Updates {
-	type Item = (Bucket, git2::Oid);
+	type Item = (String, BucketUpdate);
 
 	fn next(&mut self) -> Option<Self::Item> {
 		self.inner.next()
@@ -226,3 +611,208 @@ impl<W: Write> CatOptions<W> {
 		Self { name, writer }
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::app;
+	use crate::app::App;
+	use crate::bucket::Source;
+	use crate::depends;
+	use crate::manifest::Manifest;
+	use crate::manifest::PSModule;
+	use crate::psmodule;
+	use crate::test;
+
+	#[test]
+	fn update() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let config = Config {
+			install_dir: util::path_to_string(temp_dir.path().join("scoop")),
+			..Config::default()
+		};
+		let shovel = Shovel::new(config).unwrap();
+
+		let json = test::manifest_json("1.0", "");
+		let upstream_dir = temp_dir.path().join("upstream");
+		let upstream = test::create_bucket(&upstream_dir, &[("app", &json)]);
+
+		let url = util::path_to_string(&upstream_dir);
+		let main = shovel
+			.buckets
+			.add("main", &url, bucket::CloneOptions::new())
+			.unwrap();
+		let from = main.commit().unwrap().unwrap().id;
+
+		// A bucket without a remote cannot be pulled.
+		test::create_bucket(&shovel.buckets.path("local"), &[("app", &json)]);
+
+		let json = test::manifest_json("2.0", "");
+		let to = test::commit_manifests(&upstream, &[("app", &json)], "Update app");
+
+		let updates: Vec<_> = shovel.update(&UpdateOptions::new()).unwrap().collect();
+
+		assert!(matches!(
+			&updates[..],
+			[
+				(main, BucketUpdate::Updated { from: f, to: t, forced: false }),
+				(local, BucketUpdate::Failed(_)),
+			] if main == "main" && local == "local" && *f == from && *t == to
+		));
+
+		let mut options = UpdateOptions::new();
+		options.buckets(["main", "missing"]);
+
+		let updates: Vec<_> = shovel.update(&options).unwrap().collect();
+
+		assert!(matches!(
+			&updates[..],
+			[
+				(_, BucketUpdate::UpToDate),
+				(
+					_,
+					BucketUpdate::Failed(Error::Bucket(bucket::Error::NotFound))
+				),
+			]
+		));
+	}
+
+	#[test]
+	fn link_app() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let config = Config {
+			install_dir: util::path_to_string(temp_dir.path().join("scoop")),
+			..Config::default()
+		};
+		let shovel = Shovel::new(config).unwrap();
+		let env = env::Memory::new();
+
+		let dir = shovel.apps.path("app", "1.0");
+		fs::create_dir_all(&dir).unwrap();
+		fs::write(dir.join("Example.psd1"), "@{}").unwrap();
+
+		let resolved = Resolved {
+			bucket: "main".to_owned(),
+			name: "app".to_owned(),
+			kind: depends::Kind::Requested,
+			manifest: Manifest {
+				version: "1.0".to_owned(),
+				psmodule: Some(PSModule {
+					name: "Example".to_owned(),
+				}),
+				..Default::default()
+			},
+			source: Source::History {
+				commit: "0123456789abcdef0123456789abcdef01234567".to_owned(),
+			},
+		};
+
+		let app = shovel.link_app(&resolved, &env).unwrap();
+		let metadata = app.metadata().unwrap();
+
+		assert_eq!(metadata.bucket, "main");
+		assert_eq!(metadata.source, Some(resolved.source.clone()));
+		assert!(shovel
+			.apps
+			.current_path("app")
+			.join("Example.psd1")
+			.exists());
+		assert!(shovel.modules.path("Example").join("Example.psd1").exists());
+		assert!(env
+			.contains_path(
+				psmodule::PSMODULEPATH,
+				&util::path_to_string(shovel.config().module_dir())
+			)
+			.unwrap());
+
+		shovel.unlink_app("app").unwrap();
+
+		assert!(shovel.modules.path("Example").symlink_metadata().is_err());
+		assert!(shovel.apps.open_current("app").is_err());
+		assert!(dir.join("Example.psd1").exists());
+	}
+
+	/// Installs an app from a bucket, as far as metadata is concerned.
+	fn install(shovel: &Shovel, name: &str, versions: &[&str], bucket: &str) {
+		for version in versions {
+			let dir = shovel.apps.path(name, version);
+			fs::create_dir_all(&dir).unwrap();
+
+			let metadata = app::Metadata {
+				bucket: bucket.to_owned(),
+				..Default::default()
+			};
+			App::open(dir).set_metadata(&metadata).unwrap();
+		}
+
+		let current = shovel.apps.path(name, versions.last().unwrap());
+		util::link_dir(current, shovel.apps.current_path(name)).unwrap();
+	}
+
+	#[test]
+	fn remove_bucket() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let config = Config {
+			install_dir: util::path_to_string(temp_dir.path().join("scoop")),
+			..Config::default()
+		};
+		let shovel = Shovel::new(config).unwrap();
+
+		let json = test::manifest_json("1.0", "");
+		test::create_bucket(&shovel.buckets.path("used"), &[("app", &json)]);
+		test::create_bucket(&shovel.buckets.path("unused"), &[("lib", &json)]);
+		install(&shovel, "app", &["1.0"], "used");
+
+		assert_eq!(shovel.bucket_apps("used").unwrap(), ["app"]);
+
+		shovel.remove_bucket("unused", false).unwrap();
+		assert!(matches!(
+			shovel.remove_bucket("used", false),
+			Err(Error::Bucket(bucket::Error::InUse(apps))) if apps == ["app"]
+		));
+
+		shovel.remove_bucket("used", true).unwrap();
+		assert!(!shovel.buckets.path("used").exists());
+		assert!(matches!(
+			shovel.remove_bucket("used", true),
+			Err(Error::Bucket(bucket::Error::NotFound))
+		));
+	}
+
+	#[test]
+	fn rename_bucket() {
+		let temp_dir = tempfile::tempdir().unwrap();
+		let config = Config {
+			install_dir: util::path_to_string(temp_dir.path().join("scoop")),
+			..Config::default()
+		};
+		let shovel = Shovel::new(config).unwrap();
+
+		let json = test::manifest_json("1.0", "");
+		test::create_bucket(&shovel.buckets.path("old"), &[("app", &json)]);
+		test::create_bucket(&shovel.buckets.path("other"), &[("lib", &json)]);
+		install(&shovel, "app", &["1.0", "2.0"], "old");
+		install(&shovel, "lib", &["1.0"], "other");
+
+		assert!(matches!(
+			shovel.rename_bucket("old", "other"),
+			Err(Error::Bucket(bucket::Error::Exists))
+		));
+
+		for invalid in ["../new", r"nested\new", ".."] {
+			assert!(matches!(
+				shovel.rename_bucket("old", invalid),
+				Err(Error::Bucket(bucket::Error::InvalidName(_)))
+			));
+		}
+		assert_eq!(shovel.rename_bucket("old", "new").unwrap(), ["app"]);
+
+		assert!(shovel.buckets.open("new").is_ok());
+		assert_eq!(shovel.bucket_apps("new").unwrap(), ["app"]);
+		assert_eq!(shovel.bucket_apps("other").unwrap(), ["lib"]);
+
+		// Old versions are rewritten too, so switching back to them keeps the bucket.
+		let old = shovel.apps.open("app", "1.0").unwrap();
+		assert_eq!(old.metadata().unwrap().bucket, "new");
+	}
+}
@@ -1,3 +1,4 @@
+use std::fs;
 use std::path;
 use std::sync;
 
@@ -7,3 +8,64 @@ static TESTDIR: sync::OnceLock<path::PathBuf> = sync::OnceLock::new();
 pub fn testdir() -> &'static path::Path {
 	TESTDIR.get_or_init(|| path::PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata")))
 }
+
+/// Returns the JSON for a minimal manifest with extra fields.
+/// `extra` must be empty, or a comma-separated list of JSON object members.
+pub fn manifest_json(version: &str, extra: &str) -> String {
+	let sep = if extra.is_empty() { "" } else { "," };
+
+	format!(
+		r#"{{"version": "{version}", "homepage": "https://example.test", "license": "MIT", "url": "https://example.test/app.zip"{sep}{extra}}}"#
+	)
+}
+
+/// Creates a bucket repository in `dir` and commits a set of manifests as (name, json).
+pub fn create_bucket(dir: &path::Path, manifests: &[(&str, &str)]) -> git2::Repository {
+	let repo = git2::Repository::init(dir).unwrap();
+
+	commit_manifests(&repo, manifests, "Initial commit");
+
+	repo
+}
+
+/// Writes and commits a set of manifests as (name, json) to a bucket repository.
+pub fn commit_manifests(
+	repo: &git2::Repository,
+	manifests: &[(&str, &str)],
+	message: &str,
+) -> git2::Oid {
+	let dir = repo.workdir().unwrap();
+	fs::create_dir_all(dir.join("bucket")).unwrap();
+
+	let mut index = repo.index().unwrap();
+
+	for (name, json) in manifests {
+		let relpath = path::Path::new("bucket").join(format!("{name}.json"));
+
+		fs::write(dir.join(&relpath), json).unwrap();
+		index.add_path(&relpath).unwrap();
+	}
+
+	index.write().unwrap();
+
+	commit_index(repo, &mut index, message)
+}
+
+/// Commits the index of a repository on top of HEAD, if any.
+pub fn commit_index(repo: &git2::Repository, index: &mut git2::Index, message: &str) -> git2::Oid {
+	let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+	let signature = git2::Signature::now("Shovel", "shovel@example.test").unwrap();
+
+	let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+	let parents: Vec<_> = parent.iter().collect();
+
+	repo.commit(
+		Some("HEAD"),
+		&signature,
+		&signature,
+		message,
+		&tree,
+		&parents,
+	)
+	.unwrap()
+}
@@ -120,9 +120,84 @@ pub fn url_to_filename(url: &str) -> UrlResult<String> {
 		// i.e. https://example.test/original.txt#/renamed.txt returns renamed.txt instead of original.txt.
 		.and_then(|f| f.strip_prefix('/'))
 		// Otherwise, return the last path segment, if any.
-		.or_else(|| url.path_segments()?.last());
+		.or_else(|| url.path_segments()?.next_back());
 
 	let filename = filename.ok_or(UrlError::FilenameNotFound)?;
 
 	Ok(filename.to_owned())
 }
+
+/// Creates a link at `link` pointing to the directory `original`.
+///
+/// On Windows, this creates a directory junction like Scoop does, as unlike a directory symlink,
+/// it does not require Developer Mode or administrator privileges.
+///
+/// # Arguments
+///
+/// * `original` - The directory to link to. Junctions only support absolute paths.
+/// * `link` - The path of the link.
+pub fn link_dir<P, Q>(original: P, link: Q) -> io::Result<()>
+where
+	P: AsRef<Path>,
+	Q: AsRef<Path>,
+{
+	#[cfg(windows)]
+	{
+		// The standard library cannot create junctions, so this is left to `mklink`, like Scoop.
+		let output = std::process::Command::new("cmd")
+			.args(["/d", "/c", "mklink", "/j"])
+			.arg(link.as_ref())
+			.arg(original.as_ref())
+			.output()?;
+
+		if output.status.success() {
+			Ok(())
+		} else {
+			let message = String::from_utf8_lossy(&output.stderr);
+			Err(io::Error::other(message.trim().to_owned()))
+		}
+	}
+
+	#[cfg(not(windows))]
+	{
+		std::os::unix::fs::symlink(original, link)
+	}
+}
+
+/// Removes a link created by [`link_dir`]. The directory it points to is left untouched.
+///
+/// # Arguments
+///
+/// * `link` - The path of the link.
+pub fn unlink_dir<P>(link: P) -> io::Result<()>
+where
+	P: AsRef<Path>,
+{
+	// Junctions are removed like directories on Windows, but symlinks are removed like files elsewhere.
+	if cfg!(windows) {
+		fs::remove_dir(link)
+	} else {
+		fs::remove_file(link)
+	}
+}
+
+/// Writes a file by writing a temporary file next to it and renaming it into place,
+/// so an interrupted write never leaves the file truncated.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+/// * `contents` - The contents to write.
+pub fn write_atomic<P>(path: P, contents: &[u8]) -> io::Result<()>
+where
+	P: AsRef<Path>,
+{
+	let path = path.as_ref();
+	let dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+	let mut file = tempfile::NamedTempFile::new_in(dir)?;
+	io::Write::write_all(&mut file, contents)?;
+	file.persist(path).map_err(|err| err.error)?;
+
+	Ok(())
+}